@@ -1,5 +1,6 @@
 #pragma once
 
+#include <cstddef>
 #include <cstdint>
 #include <chrono>
 #include <array>
@@ -59,6 +60,22 @@ struct alignas(64) MarketTick {
     }
 };
 
+// Layout shared with Rust (src/layout.rs, tests/ffi_layout.txt)
+static_assert(sizeof(MarketTick) == 448 && alignof(MarketTick) == 64, "MarketTick layout changed");
+static_assert(offsetof(MarketTick, trade_side) == 56, "MarketTick layout changed");
+static_assert(offsetof(MarketTick, asset_id) == 60, "MarketTick layout changed");
+static_assert(offsetof(MarketTick, depth_levels) == 64, "MarketTick layout changed");
+static_assert(offsetof(MarketTick, bid_prices) == 72, "MarketTick layout changed");
+static_assert(offsetof(MarketTick, ask_sizes) == 312, "MarketTick layout changed");
+
+// MarketTick plus local receipt time; MarketTick itself stays unchanged for
+// existing consumers. Mirrors Rust's MarketTickExt
+struct alignas(64) MarketTickExt {
+    MarketTick tick;
+    int64_t recv_timestamp_ns;  // Local capture time, Unix epoch ns (0 = not stamped)
+    int64_t hw_timestamp_ns;    // NIC receive time on the PTP hardware clock (0 = none)
+};
+
 // Order Structure
 
 struct alignas(64) Order {
@@ -80,6 +97,38 @@ struct alignas(64) Order {
           submit_time(now()), venue_id(0), is_active(true) {}
 };
 
+static_assert(sizeof(Order) == 64 && alignof(Order) == 64, "Order layout changed");
+static_assert(offsetof(Order, side) == 12, "Order layout changed");
+static_assert(offsetof(Order, price) == 16, "Order layout changed");
+static_assert(offsetof(Order, submit_time) == 32, "Order layout changed");
+static_assert(offsetof(Order, venue_id) == 40, "Order layout changed");
+
+// Execution Report (gateway -> strategy). Mirrors Rust's ExecutionReport
+
+enum class ExecType : uint8_t {
+    NEW = 0,
+    PARTIAL_FILL = 1,
+    FILL = 2,
+    CANCELLED = 3,
+    REJECTED = 4
+};
+
+struct alignas(64) ExecutionReport {
+    uint64_t order_id;
+    uint64_t exec_id;
+    uint32_t asset_id;
+    Side side;
+    ExecType exec_type;
+    uint8_t venue_id;
+    uint8_t padding;
+    double last_price;        // This fill; 0 for non-fills
+    uint64_t last_quantity;
+    uint64_t cum_quantity;
+    uint64_t leaves_quantity;
+    int64_t transact_time_ns; // Unix epoch ns
+};
+static_assert(sizeof(ExecutionReport) == 64, "ExecutionReport must stay one cache line");
+
 // Quote Pair (Bid/Ask)
 
 struct QuotePair {
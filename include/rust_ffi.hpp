@@ -19,6 +19,25 @@ namespace rust_ffi {
 struct RustMarketMaker;
 struct RustRiskControl;
 struct RustLockFreeQueue;
+struct RustShmRing;
+
+// Mirrors Rust's #[repr(i32)] FfiStatus
+enum class FfiStatus : int32_t {
+    Ok = 0,
+    Full = 1,
+    Empty = 2,
+    NotMapped = 3,
+    BadName = 4,
+    Disconnected = 5,
+    VersionMismatch = 6,
+    Corrupt = 7,
+    NullPointer = 8,
+    InvalidArgument = 9,
+    OsError = 10,
+    InUse = 11,
+    Panic = 12,
+    Unknown = -1,
+};
 
 // ====
 // C++ -> Rust FFI Functions
@@ -42,6 +61,15 @@ extern "C" {
     bool rust_risk_control_check_pre_trade(RustRiskControl* rc,
                                            const Order* order,
                                            int64_t current_position);
+    // InvalidArgument (nothing booked) for a NaN/out-of-range price or a
+    // quantity above INT64_MAX; the new position goes to position_out
+    FfiStatus rust_risk_control_apply_fill(RustRiskControl* rc,
+                                           uint32_t asset_id,
+                                           uint8_t side,
+                                           uint64_t quantity,
+                                           double price,
+                                           int64_t* position_out);
+    double rust_risk_control_realized_pnl(RustRiskControl* rc);
     void rust_risk_control_trigger_kill_switch(RustRiskControl* rc);
     bool rust_risk_control_is_halted(RustRiskControl* rc);
     
@@ -52,6 +80,22 @@ extern "C" {
     bool rust_queue_pop(RustLockFreeQueue* queue, MarketTick* tick);
     bool rust_queue_is_empty(RustLockFreeQueue* queue);
     size_t rust_queue_size(RustLockFreeQueue* queue);
+    FfiStatus rust_queue_push_ex(RustLockFreeQueue* queue, const MarketTick* tick);
+    FfiStatus rust_queue_pop_ex(RustLockFreeQueue* queue, MarketTick* tick);
+    
+    // Rust shared-memory ring (Linux)
+    FfiStatus rust_shm_create(const char* name, size_t capacity, RustShmRing** out);
+    FfiStatus rust_shm_attach(const char* name, RustShmRing** out);
+    void rust_shm_free(RustShmRing* ring);
+    FfiStatus rust_shm_write_tick(RustShmRing* ring, const MarketTick* tick);
+    FfiStatus rust_shm_read_tick(RustShmRing* ring, MarketTick* tick);
+    
+    // Message for the last non-Ok FfiStatus on this thread ("" if none)
+    const char* rust_last_error();
+    void rust_clear_last_error();
+    
+    // True once any rust_* call has caught a panic; risk controls then stay halted
+    bool rust_ffi_panicked();
     
     // Benchmarking
     void rust_benchmark_queue_throughput();
@@ -112,6 +156,15 @@ public:
         return rust_risk_control_check_pre_trade(handle_, &order, current_position);
     }
     
+    // position_out is left untouched unless the fill was booked (FfiStatus::Ok)
+    FfiStatus apply_fill(uint32_t asset_id, uint8_t side, uint64_t quantity, double price, int64_t& position_out) const {
+        return rust_risk_control_apply_fill(handle_, asset_id, side, quantity, price, &position_out);
+    }
+    
+    double realized_pnl() const {
+        return rust_risk_control_realized_pnl(handle_);
+    }
+    
     void trigger_kill_switch() const {
         rust_risk_control_trigger_kill_switch(handle_);
     }
@@ -128,16 +181,64 @@ private:
 // Rust -> C++ FFI Functions (exported from C++)
 // ====
 
+// Predictor input layout shared with Rust's MarketTick::feature_vector:
+// [0] mid, [1] microprice, [2] top-of-book imbalance, [3] spread,
+// [4] top-3 bid depth, [5] top-3 ask depth, [6] top-3 depth imbalance,
+// [7] microprice - mid, [8] bid_size, [9] ask_size
+constexpr size_t HFT_FEATURE_COUNT = 10;
+
+// Execution event callbacks registered by Rust's ExecSink. Copy the struct;
+// callbacks may be invoked from any thread and return false if the event was dropped.
+// ctx owns a reference to Rust-side state: once a registration is replaced or
+// unregistered, wait for callbacks already running through it to return, then
+// call release(ctx) exactly once. Never call through a vtable after releasing it
+typedef bool (*ExecCallback)(void* ctx, const ExecutionReport* report);
+
+struct ExecCallbacks {
+    void* ctx;
+    ExecCallback on_ack;
+    ExecCallback on_fill;     // Partial or full; Rust derives which from leaves_quantity
+    ExecCallback on_cancel;
+    ExecCallback on_reject;
+    void (*release)(void* ctx);
+};
+
 extern "C" {
-    // Shared memory operations
+    // Shared memory operations (legacy; Rust only calls the _ex variants)
     bool shm_write_tick(const char* name, const MarketTick* tick);
     bool shm_read_tick(const char* name, MarketTick* tick);
     
-    // Hawkes engine integration
+    // Status-code variants: return a FfiStatus value as int32_t
+    int32_t shm_write_tick_ex(const char* name, const MarketTick* tick);
+    int32_t shm_read_tick_ex(const char* name, MarketTick* tick);
+    
+    // Batch variants: return the number of ticks read/written
+    uint32_t shm_read_ticks(const char* name, MarketTick* buf, uint32_t max);
+    uint32_t shm_write_ticks(const char* name, const MarketTick* ticks, uint32_t count);
+    
+    // Execution events: replace / clear the gateway's registered callbacks
+    void cpp_gateway_register_callbacks(const ExecCallbacks* callbacks);
+    void cpp_gateway_unregister_callbacks();
+    
+    // Hawkes engine integration (Rust's HawkesEngine owns the pointer)
+    void* cpp_hawkes_create();
     void cpp_hawkes_update(void* engine, const MarketTick* tick);
+    void cpp_hawkes_destroy(void* engine);
     
-    // FPGA inference integration
+    // FPGA inference integration (Rust's FpgaPredictor owns the pointer)
+    // `features` follows MarketTick::feature_vector (HFT_FEATURE_COUNT doubles)
+    void* cpp_fpga_create(uint32_t num_features, uint32_t num_outputs);
     void cpp_fpga_predict(void* engine, const double* features, double* output);
+    void cpp_fpga_predict_n(void* engine, const double* features, uint32_t num_features,
+                            double* output, uint32_t num_outputs);
+    void cpp_fpga_destroy(void* engine);
+    
+    // Async inference: returns false if the card can't take the request.
+    // `done` is called exactly once per accepted request from a single
+    // completion thread, and never after cpp_fpga_destroy returns
+    typedef void (*FpgaDoneFn)(void* ctx, uint64_t request_id, const double* output, uint32_t num_outputs);
+    bool cpp_fpga_submit(void* engine, uint64_t request_id, const double* features, uint32_t num_features,
+                         FpgaDoneFn done, void* ctx);
 }
 
 } // namespace rust_ffi
@@ -26,10 +26,16 @@ struct alignas(64) SharedMemoryHeader {
     uint64_t capacity;
     uint64_t element_size;
     char name[64];
+    uint64_t flags;                       // Rust-side options (checksums); 0 from C++
+    uint32_t magic;                       // 0 from C++; Rust checks it when set
+    uint32_t version;
+    std::atomic<uint32_t> creator_beat;   // Bumped by the creating process
+    std::atomic<uint32_t> attacher_beat;  // Bumped by the attaching process
     
     SharedMemoryHeader() 
         : write_seq(0), read_seq(0), is_initialized(false), 
-          capacity(Capacity), element_size(sizeof(T)) {
+          capacity(Capacity), element_size(sizeof(T)), flags(0), magic(0),
+          version(0), creator_beat(0), attacher_beat(0) {
         std::memset(name, 0, sizeof(name));
     }
 };
@@ -103,6 +109,7 @@ public:
             reinterpret_cast<char*>(mapped_region_) + 
             sizeof(SharedMemoryHeader<T, Capacity>));
         
+        created_ = create;
         if (create) {
             new (header_) SharedMemoryHeader<T, Capacity>();
             std::strncpy(header_->name, segment_name.c_str(), 
@@ -187,7 +194,17 @@ public:
         return size() >= Capacity;
     }
     
+    // 
+    // Liveness: bump our word regularly; the Rust side's peer_alive()
+    // watches it
+    // 
+    void heartbeat() {
+        auto& beat = created_ ? header_->creator_beat : header_->attacher_beat;
+        beat.fetch_add(1, std::memory_order_release);
+    }
+    
 private:
+    bool created_ = false;
     int fd_;
     void* mapped_region_;
     size_t total_size_;
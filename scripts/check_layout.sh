@@ -0,0 +1,15 @@
+#!/bin/bash
+
+# Diff the Rust and C++ views of the shared FFI structs against
+# tests/ffi_layout.txt. Run from the repository root
+
+set -e
+
+mkdir -p build
+g++ -std=c++17 -Iinclude tests/layout_dump.cpp -o build/layout_dump
+./build/layout_dump > build/ffi_layout_cpp.txt
+HFT_LAYOUT_DUMP=build/ffi_layout_rust.txt cargo test --quiet --test layout_dump
+
+diff -u tests/ffi_layout.txt build/ffi_layout_cpp.txt
+diff -u tests/ffi_layout.txt build/ffi_layout_rust.txt
+echo "FFI layout matches"
@@ -0,0 +1,123 @@
+// CPU affinity and real-time scheduling helpers for the hot threads
+// Linux only; other platforms return AffinityError::Unsupported
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AffinityError {
+    /// Platform has no affinity/scheduling support wired up
+    Unsupported,
+    /// Core index beyond what the affinity mask can express
+    InvalidCore(usize),
+    /// Priority outside the SCHED_FIFO range (1..=99)
+    InvalidPriority(i32),
+    /// Error code returned by the OS call
+    Os(i32),
+}
+
+impl fmt::Display for AffinityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AffinityError::Unsupported => write!(f, "thread affinity not supported on this platform"),
+            AffinityError::InvalidCore(core) => write!(f, "invalid core index {}", core),
+            AffinityError::InvalidPriority(prio) => write!(f, "invalid real-time priority {}", prio),
+            AffinityError::Os(code) => write!(f, "OS error {}", code),
+        }
+    }
+}
+
+impl std::error::Error for AffinityError {}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    use std::os::raw::{c_int, c_ulong};
+    
+    pub const CPU_SETSIZE: usize = 1024;
+    pub const SCHED_FIFO: c_int = 1;
+    
+    // glibc cpu_set_t: 1024-bit mask
+    #[repr(C)]
+    pub struct CpuSet {
+        pub bits: [u64; CPU_SETSIZE / 64],
+    }
+    
+    #[repr(C)]
+    pub struct SchedParam {
+        pub sched_priority: c_int,
+    }
+    
+    extern "C" {
+        pub fn pthread_self() -> c_ulong;
+        pub fn pthread_setaffinity_np(thread: c_ulong, cpusetsize: usize, cpuset: *const CpuSet) -> c_int;
+        pub fn pthread_setschedparam(thread: c_ulong, policy: c_int, param: *const SchedParam) -> c_int;
+    }
+}
+
+/// Pin the calling thread to a single core
+#[cfg(target_os = "linux")]
+pub fn pin_current_thread_to(core: usize) -> Result<(), AffinityError> {
+    if core >= sys::CPU_SETSIZE {
+        return Err(AffinityError::InvalidCore(core));
+    }
+    
+    let mut set = sys::CpuSet { bits: [0; sys::CPU_SETSIZE / 64] };
+    set.bits[core / 64] |= 1u64 << (core % 64);
+    
+    let rc = unsafe {
+        sys::pthread_setaffinity_np(sys::pthread_self(), std::mem::size_of::<sys::CpuSet>(), &set)
+    };
+    match rc {
+        0 => Ok(()),
+        22 => Err(AffinityError::InvalidCore(core)),  // EINVAL: core not present/allowed
+        code => Err(AffinityError::Os(code)),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pin_current_thread_to(_core: usize) -> Result<(), AffinityError> {
+    Err(AffinityError::Unsupported)
+}
+
+/// Switch the calling thread to SCHED_FIFO at `prio` (needs CAP_SYS_NICE)
+#[cfg(target_os = "linux")]
+pub fn set_realtime_priority(prio: i32) -> Result<(), AffinityError> {
+    if !(1..=99).contains(&prio) {
+        return Err(AffinityError::InvalidPriority(prio));
+    }
+    
+    let param = sys::SchedParam { sched_priority: prio };
+    let rc = unsafe { sys::pthread_setschedparam(sys::pthread_self(), sys::SCHED_FIFO, &param) };
+    match rc {
+        0 => Ok(()),
+        code => Err(AffinityError::Os(code)),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_realtime_priority(_prio: i32) -> Result<(), AffinityError> {
+    Err(AffinityError::Unsupported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_pin_to_core_zero() {
+        // Pin a scratch thread so the test harness thread keeps its mask
+        let result = std::thread::spawn(|| pin_current_thread_to(0)).join().unwrap();
+        
+        #[cfg(target_os = "linux")]
+        assert_eq!(result, Ok(()));
+        #[cfg(not(target_os = "linux"))]
+        assert_eq!(result, Err(AffinityError::Unsupported));
+    }
+    
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_invalid_arguments_rejected() {
+        assert_eq!(pin_current_thread_to(4096), Err(AffinityError::InvalidCore(4096)));
+        assert_eq!(set_realtime_priority(0), Err(AffinityError::InvalidPriority(0)));
+        assert_eq!(set_realtime_priority(100), Err(AffinityError::InvalidPriority(100)));
+    }
+}
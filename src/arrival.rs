@@ -0,0 +1,91 @@
+// Live fill arrival-rate estimate for the quoting model
+// Exponentially decayed event count: each fill adds 1/tau and the estimate
+// decays by e^(-dt/tau) between fills, so a steady cadence of one fill every
+// `d` seconds settles at roughly 1/d fills per second (exactly
+// (1/tau) / (1 - e^(-d/tau))), and bursts push it up immediately.
+
+/// Exponentially weighted fill rate in fills per second.
+#[derive(Debug, Clone, Copy)]
+pub struct ArrivalRateEstimator {
+    rate: f64,
+    tau_secs: f64,
+    last_fill_ns: Option<i64>,
+}
+
+impl ArrivalRateEstimator {
+    /// Start from `prior_rate` (fills/sec), which fades with time constant `tau_secs`
+    pub fn new(prior_rate: f64, tau_secs: f64) -> Self {
+        assert!(prior_rate > 0.0, "Prior arrival rate must be positive");
+        assert!(tau_secs > 0.0, "Time constant must be positive");
+        Self { rate: prior_rate, tau_secs, last_fill_ns: None }
+    }
+    
+    /// Count a fill observed at `now_ns`. The first fill only anchors the
+    /// clock, so the estimate stays at the prior until a second one arrives
+    pub fn record_fill(&mut self, now_ns: i64) {
+        if let Some(last) = self.last_fill_ns {
+            // Out-of-order stamps count as simultaneous
+            let dt = (now_ns - last).max(0) as f64 / 1e9;
+            self.rate = self.rate * (-dt / self.tau_secs).exp() + 1.0 / self.tau_secs;
+        }
+        self.last_fill_ns = Some(self.last_fill_ns.map_or(now_ns, |last| last.max(now_ns)));
+    }
+    
+    /// Estimate as of the last fill
+    #[inline(always)]
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+    
+    /// Estimate decayed to `now_ns`, for when fills have stopped arriving
+    pub fn rate_at(&self, now_ns: i64) -> f64 {
+        match self.last_fill_ns {
+            Some(last) => self.rate * (-((now_ns - last).max(0) as f64 / 1e9) / self.tau_secs).exp(),
+            None => self.rate,
+        }
+    }
+}
+
+impl Default for ArrivalRateEstimator {
+    /// 10 fills/sec prior (the old fixed rate) with a 5s time constant
+    fn default() -> Self {
+        Self::new(10.0, 5.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_steady_cadence_converges() {
+        let mut est = ArrivalRateEstimator::new(1.0, 2.0);
+        est.record_fill(0);
+        assert_eq!(est.rate(), 1.0);  // Cold start: still the prior
+        
+        // 50 fills/sec for 20 seconds
+        for i in 1..=1_000i64 {
+            est.record_fill(i * 20_000_000);
+        }
+        assert!((est.rate() - 50.0).abs() < 1.0, "rate {}", est.rate());
+        
+        // Silence decays the estimate
+        let later = 1_000 * 20_000_000 + 2_000_000_000;
+        assert!(est.rate_at(later) < est.rate() * 0.4);
+    }
+    
+    #[test]
+    fn test_burst_raises_estimate() {
+        let mut est = ArrivalRateEstimator::new(5.0, 1.0);
+        for i in 0..20i64 {
+            est.record_fill(i * 200_000_000);  // 5/sec
+        }
+        let steady = est.rate();
+        
+        let start = 20 * 200_000_000;
+        for i in 0..10i64 {
+            est.record_fill(start + i * 1_000_000);  // 10 fills in 10ms
+        }
+        assert!(est.rate() > steady + 8.0, "{} -> {}", steady, est.rate());
+    }
+}
@@ -0,0 +1,166 @@
+// Per-venue circuit breaker
+// Repeated rejects/timeouts on one venue take only that venue out of routing;
+// the global kill switch in RiskControl stays reserved for engine-wide problems
+
+use std::collections::HashMap;
+
+use crate::HiResTimer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    /// Normal operation
+    Closed,
+    /// Tripped; no orders until the cooldown elapses
+    Open,
+    /// Cooldown over; the next result decides between Closed and Open
+    HalfOpen,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BreakerConfig {
+    /// Consecutive failures that trip the breaker
+    pub failure_threshold: u32,
+    pub cooldown_ns: i64,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown_ns: 1_000_000_000,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Venue {
+    consecutive_failures: u32,
+    opened_at_ns: Option<i64>,
+}
+
+/// Closed/Open/HalfOpen breaker per `venue_id`; venues never seen are Closed.
+///
+/// The `_at` variants take an explicit clock for replay and tests; the plain
+/// ones read `HiResTimer::unix_now_ns()`.
+pub struct VenueBreaker {
+    config: BreakerConfig,
+    venues: HashMap<u8, Venue>,
+}
+
+impl VenueBreaker {
+    pub fn new(config: BreakerConfig) -> Self {
+        Self {
+            config,
+            venues: HashMap::new(),
+        }
+    }
+    
+    pub fn record_failure(&mut self, venue_id: u8) -> BreakerState {
+        self.record_failure_at(venue_id, HiResTimer::unix_now_ns())
+    }
+    
+    pub fn record_success(&mut self, venue_id: u8) -> BreakerState {
+        self.record_success_at(venue_id, HiResTimer::unix_now_ns())
+    }
+    
+    pub fn is_available(&self, venue_id: u8) -> bool {
+        self.is_available_at(venue_id, HiResTimer::unix_now_ns())
+    }
+    
+    pub fn record_failure_at(&mut self, venue_id: u8, now_ns: i64) -> BreakerState {
+        let state = self.state_at(venue_id, now_ns);
+        let threshold = self.config.failure_threshold;
+        let venue = self.venues.entry(venue_id).or_default();
+        venue.consecutive_failures = venue.consecutive_failures.saturating_add(1);
+        
+        // A failed probe re-opens at once and restarts the cooldown
+        if state == BreakerState::HalfOpen || venue.consecutive_failures >= threshold {
+            venue.opened_at_ns = Some(now_ns);
+            return BreakerState::Open;
+        }
+        state
+    }
+    
+    pub fn record_success_at(&mut self, venue_id: u8, now_ns: i64) -> BreakerState {
+        let state = self.state_at(venue_id, now_ns);
+        let venue = self.venues.entry(venue_id).or_default();
+        
+        // Late acks for orders sent before the trip don't close an Open breaker
+        if state == BreakerState::Open {
+            return state;
+        }
+        venue.consecutive_failures = 0;
+        venue.opened_at_ns = None;
+        BreakerState::Closed
+    }
+    
+    pub fn state_at(&self, venue_id: u8, now_ns: i64) -> BreakerState {
+        match self.venues.get(&venue_id).and_then(|v| v.opened_at_ns) {
+            None => BreakerState::Closed,
+            Some(opened) if now_ns.saturating_sub(opened) >= self.config.cooldown_ns => BreakerState::HalfOpen,
+            Some(_) => BreakerState::Open,
+        }
+    }
+    
+    /// Orders may be routed to the venue (Closed or HalfOpen)
+    #[inline(always)]
+    pub fn is_available_at(&self, venue_id: u8, now_ns: i64) -> bool {
+        self.state_at(venue_id, now_ns) != BreakerState::Open
+    }
+    
+    pub fn consecutive_failures(&self, venue_id: u8) -> u32 {
+        self.venues.get(&venue_id).map_or(0, |v| v.consecutive_failures)
+    }
+}
+
+impl Default for VenueBreaker {
+    fn default() -> Self {
+        Self::new(BreakerConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const COOLDOWN: i64 = 1_000;
+    
+    fn breaker() -> VenueBreaker {
+        VenueBreaker::new(BreakerConfig { failure_threshold: 3, cooldown_ns: COOLDOWN })
+    }
+    
+    #[test]
+    fn test_trip_half_open_and_close() {
+        let mut breaker = breaker();
+        
+        assert_eq!(breaker.record_failure_at(1, 0), BreakerState::Closed);
+        assert_eq!(breaker.record_failure_at(1, 10), BreakerState::Closed);
+        assert_eq!(breaker.record_failure_at(1, 20), BreakerState::Open);
+        assert!(!breaker.is_available_at(1, 20));
+        assert!(!breaker.is_available_at(1, 20 + COOLDOWN - 1));
+        // Other venues are unaffected
+        assert!(breaker.is_available_at(2, 20));
+        // A stale success can't close it early
+        assert_eq!(breaker.record_success_at(1, 500), BreakerState::Open);
+        
+        assert_eq!(breaker.state_at(1, 20 + COOLDOWN), BreakerState::HalfOpen);
+        assert!(breaker.is_available_at(1, 20 + COOLDOWN));
+        
+        assert_eq!(breaker.record_success_at(1, 20 + COOLDOWN), BreakerState::Closed);
+        assert_eq!(breaker.consecutive_failures(1), 0);
+        assert!(breaker.is_available_at(1, 20 + COOLDOWN));
+    }
+    
+    #[test]
+    fn test_failed_probe_reopens() {
+        let mut breaker = breaker();
+        for t in 0..3 {
+            breaker.record_failure_at(4, t);
+        }
+        assert_eq!(breaker.state_at(4, 2 + COOLDOWN), BreakerState::HalfOpen);
+        
+        assert_eq!(breaker.record_failure_at(4, 2 + COOLDOWN), BreakerState::Open);
+        assert!(!breaker.is_available_at(4, 2 + COOLDOWN + 1));
+        assert!(breaker.is_available_at(4, 2 + 2 * COOLDOWN));
+    }
+}
@@ -0,0 +1,292 @@
+// Single-producer broadcast ring
+// Every consumer sees every item; the producer never waits and overwrites the
+// oldest slot, so a consumer that falls a full ring behind is told how many
+// items it missed instead of silently skipping them.
+//
+// Slots are seqlocks: the producer marks a slot odd while writing item `w`
+// and even (`2w + 2`) when done; readers copy the slot optimistically and
+// keep the copy only if the sequence was stable around it.
+
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{fence, AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::wait::WaitStrategy;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvError {
+    /// Nothing new published yet
+    Empty,
+    /// This many items were overwritten before being read; the cursor has
+    /// skipped to the oldest item still in the ring
+    Lagged(u64),
+}
+
+struct BroadcastSlot<T> {
+    seq: AtomicU64,
+    data: UnsafeCell<MaybeUninit<T>>,
+}
+
+pub struct BroadcastRing<T, const CAPACITY: usize> {
+    slots: Box<[BroadcastSlot<T>]>,
+    tail: AtomicU64,  // Items published so far
+    producer_alive: AtomicBool,
+}
+
+unsafe impl<T: Send, const CAPACITY: usize> Send for BroadcastRing<T, CAPACITY> {}
+unsafe impl<T: Send, const CAPACITY: usize> Sync for BroadcastRing<T, CAPACITY> {}
+
+impl<T: Copy, const CAPACITY: usize> BroadcastRing<T, CAPACITY> {
+    /// New ring and its only producer; consumers come from `Producer::subscribe`
+    pub fn channel() -> Producer<T, CAPACITY> {
+        assert!(CAPACITY.is_power_of_two(), "Capacity must be power of 2");
+        let ring = Arc::new(Self {
+            slots: (0..CAPACITY)
+                .map(|_| BroadcastSlot { seq: AtomicU64::new(0), data: UnsafeCell::new(MaybeUninit::uninit()) })
+                .collect(),
+            tail: AtomicU64::new(0),
+            producer_alive: AtomicBool::new(true),
+        });
+        Producer { ring, next: 0 }
+    }
+    
+    #[inline(always)]
+    fn slot(&self, index: u64) -> &BroadcastSlot<T> {
+        &self.slots[(index as usize) & (CAPACITY - 1)]
+    }
+}
+
+// Producer
+
+pub struct Producer<T, const CAPACITY: usize> {
+    ring: Arc<BroadcastRing<T, CAPACITY>>,
+    next: u64,
+}
+
+impl<T: Copy, const CAPACITY: usize> Producer<T, CAPACITY> {
+    /// Publish to every consumer; never blocks, overwrites the oldest item
+    #[inline(always)]
+    pub fn publish(&mut self, item: T) {
+        let w = self.next;
+        let slot = self.ring.slot(w);
+        
+        slot.seq.store(2 * w + 1, Ordering::Relaxed);
+        // Odd marker must be visible before any byte of the new data
+        fence(Ordering::Release);
+        unsafe { std::ptr::write_volatile(slot.data.get(), MaybeUninit::new(item)) };
+        slot.seq.store(2 * w + 2, Ordering::Release);
+        
+        self.next = w + 1;
+        self.ring.tail.store(self.next, Ordering::Release);
+    }
+    
+    /// New consumer that starts with the next published item
+    pub fn subscribe(&self) -> Consumer<T, CAPACITY> {
+        Consumer { ring: Arc::clone(&self.ring), cursor: self.next }
+    }
+    
+    pub fn published(&self) -> u64 {
+        self.next
+    }
+}
+
+impl<T, const CAPACITY: usize> Drop for Producer<T, CAPACITY> {
+    fn drop(&mut self) {
+        self.ring.producer_alive.store(false, Ordering::Release);
+    }
+}
+
+// Consumer
+
+pub struct Consumer<T, const CAPACITY: usize> {
+    ring: Arc<BroadcastRing<T, CAPACITY>>,
+    cursor: u64,
+}
+
+impl<T: Copy, const CAPACITY: usize> Consumer<T, CAPACITY> {
+    /// Next item for this consumer (non-blocking)
+    #[inline(always)]
+    pub fn recv(&mut self) -> Result<T, RecvError> {
+        let expected = 2 * self.cursor + 2;
+        let slot = self.ring.slot(self.cursor);
+        
+        let before = slot.seq.load(Ordering::Acquire);
+        if before == expected {
+            let copy = unsafe { std::ptr::read_volatile(slot.data.get()) };
+            fence(Ordering::Acquire);
+            if slot.seq.load(Ordering::Relaxed) == expected {
+                self.cursor += 1;
+                // Sequence was stable around the copy, so it is a whole published item
+                return Ok(unsafe { copy.assume_init() });
+            }
+        } else if before < expected {
+            // Slot still holds an older lap (or item `cursor` is mid-write)
+            return Err(RecvError::Empty);
+        }
+        
+        // Overwritten while we were behind: skip to the oldest item left
+        let tail = self.ring.tail.load(Ordering::Acquire);
+        let oldest = tail.saturating_sub(CAPACITY as u64).max(self.cursor + 1);
+        let missed = oldest - self.cursor;
+        self.cursor = oldest;
+        Err(RecvError::Lagged(missed))
+    }
+    
+    /// Block until the next item (or a `Lagged` gap) is available, backing
+    /// off per `strategy`; never returns `Empty`
+    #[inline(always)]
+    pub fn recv_wait(&mut self, strategy: WaitStrategy) -> Result<T, RecvError> {
+        strategy.wait(|| self.poll())
+    }
+    
+    /// As `recv_wait`, but `Err(Empty)` once `timeout` passes
+    pub fn recv_wait_timeout(&mut self, strategy: WaitStrategy, timeout: Duration) -> Result<T, RecvError> {
+        strategy.wait_timeout(timeout, || self.poll()).unwrap_or(Err(RecvError::Empty))
+    }
+    
+    #[inline(always)]
+    fn poll(&mut self) -> Option<Result<T, RecvError>> {
+        match self.recv() {
+            Err(RecvError::Empty) => None,
+            result => Some(result),
+        }
+    }
+    
+    /// Items published but not yet received (may exceed the capacity when lagging)
+    pub fn pending(&self) -> u64 {
+        self.ring.tail.load(Ordering::Acquire).saturating_sub(self.cursor)
+    }
+    
+    /// True while the producer is alive
+    pub fn is_connected(&self) -> bool {
+        self.ring.producer_alive.load(Ordering::Acquire)
+    }
+}
+
+impl<T, const CAPACITY: usize> Clone for Consumer<T, CAPACITY> {
+    /// Independent consumer at the same position
+    fn clone(&self) -> Self {
+        Self { ring: Arc::clone(&self.ring), cursor: self.cursor }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_every_consumer_sees_every_item() {
+        let mut producer = BroadcastRing::<u64, 8>::channel();
+        let mut a = producer.subscribe();
+        let mut b = producer.subscribe();
+        assert_eq!(a.recv(), Err(RecvError::Empty));
+        
+        for i in 0..5 {
+            producer.publish(i);
+        }
+        let got_a: Vec<u64> = std::iter::from_fn(|| a.recv().ok()).collect();
+        let got_b: Vec<u64> = std::iter::from_fn(|| b.recv().ok()).collect();
+        assert_eq!(got_a, vec![0, 1, 2, 3, 4]);
+        assert_eq!(got_a, got_b);
+        assert_eq!(a.recv(), Err(RecvError::Empty));
+        
+        drop(producer);
+        assert!(!a.is_connected());
+    }
+    
+    #[test]
+    fn test_lagging_consumer_reports_missed_items() {
+        let mut producer = BroadcastRing::<u64, 4>::channel();
+        let mut fast = producer.subscribe();
+        let mut slow = producer.subscribe();
+        
+        for i in 0..10 {
+            producer.publish(i);
+            assert_eq!(fast.recv(), Ok(i));
+        }
+        assert_eq!(slow.pending(), 10);
+        
+        // Items 0..6 are gone; 6..10 are still in the ring
+        assert_eq!(slow.recv(), Err(RecvError::Lagged(6)));
+        let rest: Vec<u64> = std::iter::from_fn(|| slow.recv().ok()).collect();
+        assert_eq!(rest, vec![6, 7, 8, 9]);
+    }
+    
+    #[test]
+    fn test_tick_fan_out_with_wait() {
+        use crate::MarketTick;
+        
+        let mut producer = BroadcastRing::<MarketTick, 1024>::channel();
+        let consumers: Vec<_> = (0..4).map(|_| producer.subscribe()).collect();
+        let strategy = WaitStrategy::SpinThenYield { spins: 16 };
+        
+        std::thread::scope(|s| {
+            let handles: Vec<_> = consumers
+                .into_iter()
+                .map(|mut consumer| {
+                    s.spawn(move || {
+                        let (mut received, mut missed) = (0u64, 0u64);
+                        while received + missed < 500 {
+                            match consumer.recv_wait(strategy) {
+                                Ok(tick) => {
+                                    assert_eq!(tick.timestamp_ns as u64, received + missed);
+                                    received += 1;
+                                }
+                                Err(RecvError::Lagged(n)) => missed += n,
+                                Err(RecvError::Empty) => unreachable!("recv_wait never reports Empty"),
+                            }
+                        }
+                        assert_eq!(
+                            consumer.recv_wait_timeout(strategy, Duration::from_millis(1)).err(),
+                            Some(RecvError::Empty)
+                        );
+                        received
+                    })
+                })
+                .collect();
+            
+            for i in 0..500 {
+                producer.publish(MarketTick { timestamp_ns: i, ..Default::default() });
+            }
+            // The ring holds every tick, so nobody can fall behind
+            for handle in handles {
+                assert_eq!(handle.join().unwrap(), 500);
+            }
+        });
+    }
+    
+    #[test]
+    fn test_concurrent_consumers_stay_in_order() {
+        let mut producer = BroadcastRing::<u64, 64>::channel();
+        let consumers: Vec<_> = (0..2).map(|_| producer.subscribe()).collect();
+        
+        std::thread::scope(|s| {
+            for mut consumer in consumers {
+                s.spawn(move || {
+                    let mut last = None;
+                    let mut seen = 0u64;
+                    while seen < 5_000 {
+                        match consumer.recv() {
+                            Ok(item) => {
+                                // Strictly increasing, gaps only after a Lagged
+                                assert!(last.is_none_or(|l| item == l + 1), "{:?} -> {}", last, item);
+                                last = Some(item);
+                                seen = item + 1;
+                            }
+                            Err(RecvError::Lagged(_)) => last = None,
+                            Err(RecvError::Empty) => std::thread::yield_now(),
+                        }
+                    }
+                });
+            }
+            for i in 0..5_000 {
+                producer.publish(i);
+                if i % 32 == 0 {
+                    std::thread::yield_now();
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,103 @@
+// Latency budgets for market data
+// A tick that waited too long in a queue is worse than no tick: acting on it
+// quotes against a market that has already moved. `now_ns` must come from the
+// same clock that stamped `timestamp_ns`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{LockFreeSPSC, MarketTick};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyBudget {
+    budget_ns: i64,
+}
+
+impl LatencyBudget {
+    pub fn new(budget_ns: i64) -> Self {
+        assert!(budget_ns >= 0, "Budget must not be negative");
+        Self { budget_ns }
+    }
+    
+    /// Older than the budget; a tick exactly at the budget is still fresh
+    #[inline(always)]
+    pub fn is_expired(&self, tick: &MarketTick, now_ns: i64) -> bool {
+        now_ns.saturating_sub(tick.timestamp_ns) > self.budget_ns
+    }
+    
+    pub fn budget_ns(&self) -> i64 {
+        self.budget_ns
+    }
+}
+
+/// Consumer side of a tick ring that discards stale ticks; must be the ring's only consumer
+pub struct FreshConsumer<'a, const CAPACITY: usize> {
+    queue: &'a LockFreeSPSC<MarketTick, CAPACITY>,
+    budget: LatencyBudget,
+    stale: AtomicU64,
+}
+
+impl<'a, const CAPACITY: usize> FreshConsumer<'a, CAPACITY> {
+    pub fn new(queue: &'a LockFreeSPSC<MarketTick, CAPACITY>, budget: LatencyBudget) -> Self {
+        Self {
+            queue,
+            budget,
+            stale: AtomicU64::new(0),
+        }
+    }
+    
+    /// Pop until a tick within budget is found or the ring is empty
+    #[inline(always)]
+    pub fn pop_fresh(&self, now_ns: i64) -> Option<MarketTick> {
+        while let Some(tick) = self.queue.pop() {
+            if !self.budget.is_expired(&tick, now_ns) {
+                return Some(tick);
+            }
+            self.stale.fetch_add(1, Ordering::Relaxed);
+        }
+        None
+    }
+    
+    /// Ticks discarded for exceeding the budget
+    pub fn discarded_stale(&self) -> u64 {
+        self.stale.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn tick(timestamp_ns: i64) -> MarketTick {
+        MarketTick { timestamp_ns, ..Default::default() }
+    }
+    
+    #[test]
+    fn test_budget_boundary() {
+        let budget = LatencyBudget::new(500);
+        assert!(!budget.is_expired(&tick(1_000), 1_500));
+        assert!(budget.is_expired(&tick(1_000), 1_501));
+        // Clock skew (tick stamped after now) is never stale
+        assert!(!budget.is_expired(&tick(2_000), 1_000));
+    }
+    
+    #[test]
+    fn test_pop_fresh_skips_expired() {
+        let queue: LockFreeSPSC<MarketTick, 16> = LockFreeSPSC::new();
+        let consumer = FreshConsumer::new(&queue, LatencyBudget::new(1_000));
+        let now = 10_000;
+        
+        for ts in [1_000, 2_000, 9_500, 3_000, 9_900] {
+            queue.push(tick(ts));
+        }
+        
+        assert_eq!(consumer.pop_fresh(now).map(|t| t.timestamp_ns), Some(9_500));
+        assert_eq!(consumer.discarded_stale(), 2);
+        assert_eq!(consumer.pop_fresh(now).map(|t| t.timestamp_ns), Some(9_900));
+        assert_eq!(consumer.discarded_stale(), 3);
+        assert!(consumer.pop_fresh(now).is_none());
+        
+        queue.push(tick(1));
+        assert!(consumer.pop_fresh(now).is_none());
+        assert_eq!(consumer.discarded_stale(), 4);
+    }
+}
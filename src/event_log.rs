@@ -0,0 +1,204 @@
+// Lock-free engine event log for post-mortem tracing
+// The hot thread pushes fixed-size events into an SPSC ring; a background
+// thread drains them. Pushing never blocks or allocates: when the ring is
+// full the event is dropped and counted.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::{HiResTimer, KillReason, LockFreeSPSC, RejectReason};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EngineEvent {
+    QuoteGenerated { asset_id: u32, bid: f64, ask: f64 },
+    OrderSubmitted { order_id: u64 },
+    OrderRejected { order_id: u64, reason: RejectReason },
+    Fill { order_id: u64, quantity: u64, price: f64 },
+    KillSwitch(KillReason),
+}
+
+impl Default for EngineEvent {
+    // Only used to initialise empty ring slots
+    fn default() -> Self {
+        EngineEvent::OrderSubmitted { order_id: 0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LoggedEvent {
+    pub timestamp_ns: i64,
+    pub event: EngineEvent,
+}
+
+// Event Log
+
+pub struct EventLog<const CAPACITY: usize> {
+    ring: LockFreeSPSC<LoggedEvent, CAPACITY>,
+    dropped: AtomicU64,
+}
+
+impl<const CAPACITY: usize> EventLog<CAPACITY> {
+    pub fn new() -> Self {
+        Self {
+            ring: LockFreeSPSC::new(),
+            dropped: AtomicU64::new(0),
+        }
+    }
+    
+    /// Producer: stamp with `HiResTimer::now_ns()` and push
+    #[inline(always)]
+    pub fn record(&self, event: EngineEvent) -> bool {
+        self.record_at(HiResTimer::now_ns(), event)
+    }
+    
+    /// Producer: push with a caller-supplied timestamp; false if dropped
+    #[inline(always)]
+    pub fn record_at(&self, timestamp_ns: i64, event: EngineEvent) -> bool {
+        let pushed = self.ring.push(LoggedEvent { timestamp_ns, event });
+        if !pushed {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        pushed
+    }
+    
+    /// Consumer: next event in push order
+    #[inline(always)]
+    pub fn pop(&self) -> Option<LoggedEvent> {
+        self.ring.pop()
+    }
+    
+    /// Consumer: hand every queued event to `sink`, returning how many
+    pub fn drain<F: FnMut(LoggedEvent)>(&self, mut sink: F) -> usize {
+        let mut drained = 0;
+        while let Some(event) = self.ring.pop() {
+            sink(event);
+            drained += 1;
+        }
+        drained
+    }
+    
+    /// Events lost because the ring was full
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<const CAPACITY: usize> Default for EventLog<CAPACITY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const CAPACITY: usize> EventLog<CAPACITY> {
+    /// Start the consumer thread, draining every `poll_interval` into `sink`
+    pub fn spawn_drainer<F>(log: Arc<Self>, poll_interval: Duration, mut sink: F) -> EventLogDrainer
+    where
+        F: FnMut(LoggedEvent) + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        
+        let handle = std::thread::spawn(move || {
+            while !thread_stop.load(Ordering::Acquire) {
+                if log.drain(&mut sink) == 0 {
+                    std::thread::sleep(poll_interval);
+                }
+            }
+            // Final sweep so nothing recorded before stop() is lost
+            log.drain(&mut sink);
+        });
+        
+        EventLogDrainer { stop, handle: Some(handle) }
+    }
+}
+
+/// Handle to the background drain thread; stops and joins on drop
+pub struct EventLogDrainer {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl EventLogDrainer {
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+    
+    fn shutdown(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for EventLogDrainer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    
+    #[test]
+    fn test_event_log_drains_in_order() {
+        let log: EventLog<16> = EventLog::new();
+        let events = [
+            EngineEvent::QuoteGenerated { asset_id: 1, bid: 99.9, ask: 100.1 },
+            EngineEvent::OrderSubmitted { order_id: 7 },
+            EngineEvent::OrderRejected { order_id: 8, reason: RejectReason::PositionLimit },
+            EngineEvent::Fill { order_id: 7, quantity: 100, price: 100.1 },
+            EngineEvent::KillSwitch(KillReason::Manual),
+        ];
+        for (i, event) in events.iter().enumerate() {
+            assert!(log.record_at(i as i64, *event));
+        }
+        
+        let mut drained = Vec::new();
+        assert_eq!(log.drain(|e| drained.push(e)), events.len());
+        for (i, logged) in drained.iter().enumerate() {
+            assert_eq!(logged.timestamp_ns, i as i64);
+            assert_eq!(logged.event, events[i]);
+        }
+        assert_eq!(log.dropped(), 0);
+    }
+    
+    #[test]
+    fn test_event_log_counts_drops_when_saturated() {
+        let log: EventLog<8> = EventLog::new();
+        let mut accepted = 0;
+        for order_id in 0..20 {
+            if log.record(EngineEvent::OrderSubmitted { order_id }) {
+                accepted += 1;
+            }
+        }
+        assert_eq!(log.dropped(), 20 - accepted);
+        assert!(log.dropped() > 0);
+        assert_eq!(log.drain(|_| {}) as u64, accepted);
+    }
+    
+    #[test]
+    fn test_event_log_background_drainer() {
+        let log: Arc<EventLog<64>> = Arc::new(EventLog::new());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink_seen = Arc::clone(&seen);
+        
+        let drainer = EventLog::spawn_drainer(Arc::clone(&log), Duration::from_micros(50), move |e| {
+            sink_seen.lock().unwrap().push(e.event);
+        });
+        for order_id in 0..10 {
+            while !log.record(EngineEvent::OrderSubmitted { order_id }) {
+                std::thread::yield_now();
+            }
+        }
+        drainer.stop();
+        
+        let seen = seen.lock().unwrap();
+        let expected: Vec<_> = (0..10).map(|order_id| EngineEvent::OrderSubmitted { order_id }).collect();
+        assert_eq!(*seen, expected);
+    }
+}
@@ -0,0 +1,215 @@
+// Parent order execution: slice a large order into child orders over a time window
+// Schedules are fixed at construction; callers drive them with their own clock
+// (`HiResTimer::now_ns()` or replayed tick timestamps)
+
+use crate::{Order, OrderIdGen};
+
+// Shared slice schedule
+
+struct SliceSchedule {
+    parent: Order,
+    start_ns: i64,
+    interval_ns: i64,
+    quantities: Vec<u64>,
+    next: usize,
+}
+
+impl SliceSchedule {
+    fn new(parent: Order, window_secs: f64, quantities: Vec<u64>) -> Self {
+        assert!(!quantities.is_empty(), "At least one slice required");
+        assert!(window_secs >= 0.0, "Window must not be negative");
+        debug_assert_eq!(quantities.iter().sum::<u64>(), parent.quantity);
+        
+        let interval_ns = (window_secs * 1e9 / quantities.len() as f64) as i64;
+        Self {
+            start_ns: parent.submit_time_ns,
+            parent,
+            interval_ns,
+            quantities,
+            next: 0,
+        }
+    }
+    
+    #[inline(always)]
+    fn slice_time_ns(&self, index: usize) -> i64 {
+        self.start_ns + self.interval_ns * index as i64
+    }
+    
+    fn next_child(&mut self, now_ns: i64, ids: &OrderIdGen) -> Option<Order> {
+        // Zero-sized slices (possible with sparse volume curves) are skipped
+        while self.next < self.quantities.len() && self.quantities[self.next] == 0 {
+            self.next += 1;
+        }
+        if self.next >= self.quantities.len() || now_ns < self.slice_time_ns(self.next) {
+            return None;
+        }
+        
+        let child = Order {
+            order_id: ids.next_id(),
+            quantity: self.quantities[self.next],
+            submit_time_ns: self.slice_time_ns(self.next),
+            is_active: true,
+            ..self.parent
+        };
+        self.next += 1;
+        Some(child)
+    }
+    
+    fn remaining_qty(&self) -> u64 {
+        self.quantities[self.next.min(self.quantities.len())..].iter().sum()
+    }
+}
+
+/// Split `total` by `weights`, flooring each slice and putting the remainder on the last
+fn weighted_split(total: u64, weights: &[f64]) -> Vec<u64> {
+    let sum: f64 = weights.iter().sum();
+    assert!(sum > 0.0 && weights.iter().all(|w| *w >= 0.0), "Weights must be non-negative with a positive sum");
+    
+    let mut quantities: Vec<u64> = weights
+        .iter()
+        .map(|w| (total as f64 * w / sum).floor() as u64)
+        .collect();
+    let allocated: u64 = quantities[..quantities.len() - 1].iter().sum();
+    *quantities.last_mut().unwrap() = total.saturating_sub(allocated);
+    quantities
+}
+
+// TWAP
+
+/// Equal slices at evenly spaced times starting at `parent.submit_time_ns`.
+///
+/// Children copy the parent but take a fresh `order_id` from the caller's
+/// `OrderIdGen` (the parent's stays available as `parent_id`); the final slice
+/// carries any remainder so child quantities always sum to the parent quantity.
+pub struct TwapScheduler {
+    schedule: SliceSchedule,
+}
+
+impl TwapScheduler {
+    pub fn new(parent: Order, window_secs: f64, slices: usize) -> Self {
+        assert!(slices > 0, "At least one slice required");
+        let base = parent.quantity / slices as u64;
+        let mut quantities = vec![base; slices];
+        quantities[slices - 1] += parent.quantity - base * slices as u64;
+        Self { schedule: SliceSchedule::new(parent, window_secs, quantities) }
+    }
+    
+    /// Next child order if its scheduled time has arrived (one per call)
+    #[inline(always)]
+    pub fn next_child(&mut self, now_ns: i64, ids: &OrderIdGen) -> Option<Order> {
+        self.schedule.next_child(now_ns, ids)
+    }
+    
+    /// `order_id` of the parent the children are cut from
+    pub fn parent_id(&self) -> u64 {
+        self.schedule.parent.order_id
+    }
+    
+    /// Scheduled send time of slice `index`
+    pub fn slice_time_ns(&self, index: usize) -> i64 {
+        self.schedule.slice_time_ns(index)
+    }
+    
+    /// Parent quantity not yet released as children
+    pub fn remaining_qty(&self) -> u64 {
+        self.schedule.remaining_qty()
+    }
+    
+    pub fn is_done(&self) -> bool {
+        self.remaining_qty() == 0
+    }
+}
+
+// VWAP
+
+/// Like TWAP, but slice sizes follow a relative volume curve (one weight per slice)
+pub struct VwapScheduler {
+    schedule: SliceSchedule,
+}
+
+impl VwapScheduler {
+    pub fn new(parent: Order, window_secs: f64, volume_curve: &[f64]) -> Self {
+        assert!(!volume_curve.is_empty(), "Volume curve must not be empty");
+        let quantities = weighted_split(parent.quantity, volume_curve);
+        Self { schedule: SliceSchedule::new(parent, window_secs, quantities) }
+    }
+    
+    #[inline(always)]
+    pub fn next_child(&mut self, now_ns: i64, ids: &OrderIdGen) -> Option<Order> {
+        self.schedule.next_child(now_ns, ids)
+    }
+    
+    pub fn parent_id(&self) -> u64 {
+        self.schedule.parent.order_id
+    }
+    
+    pub fn slice_time_ns(&self, index: usize) -> i64 {
+        self.schedule.slice_time_ns(index)
+    }
+    
+    pub fn remaining_qty(&self) -> u64 {
+        self.schedule.remaining_qty()
+    }
+    
+    pub fn is_done(&self) -> bool {
+        self.remaining_qty() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn parent(quantity: u64) -> Order {
+        Order { order_id: 1, asset_id: 3, side: 0, price: 100.0, quantity, submit_time_ns: 1_000, ..Default::default() }
+    }
+    
+    #[test]
+    fn test_twap_timing_and_remainder() {
+        // 1003 over 10s in 4 slices: 250, 250, 250, 253 at +0s, +2.5s, +5s, +7.5s
+        let ids = OrderIdGen::new(100);
+        let mut twap = TwapScheduler::new(parent(1003), 10.0, 4);
+        assert_eq!(twap.slice_time_ns(1), 1_000 + 2_500_000_000);
+        
+        let first = twap.next_child(1_000, &ids).unwrap();
+        assert_eq!((first.quantity, first.asset_id, first.price), (250, 3, 100.0));
+        assert!(twap.next_child(1_000, &ids).is_none());
+        assert!(twap.next_child(2_500_000_999, &ids).is_none());
+        
+        let second = twap.next_child(2_500_001_000, &ids).unwrap();
+        assert_eq!(second.submit_time_ns, 2_500_001_000);
+        
+        // Late caller catches up one slice per call
+        let mut total = first.quantity + second.quantity;
+        let mut child_ids = vec![first.order_id, second.order_id];
+        let mut last = 0;
+        while let Some(child) = twap.next_child(i64::MAX, &ids) {
+            total += child.quantity;
+            last = child.quantity;
+            child_ids.push(child.order_id);
+        }
+        // Every child is individually addressable; the parent id is kept aside
+        assert_eq!(child_ids, vec![100, 101, 102, 103]);
+        assert_eq!(twap.parent_id(), 1);
+        assert_eq!(last, 253);
+        assert_eq!(total, 1003);
+        assert!(twap.is_done());
+    }
+    
+    #[test]
+    fn test_vwap_follows_volume_curve() {
+        let mut vwap = VwapScheduler::new(parent(1000), 3.0, &[3.0, 0.0, 1.0, 2.0]);
+        assert_eq!(vwap.remaining_qty(), 1000);
+        
+        let mut children = Vec::new();
+        let ids = OrderIdGen::new(100);
+        while let Some(child) = vwap.next_child(i64::MAX, &ids) {
+            children.push((child.submit_time_ns, child.quantity));
+        }
+        
+        // The empty bucket produces no child; remainder lands on the last slice
+        assert_eq!(children, vec![(1_000, 500), (1_500_001_000, 166), (2_250_001_000, 334)]);
+        assert_eq!(children.iter().map(|c| c.1).sum::<u64>(), 1000);
+        assert!(vwap.is_done());
+    }
+}
@@ -0,0 +1,327 @@
+// Feed handling: A/B line arbitration for redundant market data feeds
+// Primary and backup lines carry the same sequenced messages; whichever
+// delivers a sequence number first wins and the copy from the other line is dropped
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::metrics::Metrics;
+use crate::{KillReason, MarketTick, RiskControl};
+
+// Sequenced feed message
+
+#[derive(Clone, Copy)]
+pub struct SequencedTick {
+    pub seq: u64,
+    pub tick: MarketTick,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedLine {
+    Primary,
+    Backup,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ArbitrationConfig {
+    /// Out-of-order messages held while waiting for a missing sequence
+    /// number; once exceeded the gap is declared lost and skipped
+    pub max_buffered: usize,
+    /// First expected sequence number; `None` syncs to the first message seen
+    pub start_seq: Option<u64>,
+}
+
+impl Default for ArbitrationConfig {
+    fn default() -> Self {
+        Self {
+            max_buffered: 1024,
+            start_seq: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArbitrationStats {
+    pub from_primary: u64,
+    pub from_backup: u64,
+    pub duplicates: u64,
+    pub lost: u64,  // Sequence numbers missing on both lines
+}
+
+// Arbitrated Feed
+
+/// Merges a primary and backup line into one gapless, in-order sequence.
+pub struct ArbitratedFeed {
+    config: ArbitrationConfig,
+    next_seq: Option<u64>,
+    pending: BTreeMap<u64, (FeedLine, MarketTick)>,
+    stats: ArbitrationStats,
+}
+
+impl ArbitratedFeed {
+    pub fn new(config: ArbitrationConfig) -> Self {
+        Self {
+            next_seq: config.start_seq,
+            config,
+            pending: BTreeMap::new(),
+            stats: ArbitrationStats::default(),
+        }
+    }
+    
+    /// Offer a message received on either line
+    pub fn on_message(&mut self, line: FeedLine, msg: SequencedTick) {
+        let next_seq = *self.next_seq.get_or_insert(msg.seq);
+        
+        if msg.seq < next_seq || self.pending.contains_key(&msg.seq) {
+            self.stats.duplicates += 1;
+            return;
+        }
+        self.pending.insert(msg.seq, (line, msg.tick));
+        
+        // Both lines are missing the head of the queue: give up on it
+        if self.pending.len() > self.config.max_buffered {
+            if let Some(&lowest) = self.pending.keys().next() {
+                self.stats.lost += lowest - next_seq;
+                self.next_seq = Some(lowest);
+            }
+        }
+    }
+    
+    #[inline(always)]
+    pub fn on_primary(&mut self, msg: SequencedTick) {
+        self.on_message(FeedLine::Primary, msg);
+    }
+    
+    #[inline(always)]
+    pub fn on_backup(&mut self, msg: SequencedTick) {
+        self.on_message(FeedLine::Backup, msg);
+    }
+    
+    /// Next message in sequence order, if it has arrived on either line
+    pub fn poll(&mut self) -> Option<SequencedTick> {
+        let seq = self.next_seq?;
+        let (line, tick) = self.pending.remove(&seq)?;
+        
+        match line {
+            FeedLine::Primary => self.stats.from_primary += 1,
+            FeedLine::Backup => self.stats.from_backup += 1,
+        }
+        self.next_seq = Some(seq + 1);
+        Some(SequencedTick { seq, tick })
+    }
+    
+    /// Sequence number the arbiter is waiting for
+    pub fn expected_seq(&self) -> Option<u64> {
+        self.next_seq
+    }
+    
+    /// Messages buffered behind a gap
+    pub fn buffered(&self) -> usize {
+        self.pending.len()
+    }
+    
+    pub fn stats(&self) -> ArbitrationStats {
+        self.stats
+    }
+}
+
+impl Default for ArbitratedFeed {
+    fn default() -> Self {
+        Self::new(ArbitrationConfig::default())
+    }
+}
+
+// Timestamp gap detection
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GapStatus {
+    Ok,
+    /// Older than the last tick seen for the asset
+    Backwards,
+    /// Forward jump of `ns`, beyond the configured maximum interval
+    Gap { ns: i64 },
+}
+
+/// Flags per-asset timestamp reversals and over-long silences, which on a
+/// multicast feed usually mean a dropped packet.
+pub struct GapDetector {
+    max_interval_ns: i64,
+    last_ns: HashMap<u32, i64>,
+    gaps: u64,
+    backwards: u64,
+    metrics: Option<Arc<Metrics>>,
+    kill_switch: Option<Arc<RiskControl>>,
+    critical: HashSet<u32>,
+}
+
+impl GapDetector {
+    pub fn new(max_interval_ns: i64) -> Self {
+        Self {
+            max_interval_ns,
+            last_ns: HashMap::new(),
+            gaps: 0,
+            backwards: 0,
+            metrics: None,
+            kill_switch: None,
+            critical: HashSet::new(),
+        }
+    }
+    
+    /// Count every gap or reversal into a shared `Metrics`
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+    
+    /// Halt `risk` with `KillReason::FeedGap` when a critical asset gaps
+    pub fn with_kill_switch(mut self, risk: Arc<RiskControl>) -> Self {
+        self.kill_switch = Some(risk);
+        self
+    }
+    
+    pub fn with_critical_asset(mut self, asset_id: u32) -> Self {
+        self.critical.insert(asset_id);
+        self
+    }
+    
+    /// Classify a tick against the last one seen for its asset. A backwards
+    /// tick does not move the asset's high-water timestamp
+    pub fn observe(&mut self, tick: &MarketTick) -> GapStatus {
+        let status = match self.last_ns.get_mut(&tick.asset_id) {
+            None => {
+                self.last_ns.insert(tick.asset_id, tick.timestamp_ns);
+                GapStatus::Ok
+            }
+            Some(last) if tick.timestamp_ns < *last => GapStatus::Backwards,
+            Some(last) => {
+                let ns = tick.timestamp_ns - *last;
+                *last = tick.timestamp_ns;
+                if ns > self.max_interval_ns { GapStatus::Gap { ns } } else { GapStatus::Ok }
+            }
+        };
+        
+        match status {
+            GapStatus::Ok => return status,
+            GapStatus::Backwards => self.backwards += 1,
+            GapStatus::Gap { .. } => self.gaps += 1,
+        }
+        if let Some(metrics) = &self.metrics {
+            metrics.inc_feed_gaps();
+        }
+        if let Some(risk) = &self.kill_switch {
+            if self.critical.contains(&tick.asset_id) {
+                risk.halt(KillReason::FeedGap);
+            }
+        }
+        status
+    }
+    
+    /// Forward gaps detected across all assets
+    pub fn gaps(&self) -> u64 {
+        self.gaps
+    }
+    
+    /// Backwards timestamps detected across all assets
+    pub fn backwards(&self) -> u64 {
+        self.backwards
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn msg(seq: u64) -> SequencedTick {
+        let tick = MarketTick { timestamp_ns: seq as i64 * 1_000, ..Default::default() };
+        SequencedTick { seq, tick }
+    }
+    
+    fn drain(feed: &mut ArbitratedFeed, out: &mut Vec<u64>) {
+        while let Some(m) = feed.poll() {
+            assert_eq!(m.tick.timestamp_ns, m.seq as i64 * 1_000);
+            out.push(m.seq);
+        }
+    }
+    
+    #[test]
+    fn test_backup_fills_primary_gap() {
+        let mut feed = ArbitratedFeed::default();
+        let mut out = Vec::new();
+        
+        // Primary drops seq 3; backup lags but carries everything
+        for seq in [1, 2, 4, 5] {
+            feed.on_primary(msg(seq));
+            drain(&mut feed, &mut out);
+        }
+        assert_eq!(out, vec![1, 2]);
+        assert_eq!(feed.expected_seq(), Some(3));
+        
+        for seq in 1..=5 {
+            feed.on_backup(msg(seq));
+            drain(&mut feed, &mut out);
+        }
+        
+        assert_eq!(out, vec![1, 2, 3, 4, 5]);
+        let stats = feed.stats();
+        assert_eq!(stats.from_primary, 4);
+        assert_eq!(stats.from_backup, 1);
+        assert_eq!(stats.duplicates, 4);
+        assert_eq!(stats.lost, 0);
+    }
+    
+    #[test]
+    fn test_gap_missing_on_both_lines_is_skipped() {
+        let mut feed = ArbitratedFeed::new(ArbitrationConfig { max_buffered: 2, start_seq: Some(1) });
+        let mut out = Vec::new();
+        
+        for seq in [1, 3, 4, 5] {
+            feed.on_primary(msg(seq));
+            drain(&mut feed, &mut out);
+        }
+        
+        assert_eq!(out, vec![1, 3, 4, 5]);
+        assert_eq!(feed.stats().lost, 1);
+    }
+    
+    fn at(asset_id: u32, timestamp_ns: i64) -> MarketTick {
+        MarketTick { asset_id, timestamp_ns, ..Default::default() }
+    }
+    
+    #[test]
+    fn test_gap_detector_in_order() {
+        let metrics = Arc::new(Metrics::new());
+        let mut detector = GapDetector::new(1_000).with_metrics(Arc::clone(&metrics));
+        
+        for ts in [100, 600, 600, 1_600] {
+            assert_eq!(detector.observe(&at(1, ts)), GapStatus::Ok);
+        }
+        // Other assets are tracked separately
+        assert_eq!(detector.observe(&at(2, 50)), GapStatus::Ok);
+        assert_eq!((detector.gaps(), detector.backwards()), (0, 0));
+        assert_eq!(metrics.snapshot().feed_gaps, 0);
+    }
+    
+    #[test]
+    fn test_gap_detector_backwards_and_gap() {
+        let metrics = Arc::new(Metrics::new());
+        let risk = Arc::new(RiskControl::new(100));
+        let mut detector = GapDetector::new(1_000)
+            .with_metrics(Arc::clone(&metrics))
+            .with_kill_switch(Arc::clone(&risk))
+            .with_critical_asset(2);
+        
+        detector.observe(&at(1, 5_000));
+        assert_eq!(detector.observe(&at(1, 4_000)), GapStatus::Backwards);
+        // High-water mark kept at 5_000
+        assert_eq!(detector.observe(&at(1, 5_500)), GapStatus::Ok);
+        assert_eq!(detector.observe(&at(1, 9_000)), GapStatus::Gap { ns: 3_500 });
+        assert!(!risk.is_halted());  // Asset 1 isn't critical
+        
+        detector.observe(&at(2, 0));
+        assert_eq!(detector.observe(&at(2, 1_001)), GapStatus::Gap { ns: 1_001 });
+        assert_eq!(risk.kill_reason(), Some(KillReason::FeedGap));
+        
+        assert_eq!((detector.gaps(), detector.backwards()), (2, 1));
+        assert_eq!(metrics.snapshot().feed_gaps, 3);
+    }
+}
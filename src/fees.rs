@@ -0,0 +1,65 @@
+// Venue fee and rebate schedules
+// Rates are in basis points of notional; negative rates are rebates.
+// Venues without an entry are treated as fee-free.
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct VenueFees {
+    pub maker_bps: f64,
+    pub taker_bps: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FeeSchedule {
+    venues: HashMap<u8, VenueFees>,
+}
+
+impl FeeSchedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn with_venue(mut self, venue_id: u8, maker_bps: f64, taker_bps: f64) -> Self {
+        self.venues.insert(venue_id, VenueFees { maker_bps, taker_bps });
+        self
+    }
+    
+    pub fn fees(&self, venue_id: u8) -> VenueFees {
+        self.venues.get(&venue_id).copied().unwrap_or_default()
+    }
+    
+    /// Fee rate in bps for one fill (negative = rebate)
+    #[inline(always)]
+    pub fn rate_bps(&self, venue_id: u8, is_maker: bool) -> f64 {
+        let fees = self.fees(venue_id);
+        if is_maker { fees.maker_bps } else { fees.taker_bps }
+    }
+    
+    /// Effective price per unit after fees: what a buyer (side 0) really pays
+    /// or a seller (side 1) really receives
+    #[inline(always)]
+    pub fn net_price(&self, venue_id: u8, price: f64, side: u8, is_maker: bool) -> f64 {
+        let fee = price * self.rate_bps(venue_id, is_maker) / 10_000.0;
+        if side == 0 { price + fee } else { price - fee }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_fees_and_rebates() {
+        let fees = FeeSchedule::new().with_venue(1, -0.2, 0.3).with_venue(2, 0.1, 0.25);
+        
+        // 0.3 bps taker fee on 100.0 = 0.003
+        assert!((fees.net_price(1, 100.0, 0, false) - 100.003).abs() < 1e-12);
+        assert!((fees.net_price(1, 100.0, 1, false) - 99.997).abs() < 1e-12);
+        // Maker rebate makes a resting buy cheaper, a resting sell richer
+        assert!((fees.net_price(1, 100.0, 0, true) - 99.998).abs() < 1e-12);
+        assert!((fees.net_price(1, 100.0, 1, true) - 100.002).abs() < 1e-12);
+        // Unknown venue: no adjustment
+        assert_eq!(fees.net_price(9, 100.0, 0, true), 100.0);
+    }
+}
@@ -0,0 +1,782 @@
+// C ABI exported to the C++ engine
+// Implements the `rust_*` functions declared in `include/rust_ffi.hpp`, so the
+// C++ side drives the Rust `MarketMaker`, `RiskControl` and SPSC queue instead
+// of keeping its own copies. Plain `#[no_mangle] extern "C"` functions over
+// opaque boxed handles and the shared `#[repr(C)]` structs: cbindgen reads
+// this module as-is.
+//
+// Execution events flow the other way: `exec_channel` hands the C++ gateway a
+// vtable of callbacks (`ExecCallbacks`) that push its acks, fills and rejects
+// onto an MPSC ring the strategy drains.
+//
+// Every function tolerates a null handle (no-op, or false/0), since a failed
+// `*_new` on the C++ side would otherwise turn into a crash far from its cause.
+// Calls that can fail for more than one reason return `FfiStatus`; anything
+// but `Ok` also leaves a message for `rust_last_error` on the calling thread.
+//
+// No panic may unwind into C++: every entry point runs inside `ffi_guard!`,
+// which turns a panic into the function's failure value and latches
+// `panicked()`. From then on every `RustRiskControl` halts with
+// `KillReason::Panic`, since state behind the panic can't be trusted.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_void, CString};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::mpsc::{Consumer, MpscRing, Producer};
+use crate::{ExecType, ExecutionReport, FfiStatus, KillReason, LockFreeSpscDyn, MarketMaker, MarketTick, Order, RiskControl};
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+/// Record `message` for `rust_last_error` and hand `status` back
+fn fail(status: FfiStatus, message: impl std::fmt::Display) -> FfiStatus {
+    let message = CString::new(message.to_string()).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+    status
+}
+
+/// Set once any entry point has caught a panic; see `panicked`
+static PANICKED: AtomicBool = AtomicBool::new(false);
+
+/// Run an entry point's body, returning `$on_panic` if it panics. With
+/// `panic = "abort"` (the release profiles) there is nothing to catch and the
+/// process aborts instead, which is just as safe for the C++ caller
+macro_rules! ffi_guard {
+    ($on_panic:expr, $body:block) => {
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| $body)) {
+            Ok(value) => value,
+            Err(payload) => {
+                $crate::ffi::caught_panic(payload);
+                $on_panic
+            }
+        }
+    };
+}
+
+#[cold]
+pub(crate) fn caught_panic(payload: Box<dyn std::any::Any + Send>) {
+    PANICKED.store(true, Ordering::Release);
+    let message = payload.downcast_ref::<&str>().copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("non-string payload");
+    fail(FfiStatus::Panic, format_args!("panic at FFI boundary: {message}"));
+}
+
+/// Whether any entry point has caught a panic in this process
+pub fn panicked() -> bool {
+    PANICKED.load(Ordering::Acquire)
+}
+
+#[no_mangle]
+pub extern "C" fn rust_ffi_panicked() -> bool {
+    ffi_guard!(true, { panicked() })
+}
+
+/// The handle's risk control, halted first if an entry point has panicked
+unsafe fn risk<'a>(rc: *mut RustRiskControl) -> &'a RiskControl {
+    let risk = &(*rc).0;
+    if panicked() && !risk.is_halted() {
+        risk.halt(KillReason::Panic);
+    }
+    risk
+}
+
+/// Message for the last non-`Ok` status returned on this thread, `""` if none.
+/// The pointer stays valid until the next failing call on the same thread
+#[no_mangle]
+pub extern "C" fn rust_last_error() -> *const c_char {
+    ffi_guard!(c"".as_ptr(), {
+        LAST_ERROR.with(|last| last.borrow().as_ptr())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn rust_clear_last_error() {
+    ffi_guard!((), {
+        LAST_ERROR.with(|last| *last.borrow_mut() = CString::default());
+    })
+}
+
+/// Opaque to C++; created by `rust_market_maker_new`
+pub struct RustMarketMaker(MarketMaker);
+
+/// Opaque to C++; created by `rust_risk_control_new`
+pub struct RustRiskControl(RiskControl);
+
+/// Opaque to C++; created by `rust_queue_new`
+pub struct RustLockFreeQueue(LockFreeSpscDyn<MarketTick>);
+
+// Market maker
+
+#[no_mangle]
+pub extern "C" fn rust_market_maker_new(risk_aversion: f64, volatility: f64, tick_size: f64) -> *mut RustMarketMaker {
+    ffi_guard!(std::ptr::null_mut(), {
+        Box::into_raw(Box::new(RustMarketMaker(MarketMaker::new(risk_aversion, volatility, tick_size))))
+    })
+}
+
+/// # Safety
+/// `mm` must come from `rust_market_maker_new` and not be used afterwards
+#[no_mangle]
+pub unsafe extern "C" fn rust_market_maker_free(mm: *mut RustMarketMaker) {
+    ffi_guard!((), {
+        if !mm.is_null() {
+            drop(Box::from_raw(mm));
+        }
+    })
+}
+
+/// Writes the quotes to `bid_out` / `ask_out`; leaves them untouched on a null argument
+///
+/// # Safety
+/// Non-null pointers must be valid: `mm` from `rust_market_maker_new`,
+/// `tick` readable, `bid_out` / `ask_out` writable
+#[no_mangle]
+pub unsafe extern "C" fn rust_market_maker_generate_quotes(
+    mm: *mut RustMarketMaker,
+    tick: *const MarketTick,
+    inventory: i64,
+    bid_out: *mut f64,
+    ask_out: *mut f64,
+) {
+    ffi_guard!((), {
+        if mm.is_null() || tick.is_null() || bid_out.is_null() || ask_out.is_null() {
+            return;
+        }
+        let (bid, ask) = (*mm).0.generate_quotes(&*tick, inventory);
+        *bid_out = bid;
+        *ask_out = ask;
+    })
+}
+
+// Risk control
+
+#[no_mangle]
+pub extern "C" fn rust_risk_control_new(max_position: i64) -> *mut RustRiskControl {
+    ffi_guard!(std::ptr::null_mut(), {
+        Box::into_raw(Box::new(RustRiskControl(RiskControl::new(max_position))))
+    })
+}
+
+/// # Safety
+/// `rc` must come from `rust_risk_control_new` and not be used afterwards
+#[no_mangle]
+pub unsafe extern "C" fn rust_risk_control_free(rc: *mut RustRiskControl) {
+    ffi_guard!((), {
+        if !rc.is_null() {
+            drop(Box::from_raw(rc));
+        }
+    })
+}
+
+/// False (reject) on a null argument
+///
+/// # Safety
+/// Non-null pointers must be valid: `rc` from `rust_risk_control_new`, `order` readable
+#[no_mangle]
+pub unsafe extern "C" fn rust_risk_control_check_pre_trade(rc: *mut RustRiskControl, order: *const Order, current_position: i64) -> bool {
+    ffi_guard!(false, {
+        if rc.is_null() || order.is_null() {
+            return false;
+        }
+        risk(rc).check_pre_trade(&*order, current_position)
+    })
+}
+
+/// # Safety
+/// `rc` must be null or come from `rust_risk_control_new`
+#[no_mangle]
+pub unsafe extern "C" fn rust_risk_control_trigger_kill_switch(rc: *mut RustRiskControl) {
+    ffi_guard!((), {
+        if !rc.is_null() {
+            (*rc).0.trigger_kill_switch();
+        }
+    })
+}
+
+/// Book an execution and store the asset's new position in `position_out`
+/// (skipped if null). `InvalidArgument`, with nothing booked, for a price or
+/// quantity `RiskControl::apply_fill` refuses; `NullPointer` for a null handle
+///
+/// # Safety
+/// Non-null pointers must be valid: `rc` from `rust_risk_control_new`, `position_out` writable
+#[no_mangle]
+pub unsafe extern "C" fn rust_risk_control_apply_fill(rc: *mut RustRiskControl, asset_id: u32, side: u8, quantity: u64, price: f64, position_out: *mut i64) -> FfiStatus {
+    ffi_guard!(FfiStatus::Panic, {
+        if rc.is_null() {
+            return fail(FfiStatus::NullPointer, "rust_risk_control_apply_fill: null handle");
+        }
+        match risk(rc).apply_fill(asset_id, side, quantity, price) {
+            Ok(position) => {
+                if !position_out.is_null() {
+                    *position_out = position;
+                }
+                FfiStatus::Ok
+            }
+            Err(err) => fail(FfiStatus::InvalidArgument, format_args!("rust_risk_control_apply_fill: {err}")),
+        }
+    })
+}
+
+/// # Safety
+/// `rc` must be null or come from `rust_risk_control_new`
+#[no_mangle]
+pub unsafe extern "C" fn rust_risk_control_realized_pnl(rc: *mut RustRiskControl) -> f64 {
+    ffi_guard!(0.0, {
+        if rc.is_null() {
+            return 0.0;
+        }
+        risk(rc).realized_pnl()
+    })
+}
+
+/// True for a null handle: no risk control means no trading
+///
+/// # Safety
+/// `rc` must be null or come from `rust_risk_control_new`
+#[no_mangle]
+pub unsafe extern "C" fn rust_risk_control_is_halted(rc: *mut RustRiskControl) -> bool {
+    ffi_guard!(true, {
+        rc.is_null() || risk(rc).is_halted()
+    })
+}
+
+// Lock-free queue (one C++ producer thread, one consumer thread)
+
+/// Null unless `capacity` is a power of two
+#[no_mangle]
+pub extern "C" fn rust_queue_new(capacity: usize) -> *mut RustLockFreeQueue {
+    ffi_guard!(std::ptr::null_mut(), {
+        if !capacity.is_power_of_two() {
+            return std::ptr::null_mut();
+        }
+        Box::into_raw(Box::new(RustLockFreeQueue(LockFreeSpscDyn::with_capacity(capacity))))
+    })
+}
+
+/// # Safety
+/// `queue` must come from `rust_queue_new` and not be used afterwards
+#[no_mangle]
+pub unsafe extern "C" fn rust_queue_free(queue: *mut RustLockFreeQueue) {
+    ffi_guard!((), {
+        if !queue.is_null() {
+            drop(Box::from_raw(queue));
+        }
+    })
+}
+
+/// # Safety
+/// Non-null pointers must be valid: `queue` from `rust_queue_new`, `tick` readable
+#[no_mangle]
+pub unsafe extern "C" fn rust_queue_push(queue: *mut RustLockFreeQueue, tick: *const MarketTick) -> bool {
+    ffi_guard!(false, {
+        if queue.is_null() || tick.is_null() {
+            return false;
+        }
+        (*queue).0.push(*tick)
+    })
+}
+
+/// # Safety
+/// Non-null pointers must be valid: `queue` from `rust_queue_new`, `tick` writable
+#[no_mangle]
+pub unsafe extern "C" fn rust_queue_pop(queue: *mut RustLockFreeQueue, tick: *mut MarketTick) -> bool {
+    ffi_guard!(false, {
+        if queue.is_null() || tick.is_null() {
+            return false;
+        }
+        match (*queue).0.pop() {
+            Some(popped) => {
+                *tick = popped;
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+/// `rust_queue_push` with the reason for a refusal: `Full` or `NullPointer`
+///
+/// # Safety
+/// Non-null pointers must be valid: `queue` from `rust_queue_new`, `tick` readable
+#[no_mangle]
+pub unsafe extern "C" fn rust_queue_push_ex(queue: *mut RustLockFreeQueue, tick: *const MarketTick) -> FfiStatus {
+    ffi_guard!(FfiStatus::Panic, {
+        if queue.is_null() || tick.is_null() {
+            return fail(FfiStatus::NullPointer, "rust_queue_push_ex: null queue or tick");
+        }
+        if (*queue).0.push(*tick) { FfiStatus::Ok } else { FfiStatus::Full }
+    })
+}
+
+/// `rust_queue_pop` with the reason nothing was popped: `Empty` or `NullPointer`
+///
+/// # Safety
+/// Non-null pointers must be valid: `queue` from `rust_queue_new`, `tick` writable
+#[no_mangle]
+pub unsafe extern "C" fn rust_queue_pop_ex(queue: *mut RustLockFreeQueue, tick: *mut MarketTick) -> FfiStatus {
+    ffi_guard!(FfiStatus::Panic, {
+        if queue.is_null() || tick.is_null() {
+            return fail(FfiStatus::NullPointer, "rust_queue_pop_ex: null queue or tick");
+        }
+        match (*queue).0.pop() {
+            Some(popped) => {
+                *tick = popped;
+                FfiStatus::Ok
+            }
+            None => FfiStatus::Empty,
+        }
+    })
+}
+
+/// # Safety
+/// `queue` must be null or come from `rust_queue_new`
+#[no_mangle]
+pub unsafe extern "C" fn rust_queue_is_empty(queue: *mut RustLockFreeQueue) -> bool {
+    ffi_guard!(true, {
+        queue.is_null() || (*queue).0.is_empty()
+    })
+}
+
+/// # Safety
+/// `queue` must be null or come from `rust_queue_new`
+#[no_mangle]
+pub unsafe extern "C" fn rust_queue_size(queue: *mut RustLockFreeQueue) -> usize {
+    ffi_guard!(0, {
+        if queue.is_null() {
+            return 0;
+        }
+        (*queue).0.size()
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn rust_benchmark_queue_throughput() {
+    ffi_guard!((), {
+        crate::benchmark_queue_throughput();
+    })
+}
+
+// Execution events (C++ gateway -> Rust strategy)
+
+/// Called by the gateway with a report it keeps ownership of; false means
+/// the event was dropped (full queue, or a null argument)
+pub type ExecCallback = unsafe extern "C" fn(ctx: *mut c_void, report: *const ExecutionReport) -> bool;
+
+/// Vtable handed to `cpp_gateway_register_callbacks` (mirrors
+/// `ExecCallbacks` in `rust_ffi.hpp`). Callable from any gateway thread.
+/// `ctx` holds its own reference to the sink's state: the gateway calls
+/// `release` exactly once, after the last callback through this vtable has
+/// returned, and only then can the state be freed
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct ExecCallbacks {
+    pub ctx: *mut c_void,
+    pub on_ack: ExecCallback,
+    pub on_fill: ExecCallback,
+    pub on_cancel: ExecCallback,
+    pub on_reject: ExecCallback,
+    pub release: unsafe extern "C" fn(ctx: *mut c_void),
+}
+
+struct SinkState<const CAPACITY: usize> {
+    producer: Producer<ExecutionReport, CAPACITY>,
+    dropped: AtomicU64,
+}
+
+/// Producer half of `exec_channel`. Dropping it unregisters; callbacks
+/// already running on gateway threads keep the state alive until `release`
+pub struct ExecSink<const CAPACITY: usize> {
+    state: Arc<SinkState<CAPACITY>>,
+    unregister: Option<unsafe extern "C" fn()>,  // Set once registered
+}
+
+/// Queue of execution reports fed by C++ callbacks; drain the consumer
+/// from the strategy thread
+pub fn exec_channel<const CAPACITY: usize>() -> (ExecSink<CAPACITY>, Consumer<ExecutionReport, CAPACITY>) {
+    let (producer, consumer) = MpscRing::channel();
+    let state = Arc::new(SinkState { producer, dropped: AtomicU64::new(0) });
+    (ExecSink { state, unregister: None }, consumer)
+}
+
+impl<const CAPACITY: usize> ExecSink<CAPACITY> {
+    /// A vtable holding a new reference to the state; leaks it unless
+    /// `release` is eventually called
+    pub fn callbacks(&self) -> ExecCallbacks {
+        ExecCallbacks {
+            ctx: Arc::into_raw(self.state.clone()) as *mut c_void,
+            on_ack: on_ack::<CAPACITY>,
+            on_fill: on_fill::<CAPACITY>,
+            on_cancel: on_cancel::<CAPACITY>,
+            on_reject: on_reject::<CAPACITY>,
+            release: release::<CAPACITY>,
+        }
+    }
+    
+    /// Hand the callbacks to the C++ gateway (`cpp_gateway_register_callbacks`)
+    #[cfg(feature = "cpp-ffi")]
+    pub fn register(&mut self) {
+        let callbacks = self.callbacks();
+        unsafe { crate::cpp_gateway_register_callbacks(&callbacks) };
+        self.unregister = Some(crate::cpp_gateway_unregister_callbacks);
+    }
+    
+    /// Reports the gateway delivered while the queue was full
+    pub fn dropped(&self) -> u64 {
+        self.state.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<const CAPACITY: usize> Drop for ExecSink<CAPACITY> {
+    fn drop(&mut self) {
+        if let Some(unregister) = self.unregister {
+            unsafe { unregister() };
+        }
+    }
+}
+
+/// Queue `*report`, with `exec_type` set from the callback it came through
+unsafe fn deliver<const CAPACITY: usize>(ctx: *mut c_void, report: *const ExecutionReport, exec_type: impl FnOnce(&ExecutionReport) -> ExecType) -> bool {
+    if ctx.is_null() || report.is_null() {
+        return false;
+    }
+    let state = &*(ctx as *const SinkState<CAPACITY>);
+    let mut report = *report;
+    report.exec_type = exec_type(&report) as u8;
+    let queued = state.producer.push(report);
+    if !queued {
+        state.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+    queued
+}
+
+unsafe extern "C" fn on_ack<const CAPACITY: usize>(ctx: *mut c_void, report: *const ExecutionReport) -> bool {
+    ffi_guard!(false, {
+        deliver::<CAPACITY>(ctx, report, |_| ExecType::New)
+    })
+}
+
+unsafe extern "C" fn on_fill<const CAPACITY: usize>(ctx: *mut c_void, report: *const ExecutionReport) -> bool {
+    ffi_guard!(false, {
+        deliver::<CAPACITY>(ctx, report, |report| {
+            if report.leaves_quantity == 0 { ExecType::Fill } else { ExecType::PartialFill }
+        })
+    })
+}
+
+unsafe extern "C" fn on_cancel<const CAPACITY: usize>(ctx: *mut c_void, report: *const ExecutionReport) -> bool {
+    ffi_guard!(false, {
+        deliver::<CAPACITY>(ctx, report, |_| ExecType::Cancelled)
+    })
+}
+
+unsafe extern "C" fn on_reject<const CAPACITY: usize>(ctx: *mut c_void, report: *const ExecutionReport) -> bool {
+    ffi_guard!(false, {
+        deliver::<CAPACITY>(ctx, report, |_| ExecType::Rejected)
+    })
+}
+
+/// Drop the reference `callbacks()` put in `ctx`
+unsafe extern "C" fn release<const CAPACITY: usize>(ctx: *mut c_void) {
+    ffi_guard!((), {
+        if !ctx.is_null() {
+            drop(Arc::from_raw(ctx as *const SinkState<CAPACITY>));
+        }
+    })
+}
+
+// FPGA completions (C++ card thread -> `FpgaPredictor`'s completion ring)
+
+/// Called once per `cpp_fpga_submit`, from a single completion thread
+pub type FpgaDoneFn = unsafe extern "C" fn(ctx: *mut c_void, request_id: u64, output: *const f64, num_outputs: u32);
+
+/// `ctx` is the predictor's completion ring
+#[cfg(feature = "cpp-ffi")]
+pub(crate) unsafe extern "C" fn fpga_done(ctx: *mut c_void, request_id: u64, output: *const f64, num_outputs: u32) {
+    ffi_guard!((), {
+        if ctx.is_null() {
+            return;
+        }
+        let value = if output.is_null() || num_outputs == 0 { f64::NAN } else { *output };
+        let completions = &*(ctx as *const LockFreeSpscDyn<crate::FpgaCompletion>);
+        // Can't be full: `submit` caps requests in flight at the ring's capacity
+        completions.push(crate::FpgaCompletion { request_id: crate::RequestId(request_id), value });
+    })
+}
+
+// Native shared-memory ring (`shm::ShmRing`), for C++ processes that attach
+// to a segment the Rust side created, or the other way round.
+// Full and Empty are flow control, not errors: they leave `rust_last_error` alone.
+
+#[cfg(target_os = "linux")]
+pub use self::ring::*;
+
+#[cfg(target_os = "linux")]
+mod ring {
+    use std::ffi::{c_char, CStr};
+    
+    use super::fail;
+    use crate::shm::ShmRing;
+    use crate::{FfiStatus, MarketTick, ShmError};
+    
+    /// Opaque to C++; created by `rust_shm_create` or `rust_shm_attach`
+    pub struct RustShmRing(ShmRing);
+    
+    fn status(context: &str, err: ShmError) -> FfiStatus {
+        match err {
+            ShmError::Full => FfiStatus::Full,
+            ShmError::Empty => FfiStatus::Empty,
+            err => fail(err.into(), format_args!("{context}: {err}")),
+        }
+    }
+    
+    unsafe fn open(
+        context: &str,
+        name: *const c_char,
+        out: *mut *mut RustShmRing,
+        open: impl FnOnce(&str) -> Result<ShmRing, ShmError>,
+    ) -> FfiStatus {
+        if name.is_null() || out.is_null() {
+            return fail(FfiStatus::NullPointer, format_args!("{context}: null name or out pointer"));
+        }
+        *out = std::ptr::null_mut();
+        let Ok(name) = CStr::from_ptr(name).to_str() else {
+            return fail(FfiStatus::BadName, format_args!("{context}: name is not UTF-8"));
+        };
+        match open(name) {
+            Ok(ring) => {
+                *out = Box::into_raw(Box::new(RustShmRing(ring)));
+                FfiStatus::Ok
+            }
+            Err(err) => status(context, err),
+        }
+    }
+    
+    /// Create segment `name` with room for `capacity` ticks; the handle goes to `*out`
+    ///
+    /// # Safety
+    /// `name` must be null or NUL-terminated, `out` null or writable
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_shm_create(name: *const c_char, capacity: usize, out: *mut *mut RustShmRing) -> FfiStatus {
+        ffi_guard!(FfiStatus::Panic, {
+            open("rust_shm_create", name, out, |name| ShmRing::create(name, capacity))
+        })
+    }
+    
+    /// Map an existing segment; `VersionMismatch` if its layout isn't ours
+    ///
+    /// # Safety
+    /// `name` must be null or NUL-terminated, `out` null or writable
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_shm_attach(name: *const c_char, out: *mut *mut RustShmRing) -> FfiStatus {
+        ffi_guard!(FfiStatus::Panic, {
+            open("rust_shm_attach", name, out, ShmRing::attach)
+        })
+    }
+    
+    /// # Safety
+    /// `ring` must come from `rust_shm_create` / `rust_shm_attach` and not be used afterwards
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_shm_free(ring: *mut RustShmRing) {
+        ffi_guard!((), {
+            if !ring.is_null() {
+                drop(Box::from_raw(ring));
+            }
+        })
+    }
+    
+    /// # Safety
+    /// Non-null pointers must be valid: `ring` from `rust_shm_create` / `rust_shm_attach`, `tick` readable
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_shm_write_tick(ring: *mut RustShmRing, tick: *const MarketTick) -> FfiStatus {
+        ffi_guard!(FfiStatus::Panic, {
+            if ring.is_null() || tick.is_null() {
+                return fail(FfiStatus::NullPointer, "rust_shm_write_tick: null ring or tick");
+            }
+            match (*ring).0.write_tick(&*tick) {
+                Ok(()) => FfiStatus::Ok,
+                Err(err) => status("rust_shm_write_tick", err),
+            }
+        })
+    }
+    
+    /// # Safety
+    /// Non-null pointers must be valid: `ring` from `rust_shm_create` / `rust_shm_attach`, `tick` writable
+    #[no_mangle]
+    pub unsafe extern "C" fn rust_shm_read_tick(ring: *mut RustShmRing, tick: *mut MarketTick) -> FfiStatus {
+        ffi_guard!(FfiStatus::Panic, {
+            if ring.is_null() || tick.is_null() {
+                return fail(FfiStatus::NullPointer, "rust_shm_read_tick: null ring or tick");
+            }
+            match (*ring).0.read_tick() {
+                Ok(Some(read)) => {
+                    *tick = read;
+                    FfiStatus::Ok
+                }
+                Ok(None) => FfiStatus::Empty,
+                Err(err) => status("rust_shm_read_tick", err),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_risk_control_through_c_abi() {
+        let rc = rust_risk_control_new(100);
+        let order = Order { order_id: 1, side: 0, price: 100.0, quantity: 10, ..Default::default() };
+        unsafe {
+            assert!(rust_risk_control_check_pre_trade(rc, &order, 0));
+            assert!(!rust_risk_control_check_pre_trade(rc, &order, 95));
+            assert!(!rust_risk_control_is_halted(rc));
+            
+            let mut position = -1;
+            assert_eq!(rust_risk_control_apply_fill(rc, 1, 0, 10, 100.0, &mut position), FfiStatus::Ok);
+            assert_eq!(position, 10);
+            assert_eq!(rust_risk_control_apply_fill(rc, 1, 1, 10, f64::NAN, &mut position), FfiStatus::InvalidArgument);
+            assert_eq!(position, 10);
+            assert_eq!(rust_risk_control_apply_fill(rc, 1, 1, 10, 101.0, &mut position), FfiStatus::Ok);
+            assert_eq!(position, 0);
+            assert_eq!(rust_risk_control_realized_pnl(rc), 10.0);
+            assert_eq!(rust_risk_control_apply_fill(std::ptr::null_mut(), 1, 0, 1, 1.0, &mut position), FfiStatus::NullPointer);
+            
+            rust_risk_control_trigger_kill_switch(rc);
+            assert!(rust_risk_control_is_halted(rc));
+            assert!(!rust_risk_control_check_pre_trade(rc, &order, 0));
+            rust_risk_control_free(rc);
+            
+            // Null handles fail closed
+            assert!(rust_risk_control_is_halted(std::ptr::null_mut()));
+            assert!(!rust_risk_control_check_pre_trade(std::ptr::null_mut(), &order, 0));
+            rust_risk_control_free(std::ptr::null_mut());
+        }
+    }
+    
+    #[test]
+    fn test_quotes_match_rust_market_maker() {
+        let tick = MarketTick { bid_price: 99.99, ask_price: 100.01, mid_price: 100.0, ..Default::default() };
+        let expected = MarketMaker::new(0.1, 0.2, 0.01).generate_quotes(&tick, 50);
+        
+        let mm = rust_market_maker_new(0.1, 0.2, 0.01);
+        let (mut bid, mut ask) = (0.0, 0.0);
+        unsafe {
+            rust_market_maker_generate_quotes(mm, &tick, 50, &mut bid, &mut ask);
+            rust_market_maker_free(mm);
+        }
+        assert_eq!((bid, ask), expected);
+    }
+    
+    #[test]
+    fn test_queue_through_c_abi() {
+        assert!(rust_queue_new(1000).is_null());
+        let queue = rust_queue_new(2);
+        let tick = MarketTick { timestamp_ns: 5, ..Default::default() };
+        let mut out = MarketTick::default();
+        unsafe {
+            assert!(rust_queue_is_empty(queue));
+            assert!(rust_queue_push(queue, &tick));
+            assert!(rust_queue_push(queue, &tick));
+            assert!(!rust_queue_push(queue, &tick));
+            assert_eq!(rust_queue_size(queue), 2);
+            
+            assert!(rust_queue_pop(queue, &mut out));
+            assert_eq!(out.timestamp_ns, 5);
+            assert!(rust_queue_pop(queue, &mut out));
+            assert!(!rust_queue_pop(queue, &mut out));
+            rust_queue_free(queue);
+        }
+    }
+    
+    #[test]
+    fn test_exec_callbacks_feed_queue() {
+        let (sink, mut events) = exec_channel::<2>();
+        let callbacks = sink.callbacks();
+        let order = Order { order_id: 7, quantity: 10, ..Default::default() };
+        let mut report = ExecutionReport::for_order(&order, ExecType::New);
+        unsafe {
+            assert!((callbacks.on_ack)(callbacks.ctx, &report));
+            report.last_quantity = 4;
+            report.leaves_quantity = 6;
+            assert!((callbacks.on_fill)(callbacks.ctx, &report));
+            assert!(!(callbacks.on_reject)(callbacks.ctx, &report));  // Full
+            assert!(!(callbacks.on_ack)(callbacks.ctx, std::ptr::null()));
+        }
+        assert_eq!(sink.dropped(), 1);
+        
+        assert_eq!(events.pop().unwrap().exec_type(), Some(ExecType::New));
+        let fill = events.pop().unwrap();
+        assert_eq!((fill.exec_type(), fill.order_id), (Some(ExecType::PartialFill), 7));
+        assert!(events.pop().is_none());
+        unsafe { (callbacks.release)(callbacks.ctx) };
+    }
+    
+    #[test]
+    fn test_callback_in_flight_outlives_sink() {
+        let (sink, mut events) = exec_channel::<2>();
+        let callbacks = sink.callbacks();
+        let state = Arc::downgrade(&sink.state);
+        
+        // The gateway thread is still inside a callback when the strategy drops the sink
+        drop(sink);
+        let report = ExecutionReport::for_order(&Order { order_id: 9, ..Default::default() }, ExecType::New);
+        assert!(unsafe { (callbacks.on_cancel)(callbacks.ctx, &report) });
+        assert_eq!(events.pop().map(|r| r.order_id), Some(9));
+        
+        assert!(state.upgrade().is_some());
+        unsafe { (callbacks.release)(callbacks.ctx) };
+        assert!(state.upgrade().is_none());
+    }
+    
+    fn last_error() -> String {
+        unsafe { std::ffi::CStr::from_ptr(rust_last_error()) }.to_string_lossy().into_owned()
+    }
+    
+    #[test]
+    fn test_queue_status_codes() {
+        let queue = rust_queue_new(1);
+        let tick = MarketTick::default();
+        let mut out = MarketTick::default();
+        unsafe {
+            assert_eq!(rust_queue_pop_ex(queue, &mut out), FfiStatus::Empty);
+            assert_eq!(rust_queue_push_ex(queue, &tick), FfiStatus::Ok);
+            assert_eq!(rust_queue_push_ex(queue, &tick), FfiStatus::Full);
+            assert_eq!(last_error(), "");
+            
+            assert_eq!(rust_queue_push_ex(std::ptr::null_mut(), &tick), FfiStatus::NullPointer);
+            assert!(last_error().contains("null queue"));
+            rust_clear_last_error();
+            assert_eq!(last_error(), "");
+            rust_queue_free(queue);
+        }
+    }
+    
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_shm_ring_status_codes() {
+        use std::ffi::CString;
+        
+        let name = CString::new(format!("/hft_ffi_status_{}", std::process::id())).unwrap();
+        let mut ring = std::ptr::null_mut();
+        let tick = MarketTick { timestamp_ns: 9, ..Default::default() };
+        let mut out = MarketTick::default();
+        unsafe {
+            assert_eq!(rust_shm_attach(name.as_ptr(), &mut ring), FfiStatus::OsError);
+            assert!(ring.is_null());
+            assert!(last_error().starts_with("rust_shm_attach"));
+            
+            assert_eq!(rust_shm_create(name.as_ptr(), 4, &mut ring), FfiStatus::Ok);
+            assert_eq!(rust_shm_read_tick(ring, &mut out), FfiStatus::Empty);
+            assert_eq!(rust_shm_write_tick(ring, &tick), FfiStatus::Ok);
+            assert_eq!(rust_shm_read_tick(ring, &mut out), FfiStatus::Ok);
+            assert_eq!(out.timestamp_ns, 9);
+            assert_eq!(rust_shm_write_tick(std::ptr::null_mut(), &tick), FfiStatus::NullPointer);
+            rust_shm_free(ring);  // Creator's handle unlinks the segment
+        }
+    }
+}
@@ -0,0 +1,181 @@
+// FIX 4.2 message encoding for outbound orders
+// Only NewOrderSingle (35=D) for now; fields are written in standard header/body/trailer order
+
+use std::fmt;
+
+use crate::{HiResTimer, Order};
+
+const SOH: u8 = 0x01;
+
+/// Why an order couldn't be encoded
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EncodeError {
+    /// NaN or infinite limit price; FIX has no spelling for it
+    BadPrice(f64),
+    /// SenderCompID (49) or TargetCompID (56) contains SOH, which would end the field early
+    BadCompId { tag: u32 },
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::BadPrice(price) => write!(f, "price {} can't be encoded", price),
+            EncodeError::BadCompId { tag } => write!(f, "tag {} contains SOH", tag),
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {}
+
+#[inline(always)]
+fn put_field(buf: &mut Vec<u8>, tag: u32, value: &str) {
+    buf.extend_from_slice(tag.to_string().as_bytes());
+    buf.push(b'=');
+    buf.extend_from_slice(value.as_bytes());
+    buf.push(SOH);
+}
+
+/// FIX price: fixed-point with trailing zeros trimmed (no exponent form)
+fn format_price(price: f64) -> Result<String, EncodeError> {
+    if !price.is_finite() {
+        return Err(EncodeError::BadPrice(price));
+    }
+    let fixed = format!("{:.8}", price);
+    Ok(fixed.trim_end_matches('0').trim_end_matches('.').to_string())
+}
+
+fn check_comp_id(tag: u32, value: &str) -> Result<(), EncodeError> {
+    if value.as_bytes().contains(&SOH) { Err(EncodeError::BadCompId { tag }) } else { Ok(()) }
+}
+
+/// UTC timestamp in FIX `YYYYMMDD-HH:MM:SS.sss` form
+pub fn format_utc_timestamp(unix_ns: i64) -> String {
+    let millis_total = unix_ns.div_euclid(1_000_000);
+    let days = millis_total.div_euclid(86_400_000);
+    let ms_of_day = millis_total.rem_euclid(86_400_000);
+    
+    // Civil date from days since 1970-01-01 (H. Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    
+    format!(
+        "{:04}{:02}{:02}-{:02}:{:02}:{:02}.{:03}",
+        year,
+        month,
+        day,
+        ms_of_day / 3_600_000,
+        (ms_of_day / 60_000) % 60,
+        (ms_of_day / 1_000) % 60,
+        ms_of_day % 1_000
+    )
+}
+
+/// Encode a limit NewOrderSingle stamped with the current UTC time
+pub fn encode_new_order_single(order: &Order, sender: &str, target: &str, seq: u32) -> Result<Vec<u8>, EncodeError> {
+    // Wall clock, not raw TSC: tag 52 must be comparable with the venue's clock
+    encode_new_order_single_at(order, sender, target, seq, HiResTimer::unix_now_ns())
+}
+
+/// Encode a limit NewOrderSingle with an explicit SendingTime (UNIX ns)
+pub fn encode_new_order_single_at(
+    order: &Order,
+    sender: &str,
+    target: &str,
+    seq: u32,
+    sending_time_unix_ns: i64,
+) -> Result<Vec<u8>, EncodeError> {
+    check_comp_id(49, sender)?;
+    check_comp_id(56, target)?;
+    let price = format_price(order.price)?;
+    
+    let mut body = Vec::with_capacity(128);
+    put_field(&mut body, 35, "D");
+    put_field(&mut body, 49, sender);
+    put_field(&mut body, 56, target);
+    put_field(&mut body, 34, &seq.to_string());
+    put_field(&mut body, 52, &format_utc_timestamp(sending_time_unix_ns));
+    put_field(&mut body, 11, &order.order_id.to_string());
+    put_field(&mut body, 55, &order.asset_id.to_string());
+    put_field(&mut body, 54, if order.side == 0 { "1" } else { "2" });  // 1 = Buy, 2 = Sell
+    put_field(&mut body, 38, &order.quantity.to_string());
+    put_field(&mut body, 40, "2");  // Limit
+    put_field(&mut body, 44, &price);
+    
+    let mut msg = Vec::with_capacity(body.len() + 32);
+    put_field(&mut msg, 8, "FIX.4.2");
+    put_field(&mut msg, 9, &body.len().to_string());
+    msg.extend_from_slice(&body);
+    
+    let checksum = msg.iter().fold(0u32, |acc, &b| acc + b as u32) % 256;
+    put_field(&mut msg, 10, &format!("{:03}", checksum));
+    Ok(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn known_order() -> Order {
+        Order {
+            order_id: 123456,
+            asset_id: 42,
+            side: 1,
+            price: 101.25,
+            quantity: 500,
+            ..Default::default()
+        }
+    }
+    
+    #[test]
+    fn test_new_order_single_layout() {
+        // 2024-03-15 13:45:30.123 UTC
+        let msg = encode_new_order_single_at(&known_order(), "HFTCO", "VENUE", 7, 1_710_510_330_123_000_000).unwrap();
+        let text = String::from_utf8(msg.clone()).unwrap().replace('\x01', "|");
+        
+        assert_eq!(
+            text,
+            "8=FIX.4.2|9=96|35=D|49=HFTCO|56=VENUE|34=7|52=20240315-13:45:30.123|\
+             11=123456|55=42|54=2|38=500|40=2|44=101.25|10=124|"
+        );
+        
+        // Tag 9 counts from the byte after its SOH up to and including the SOH before tag 10
+        let body_start = text.find("35=").unwrap();
+        let trailer_start = text.rfind("10=").unwrap();
+        assert_eq!(trailer_start - body_start, 96);
+        
+        let checksum = msg[..trailer_start].iter().map(|&b| b as u32).sum::<u32>() % 256;
+        assert_eq!(format!("10={:03}|", checksum), &text[trailer_start..]);
+    }
+    
+    #[test]
+    fn test_utc_and_price_formatting() {
+        assert_eq!(format_utc_timestamp(0), "19700101-00:00:00.000");
+        assert_eq!(format_utc_timestamp(951_782_400_000_000_000), "20000229-00:00:00.000");
+        assert_eq!(format_price(100.0).unwrap(), "100");
+        assert_eq!(format_price(0.1 + 0.2).unwrap(), "0.3");
+        assert_eq!(format_price(99.995).unwrap(), "99.995");
+    }
+    
+    #[test]
+    fn test_unencodable_orders_are_refused() {
+        for price in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let order = Order { price, ..known_order() };
+            assert!(matches!(encode_new_order_single_at(&order, "HFTCO", "VENUE", 1, 0), Err(EncodeError::BadPrice(_))));
+        }
+        assert_eq!(
+            encode_new_order_single_at(&known_order(), "HFT\x01CO", "VENUE", 1, 0),
+            Err(EncodeError::BadCompId { tag: 49 })
+        );
+        assert_eq!(
+            encode_new_order_single_at(&known_order(), "HFTCO", "VENUE\x0156=EVIL", 1, 0),
+            Err(EncodeError::BadCompId { tag: 56 })
+        );
+    }
+}
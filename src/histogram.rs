@@ -0,0 +1,554 @@
+// Histograms for monitoring quoting and latency behaviour
+// Bucketing is deterministic: callers supply the timestamps, nothing here reads a clock
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::Order;
+
+// Quote Lifetime Histogram
+
+/// How a resting quote left the book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteOutcome {
+    Filled,
+    Cancelled,
+}
+
+/// Records how long each resting order lives between submit and fill/cancel.
+///
+/// Buckets are defined by ascending inclusive upper bounds in nanoseconds,
+/// with one extra overflow bucket for anything beyond the last bound.
+pub struct QuoteLifetimeHistogram {
+    bounds_ns: Vec<u64>,
+    filled: Vec<u64>,
+    cancelled: Vec<u64>,
+    resting: HashMap<u64, i64>,  // order_id -> submit_time_ns
+}
+
+impl QuoteLifetimeHistogram {
+    /// Default buckets: 1us, 10us, 100us, 1ms, 10ms, 100ms, 1s, overflow
+    pub const DEFAULT_BOUNDS_NS: [u64; 7] = [
+        1_000,
+        10_000,
+        100_000,
+        1_000_000,
+        10_000_000,
+        100_000_000,
+        1_000_000_000,
+    ];
+    
+    pub fn new(bounds_ns: &[u64]) -> Self {
+        assert!(!bounds_ns.is_empty(), "At least one bucket bound required");
+        assert!(
+            bounds_ns.windows(2).all(|w| w[0] < w[1]),
+            "Bucket bounds must be strictly ascending"
+        );
+        
+        Self {
+            bounds_ns: bounds_ns.to_vec(),
+            filled: vec![0; bounds_ns.len() + 1],
+            cancelled: vec![0; bounds_ns.len() + 1],
+            resting: HashMap::new(),
+        }
+    }
+    
+    /// Start tracking a resting order from its `submit_time_ns`
+    pub fn on_submit(&mut self, order: &Order) {
+        self.resting.insert(order.order_id, order.submit_time_ns);
+    }
+    
+    /// Close a resting order as filled; returns the recorded lifetime
+    pub fn on_fill(&mut self, order_id: u64, now_ns: i64) -> Option<u64> {
+        self.close(order_id, now_ns, QuoteOutcome::Filled)
+    }
+    
+    /// Close a resting order as cancelled; returns the recorded lifetime
+    pub fn on_cancel(&mut self, order_id: u64, now_ns: i64) -> Option<u64> {
+        self.close(order_id, now_ns, QuoteOutcome::Cancelled)
+    }
+    
+    fn close(&mut self, order_id: u64, now_ns: i64, outcome: QuoteOutcome) -> Option<u64> {
+        let submit_ns = self.resting.remove(&order_id)?;
+        // Clock skew between stamping sites must not produce a negative lifetime
+        let lifetime_ns = now_ns.saturating_sub(submit_ns).max(0) as u64;
+        let idx = self.bucket_index(lifetime_ns);
+        
+        match outcome {
+            QuoteOutcome::Filled => self.filled[idx] += 1,
+            QuoteOutcome::Cancelled => self.cancelled[idx] += 1,
+        }
+        Some(lifetime_ns)
+    }
+    
+    /// Index of the bucket a lifetime falls into (last index = overflow)
+    #[inline(always)]
+    pub fn bucket_index(&self, lifetime_ns: u64) -> usize {
+        self.bounds_ns.partition_point(|&bound| bound < lifetime_ns)
+    }
+    
+    pub fn bucket_bounds(&self) -> &[u64] {
+        &self.bounds_ns
+    }
+    
+    /// Per-bucket counts for one outcome; length is `bucket_bounds().len() + 1`
+    pub fn counts(&self, outcome: QuoteOutcome) -> &[u64] {
+        match outcome {
+            QuoteOutcome::Filled => &self.filled,
+            QuoteOutcome::Cancelled => &self.cancelled,
+        }
+    }
+    
+    /// Orders submitted but not yet filled or cancelled
+    pub fn resting_count(&self) -> usize {
+        self.resting.len()
+    }
+    
+    /// Total lifetimes recorded across both outcomes
+    pub fn total(&self) -> u64 {
+        self.filled.iter().chain(self.cancelled.iter()).sum()
+    }
+}
+
+impl Default for QuoteLifetimeHistogram {
+    fn default() -> Self {
+        Self::new(&Self::DEFAULT_BOUNDS_NS)
+    }
+}
+
+// Latency Histogram (HDR-style log/linear buckets)
+
+/// Lock-free latency histogram with fixed HDR-style buckets.
+///
+/// Values below `SUB_BUCKETS` are recorded exactly; above that every power of
+/// two is split into `SUB_BUCKETS` linear sub-buckets, so any recorded value
+/// is reported with at most 1/`SUB_BUCKETS` relative error. Memory is fixed at
+/// construction and `record_ns` is three relaxed atomic RMWs.
+pub struct LatencyHistogram {
+    buckets: Box<[AtomicU64]>,
+    sum: AtomicU64,
+    max: AtomicU64,
+}
+
+impl LatencyHistogram {
+    pub const SUB_BUCKET_BITS: u32 = 4;
+    pub const SUB_BUCKETS: usize = 1 << Self::SUB_BUCKET_BITS;
+    pub const BUCKET_COUNT: usize = (65 - Self::SUB_BUCKET_BITS as usize) * Self::SUB_BUCKETS;
+    
+    pub fn new() -> Self {
+        Self {
+            buckets: (0..Self::BUCKET_COUNT).map(|_| AtomicU64::new(0)).collect(),
+            sum: AtomicU64::new(0),
+            max: AtomicU64::new(0),
+        }
+    }
+    
+    /// Record one latency sample in nanoseconds (hot path). For a span
+    /// measured with `HiResTimer::ticks()`, pass `HiResTimer::ticks_to_ns(end - start)`
+    #[inline(always)]
+    pub fn record_ns(&self, ns: u64) {
+        self.buckets[Self::bucket_index(ns)].fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(ns, Ordering::Relaxed);
+        self.max.fetch_max(ns, Ordering::Relaxed);
+    }
+    
+    #[deprecated(note = "renamed to record_ns")]
+    #[inline(always)]
+    pub fn record(&self, ns: u64) {
+        self.record_ns(ns);
+    }
+    
+    /// Bucket a value maps to
+    #[inline(always)]
+    pub fn bucket_index(ns: u64) -> usize {
+        if ns < Self::SUB_BUCKETS as u64 {
+            return ns as usize;
+        }
+        let msb = 63 - ns.leading_zeros();
+        let shift = msb - Self::SUB_BUCKET_BITS;
+        let mantissa = (ns >> shift) as usize;  // In [SUB_BUCKETS, 2 * SUB_BUCKETS)
+        shift as usize * Self::SUB_BUCKETS + mantissa
+    }
+    
+    /// Inclusive `(lower, upper)` value range covered by a bucket
+    pub fn bucket_range(index: usize) -> (u64, u64) {
+        if index < Self::SUB_BUCKETS {
+            return (index as u64, index as u64);
+        }
+        let shift = (index / Self::SUB_BUCKETS - 1) as u32;
+        let mantissa = (index - shift as usize * Self::SUB_BUCKETS) as u64;
+        let lower = mantissa << shift;
+        let upper = lower.saturating_add((1u64 << shift) - 1);
+        (lower, upper)
+    }
+    
+    /// Value at percentile `p` (0..=100), reported as the upper edge of the
+    /// bucket holding that rank and clamped to the observed max; 0 if empty
+    pub fn percentile(&self, p: f64) -> u64 {
+        self.snapshot().percentile(p)
+    }
+    
+    /// p50/p99/p99.9/max in one pass over a snapshot
+    pub fn percentiles(&self) -> Percentiles {
+        self.snapshot().percentiles()
+    }
+    
+    /// Samples recorded, summed over the buckets so it always agrees with them
+    pub fn count(&self) -> u64 {
+        self.buckets.iter().map(|bucket| bucket.load(Ordering::Relaxed)).sum()
+    }
+    
+    /// Sum of all recorded values (wraps on overflow)
+    #[inline(always)]
+    pub fn sum(&self) -> u64 {
+        self.sum.load(Ordering::Relaxed)
+    }
+    
+    /// Recorded count per bucket index, skipping empty buckets
+    pub fn nonzero_buckets(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        self.buckets
+            .iter()
+            .enumerate()
+            .map(|(index, bucket)| (index, bucket.load(Ordering::Relaxed)))
+            .filter(|&(_, n)| n > 0)
+    }
+    
+    #[inline(always)]
+    pub fn max(&self) -> u64 {
+        self.max.load(Ordering::Relaxed)
+    }
+    
+    /// Copy every bucket; `count` and `max` are derived from the copied
+    /// buckets so the rows and percentiles agree even while recording continues
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        let buckets: Vec<u64> = self.buckets.iter().map(|bucket| bucket.load(Ordering::Relaxed)).collect();
+        HistogramSnapshot::from_buckets(buckets, self.sum(), self.max())
+    }
+    
+    /// Snapshot of everything recorded since the previous call, zeroing the
+    /// histogram as it goes; each sample lands in exactly one interval even
+    /// while recording continues. Merge intervals for longer windows
+    pub fn take_interval(&self) -> HistogramSnapshot {
+        let buckets: Vec<u64> = self.buckets.iter().map(|bucket| bucket.swap(0, Ordering::Relaxed)).collect();
+        let sum = self.sum.swap(0, Ordering::Relaxed);
+        HistogramSnapshot::from_buckets(buckets, sum, self.max.swap(0, Ordering::Relaxed))
+    }
+    
+    /// `bucket_lower_ns,bucket_upper_ns,count` rows for non-empty buckets, with a header
+    pub fn to_csv(&self) -> String {
+        self.snapshot().to_csv()
+    }
+    
+    /// Non-empty buckets plus count/sum/max and p50/p99/p999
+    pub fn to_json(&self) -> String {
+        self.snapshot().to_json()
+    }
+    
+    pub fn reset(&self) {
+        for bucket in self.buckets.iter() {
+            bucket.store(0, Ordering::Relaxed);
+        }
+        self.sum.store(0, Ordering::Relaxed);
+        self.max.store(0, Ordering::Relaxed);
+    }
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Upper edge of the bucket holding rank `p`, clamped to `max`
+fn value_at_percentile(counts: impl Iterator<Item = u64>, count: u64, max: u64, p: f64) -> u64 {
+    let rank = ((p.clamp(0.0, 100.0) / 100.0) * count as f64).ceil().max(1.0) as u64;
+    
+    let mut seen = 0u64;
+    for (index, n) in counts.enumerate() {
+        seen += n;
+        if seen >= rank {
+            return LatencyHistogram::bucket_range(index).1.min(max);
+        }
+    }
+    max
+}
+
+/// Point-in-time copy of a `LatencyHistogram` for offline dumps.
+///
+/// `sum` is read separately from the buckets, so samples recorded during the
+/// copy may be reflected in it but not in `count`. `max` is the recorded max
+/// clamped into the highest non-empty bucket, so it always belongs to a
+/// sample the buckets hold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistogramSnapshot {
+    pub buckets: Vec<u64>,
+    pub count: u64,
+    pub sum: u64,
+    pub max: u64,
+}
+
+/// Headline latency figures, in nanoseconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Percentiles {
+    pub p50: u64,
+    pub p99: u64,
+    pub p999: u64,
+    pub max: u64,
+}
+
+impl HistogramSnapshot {
+    /// Snapshot with no samples, the identity for `merge`
+    pub fn empty() -> Self {
+        Self { buckets: vec![0; LatencyHistogram::BUCKET_COUNT], count: 0, sum: 0, max: 0 }
+    }
+    
+    // `recorded_max` comes from its own atomic and can belong to a sample
+    // outside these buckets (or miss one in them); bring it into the top bucket
+    fn from_buckets(buckets: Vec<u64>, sum: u64, recorded_max: u64) -> Self {
+        let max = buckets.iter().rposition(|&n| n > 0).map_or(0, |index| {
+            let (lower, upper) = LatencyHistogram::bucket_range(index);
+            recorded_max.clamp(lower, upper)
+        });
+        Self { count: buckets.iter().sum(), sum, max, buckets }
+    }
+    
+    /// Fold `other` in, e.g. per-thread histograms or consecutive intervals
+    pub fn merge(&mut self, other: &HistogramSnapshot) {
+        if self.buckets.len() < other.buckets.len() {
+            self.buckets.resize(other.buckets.len(), 0);
+        }
+        for (mine, theirs) in self.buckets.iter_mut().zip(&other.buckets) {
+            *mine += theirs;
+        }
+        self.count += other.count;
+        self.sum = self.sum.wrapping_add(other.sum);
+        self.max = self.max.max(other.max);
+    }
+    
+    pub fn percentiles(&self) -> Percentiles {
+        Percentiles {
+            p50: self.percentile(50.0),
+            p99: self.percentile(99.0),
+            p999: self.percentile(99.9),
+            max: self.max,
+        }
+    }
+    
+    /// Same reporting rule as `LatencyHistogram::percentile`
+    pub fn percentile(&self, p: f64) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+        value_at_percentile(self.buckets.iter().copied(), self.count, self.max, p)
+    }
+    
+    /// `(lower_ns, upper_ns, count)` for every non-empty bucket
+    pub fn nonzero_buckets(&self) -> impl Iterator<Item = (u64, u64, u64)> + '_ {
+        self.buckets
+            .iter()
+            .enumerate()
+            .filter(|&(_, &n)| n > 0)
+            .map(|(index, &n)| {
+                let (lower, upper) = LatencyHistogram::bucket_range(index);
+                (lower, upper, n)
+            })
+    }
+    
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("bucket_lower_ns,bucket_upper_ns,count\n");
+        for (lower, upper, n) in self.nonzero_buckets() {
+            let _ = writeln!(out, "{lower},{upper},{n}");
+        }
+        out
+    }
+    
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        let _ = write!(
+            out,
+            "{{\"count\":{},\"sum\":{},\"max\":{},\"p50\":{},\"p99\":{},\"p999\":{},\"buckets\":[",
+            self.count,
+            self.sum,
+            self.max,
+            self.percentile(50.0),
+            self.percentile(99.0),
+            self.percentile(99.9),
+        );
+        for (i, (lower, upper, n)) in self.nonzero_buckets().enumerate() {
+            let sep = if i == 0 { "" } else { "," };
+            let _ = write!(out, "{sep}{{\"lower_ns\":{lower},\"upper_ns\":{upper},\"count\":{n}}}");
+        }
+        out.push_str("]}");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn order(order_id: u64, submit_time_ns: i64) -> Order {
+        Order { order_id, submit_time_ns, ..Default::default() }
+    }
+    
+    #[test]
+    fn test_quote_lifetimes_land_in_buckets() {
+        let mut hist = QuoteLifetimeHistogram::default();
+        
+        hist.on_submit(&order(1, 0));
+        hist.on_submit(&order(2, 1_000));
+        hist.on_submit(&order(3, 5_000));
+        hist.on_submit(&order(4, 0));
+        assert_eq!(hist.resting_count(), 4);
+        
+        assert_eq!(hist.on_fill(1, 500), Some(500));              // <= 1us
+        assert_eq!(hist.on_cancel(2, 51_000), Some(50_000));      // <= 100us
+        assert_eq!(hist.on_fill(3, 2_005_000), Some(2_000_000));  // <= 10ms
+        assert_eq!(hist.on_cancel(4, 5_000_000_000), Some(5_000_000_000)); // overflow
+        
+        assert_eq!(hist.counts(QuoteOutcome::Filled), &[1, 0, 0, 0, 1, 0, 0, 0]);
+        assert_eq!(hist.counts(QuoteOutcome::Cancelled), &[0, 0, 1, 0, 0, 0, 0, 1]);
+        assert_eq!(hist.total(), 4);
+        assert_eq!(hist.resting_count(), 0);
+    }
+    
+    #[test]
+    fn test_quote_lifetime_unknown_and_boundary() {
+        let mut hist = QuoteLifetimeHistogram::new(&[100, 200]);
+        assert_eq!(hist.on_fill(99, 10), None);
+        
+        hist.on_submit(&order(1, 0));
+        hist.on_submit(&order(2, 0));
+        hist.on_fill(1, 100);  // Upper bound is inclusive
+        hist.on_fill(2, 101);
+        assert_eq!(hist.counts(QuoteOutcome::Filled), &[1, 1, 0]);
+    }
+    
+    #[test]
+    fn test_latency_bucket_boundaries() {
+        // Exact region
+        for v in 0..LatencyHistogram::SUB_BUCKETS as u64 {
+            assert_eq!(LatencyHistogram::bucket_range(LatencyHistogram::bucket_index(v)), (v, v));
+        }
+        // Every value sits inside its bucket and buckets tile without gaps
+        let mut expected_lower = 0u64;
+        for index in 0..LatencyHistogram::BUCKET_COUNT {
+            let (lower, upper) = LatencyHistogram::bucket_range(index);
+            assert_eq!(lower, expected_lower);
+            assert_eq!(LatencyHistogram::bucket_index(lower), index);
+            assert_eq!(LatencyHistogram::bucket_index(upper), index);
+            expected_lower = upper.wrapping_add(1);
+        }
+        assert_eq!(LatencyHistogram::bucket_index(u64::MAX), LatencyHistogram::BUCKET_COUNT - 1);
+    }
+    
+    #[test]
+    fn test_latency_percentiles() {
+        let hist = LatencyHistogram::new();
+        assert_eq!(hist.percentile(50.0), 0);
+        
+        for ns in 1..=1000u64 {
+            hist.record_ns(ns);
+        }
+        assert_eq!(hist.count(), 1000);
+        assert_eq!(hist.sum(), 500_500);
+        assert_eq!(hist.max(), 1000);
+        
+        // 500 lives in [496, 511] with 16 sub-buckets per octave
+        assert_eq!(hist.percentile(50.0), 511);
+        // 990 lives in [960, 991]
+        assert_eq!(hist.percentile(99.0), 991);
+        // Top bucket is clamped to the observed max
+        assert_eq!(hist.percentile(99.9), 1000);
+        assert_eq!(hist.percentile(100.0), 1000);
+        // Exact region is exact
+        assert_eq!(hist.percentile(0.5), 5);
+        
+        hist.reset();
+        assert_eq!(hist.count(), 0);
+        assert_eq!(hist.max(), 0);
+        assert_eq!(hist.percentile(99.0), 0);
+    }
+    
+    #[test]
+    fn test_interval_snapshots_merge() {
+        let hist = LatencyHistogram::new();
+        std::thread::scope(|s| {
+            for t in 0..4u64 {
+                let hist = &hist;
+                s.spawn(move || {
+                    for ns in 1..=250u64 {
+                        hist.record_ns(t * 250 + ns);
+                    }
+                });
+            }
+        });
+        
+        let first = hist.take_interval();
+        assert_eq!(first.count, 1000);
+        assert_eq!(hist.count(), 0);
+        assert_eq!(hist.percentile(50.0), 0);
+        
+        hist.record_ns(5_000);
+        let second = hist.take_interval();
+        assert_eq!(second.percentiles(), Percentiles { p50: 5_000, p99: 5_000, p999: 5_000, max: 5_000 });
+        
+        let mut window = HistogramSnapshot::empty();
+        window.merge(&first);
+        window.merge(&second);
+        assert_eq!(window.count, 1001);
+        assert_eq!(window.sum, 500_500 + 5_000);
+        assert_eq!(window.percentiles(), Percentiles { p50: 511, p99: 991, p999: 1023, max: 5_000 });
+    }
+    
+    #[test]
+    fn test_interval_max_comes_from_its_buckets() {
+        let hist = LatencyHistogram::new();
+        hist.record_ns(100);
+        // A racing record_ns(9_000) has raised max but not reached its bucket yet
+        hist.max.fetch_max(9_000, Ordering::Relaxed);
+        let first = hist.take_interval();
+        assert_eq!((first.count, first.max), (1, 103));
+        assert_eq!(first.percentile(100.0), 103);
+        
+        // Its bucket lands after the swap reset max: still not reported as 0
+        hist.buckets[LatencyHistogram::bucket_index(9_000)].fetch_add(1, Ordering::Relaxed);
+        assert_eq!(hist.count(), 1);
+        let second = hist.take_interval();
+        assert_eq!((second.count, second.max), (1, LatencyHistogram::bucket_range(LatencyHistogram::bucket_index(9_000)).0));
+        assert_eq!(hist.take_interval(), HistogramSnapshot::empty());
+    }
+    
+    #[test]
+    fn test_latency_csv_and_json_dump() {
+        let hist = LatencyHistogram::new();
+        assert_eq!(hist.to_csv(), "bucket_lower_ns,bucket_upper_ns,count\n");
+        
+        // 3 and 7 are exact; 100 and 101 share [100, 103]; 5000 is alone
+        for ns in [3, 3, 7, 100, 101, 5_000] {
+            hist.record_ns(ns);
+        }
+        
+        let csv = hist.to_csv();
+        let rows: Vec<Vec<u64>> = csv
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').map(|x| x.parse().unwrap()).collect())
+            .collect();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0], vec![3, 3, 2]);
+        assert_eq!(rows[2], vec![100, 103, 2]);
+        assert_eq!(rows.iter().map(|row| row[2]).sum::<u64>(), hist.count());
+        
+        let json: serde_json::Value = serde_json::from_str(&hist.to_json()).unwrap();
+        assert_eq!(json["count"], 6);
+        assert_eq!(json["sum"], 5_214);
+        assert_eq!(json["max"], 5_000);
+        assert_eq!(json["p50"], hist.percentile(50.0));
+        assert_eq!(json["p99"], 5_000);
+        assert_eq!(json["p999"], 5_000);
+        assert_eq!(json["buckets"].as_array().unwrap().len(), 4);
+        assert_eq!(json["buckets"][3]["count"], 1);
+    }
+}
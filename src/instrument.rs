@@ -0,0 +1,206 @@
+// Instrument reference data: exchange tick and lot rules per asset
+// Orders are snapped to these rules before they leave the engine, so a
+// venue never sees an off-tick price or a sub-lot quantity.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{round_to_tick, Order};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Instrument {
+    pub tick_size: f64,
+    pub lot_size: u64,  // 0 rounds as 1 (no lot rule)
+    pub min_qty: u64,
+    pub max_qty: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundError {
+    UnknownInstrument(u32),
+    /// Quantity after rounding down to a lot multiple
+    BelowMinQty { quantity: u64, min_qty: u64 },
+    AboveMaxQty { quantity: u64, max_qty: u64 },
+}
+
+impl fmt::Display for RoundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RoundError::UnknownInstrument(asset_id) => write!(f, "no instrument for asset {}", asset_id),
+            RoundError::BelowMinQty { quantity, min_qty } => write!(f, "quantity {} below minimum {}", quantity, min_qty),
+            RoundError::AboveMaxQty { quantity, max_qty } => write!(f, "quantity {} above maximum {}", quantity, max_qty),
+        }
+    }
+}
+
+impl std::error::Error for RoundError {}
+
+impl Instrument {
+    pub fn new(tick_size: f64, lot_size: u64) -> Self {
+        Self {
+            tick_size,
+            lot_size: lot_size.max(1),
+            min_qty: lot_size.max(1),
+            max_qty: u64::MAX,
+        }
+    }
+    
+    pub fn with_qty_limits(mut self, min_qty: u64, max_qty: u64) -> Self {
+        self.min_qty = min_qty;
+        self.max_qty = max_qty;
+        self
+    }
+    
+    /// Snap `price` to the nearest tick and `quantity` down to a lot multiple.
+    /// The order is left untouched on error
+    pub fn round_order(&self, order: &mut Order) -> Result<(), RoundError> {
+        // Fields are public, so a 0 lot can get past `new`; don't divide by it
+        let quantity = order.quantity - order.quantity % self.lot_size.max(1);
+        if quantity < self.min_qty {
+            return Err(RoundError::BelowMinQty { quantity, min_qty: self.min_qty });
+        }
+        if quantity > self.max_qty {
+            return Err(RoundError::AboveMaxQty { quantity, max_qty: self.max_qty });
+        }
+        
+        order.price = round_to_tick(order.price, self.tick_size);
+        order.quantity = quantity;
+        Ok(())
+    }
+}
+
+/// Instruments keyed by `asset_id`
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentTable {
+    instruments: HashMap<u32, Instrument>,
+}
+
+impl InstrumentTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn with_instrument(mut self, asset_id: u32, instrument: Instrument) -> Self {
+        self.instruments.insert(asset_id, instrument);
+        self
+    }
+    
+    pub fn get(&self, asset_id: u32) -> Option<&Instrument> {
+        self.instruments.get(&asset_id)
+    }
+    
+    /// Apply the rules of the order's own instrument
+    pub fn round_order(&self, order: &mut Order) -> Result<(), RoundError> {
+        self.get(order.asset_id)
+            .ok_or(RoundError::UnknownInstrument(order.asset_id))?
+            .round_order(order)
+    }
+}
+
+// Order Builder
+
+/// Assembles an `Order`, applying instrument rules on `build` when given.
+pub struct OrderBuilder<'a> {
+    order: Order,
+    instruments: Option<&'a InstrumentTable>,
+}
+
+impl<'a> OrderBuilder<'a> {
+    pub fn new(order_id: u64, asset_id: u32, side: u8) -> Self {
+        Self {
+            order: Order { order_id, asset_id, side, is_active: true, ..Default::default() },
+            instruments: None,
+        }
+    }
+    
+    pub fn with_price(mut self, price: f64) -> Self {
+        self.order.price = price;
+        self
+    }
+    
+    pub fn with_quantity(mut self, quantity: u64) -> Self {
+        self.order.quantity = quantity;
+        self
+    }
+    
+    pub fn with_venue(mut self, venue_id: u8) -> Self {
+        self.order.venue_id = venue_id;
+        self
+    }
+    
+    pub fn with_submit_time(mut self, submit_time_ns: i64) -> Self {
+        self.order.submit_time_ns = submit_time_ns;
+        self
+    }
+    
+    /// Round against `instruments`; the asset must then be registered there
+    pub fn with_instruments(mut self, instruments: &'a InstrumentTable) -> Self {
+        self.instruments = Some(instruments);
+        self
+    }
+    
+    pub fn build(self) -> Result<Order, RoundError> {
+        let mut order = self.order;
+        if let Some(instruments) = self.instruments {
+            instruments.round_order(&mut order)?;
+        }
+        Ok(order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_round_order_to_tick_and_lot() {
+        let table = InstrumentTable::new().with_instrument(7, Instrument::new(0.01, 10).with_qty_limits(20, 1_000));
+        
+        let order = OrderBuilder::new(1, 7, 0)
+            .with_price(1.237)
+            .with_quantity(153)
+            .with_instruments(&table)
+            .build()
+            .unwrap();
+        assert!((order.price - 1.24).abs() < 1e-12);
+        assert_eq!(order.quantity, 150);
+        assert_eq!(order.order_id, 1);
+        assert!(order.is_active);
+        
+        // Rounds down to 10, under the 20 minimum
+        let below = OrderBuilder::new(2, 7, 1).with_price(1.0).with_quantity(19).with_instruments(&table).build();
+        assert_eq!(below.err(), Some(RoundError::BelowMinQty { quantity: 10, min_qty: 20 }));
+        
+        let above = OrderBuilder::new(3, 7, 1).with_price(1.0).with_quantity(5_000).with_instruments(&table).build();
+        assert_eq!(above.err(), Some(RoundError::AboveMaxQty { quantity: 5_000, max_qty: 1_000 }));
+        
+        let unknown = OrderBuilder::new(4, 8, 0).with_quantity(100).with_instruments(&table).build();
+        assert_eq!(unknown.err(), Some(RoundError::UnknownInstrument(8)));
+        
+        // Without instruments nothing is adjusted
+        let raw = OrderBuilder::new(5, 8, 0).with_price(1.237).with_quantity(153).build().unwrap();
+        assert_eq!((raw.price, raw.quantity), (1.237, 153));
+    }
+    
+    #[test]
+    fn test_round_order_leaves_order_on_error() {
+        let instrument = Instrument::new(0.05, 100);
+        let mut order = Order { price: 10.02, quantity: 99, ..Default::default() };
+        assert!(instrument.round_order(&mut order).is_err());
+        assert_eq!((order.price, order.quantity), (10.02, 99));
+        
+        order.quantity = 250;
+        assert_eq!(instrument.round_order(&mut order), Ok(()));
+        assert!((order.price - 10.0).abs() < 1e-12);
+        assert_eq!(order.quantity, 200);
+    }
+    
+    #[test]
+    fn test_zero_lot_size_rounds_as_one() {
+        assert_eq!(Instrument::new(0.01, 0).lot_size, 1);
+        let instrument = Instrument { lot_size: 0, min_qty: 0, ..Instrument::new(0.01, 1) };
+        let mut order = Order { price: 10.0, quantity: 37, ..Default::default() };
+        assert_eq!(instrument.round_order(&mut order), Ok(()));
+        assert_eq!(order.quantity, 37);
+    }
+}
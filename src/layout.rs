@@ -0,0 +1,91 @@
+// FFI layout contract
+// `MarketTick`, `Order` and `ExecutionReport` are shared byte-for-byte with the C++ engine
+// (`include/common_types.hpp`) and through shared memory. These checks run at
+// compile time, so a field reorder or type change breaks the build instead of
+// silently corrupting the other side. `tests/layout_dump.rs` prints the same
+// facts for `scripts/check_layout.sh` to diff against the C++ compiler's view.
+
+use std::mem::{align_of, offset_of, size_of};
+
+use crate::{ExecutionReport, MarketTick, Order};
+
+// MarketTick: alignas(64), 392 bytes of fields padded to 448
+const _: () = assert!(size_of::<MarketTick>() == 448);
+const _: () = assert!(align_of::<MarketTick>() == 64);
+const _: () = assert!(offset_of!(MarketTick, timestamp_ns) == 0);
+const _: () = assert!(offset_of!(MarketTick, bid_price) == 8);
+const _: () = assert!(offset_of!(MarketTick, ask_price) == 16);
+const _: () = assert!(offset_of!(MarketTick, mid_price) == 24);
+const _: () = assert!(offset_of!(MarketTick, bid_size) == 32);
+const _: () = assert!(offset_of!(MarketTick, ask_size) == 40);
+const _: () = assert!(offset_of!(MarketTick, trade_volume) == 48);
+const _: () = assert!(offset_of!(MarketTick, trade_side) == 56);
+const _: () = assert!(offset_of!(MarketTick, asset_id) == 60);
+const _: () = assert!(offset_of!(MarketTick, depth_levels) == 64);
+const _: () = assert!(offset_of!(MarketTick, _padding) == 65);
+const _: () = assert!(offset_of!(MarketTick, bid_prices) == 72);
+const _: () = assert!(offset_of!(MarketTick, ask_prices) == 152);
+const _: () = assert!(offset_of!(MarketTick, bid_sizes) == 232);
+const _: () = assert!(offset_of!(MarketTick, ask_sizes) == 312);
+
+// Order: alignas(64), 48 bytes of fields padded to 64
+const _: () = assert!(size_of::<Order>() == 64);
+const _: () = assert!(align_of::<Order>() == 64);
+const _: () = assert!(offset_of!(Order, order_id) == 0);
+const _: () = assert!(offset_of!(Order, asset_id) == 8);
+const _: () = assert!(offset_of!(Order, side) == 12);
+const _: () = assert!(offset_of!(Order, price) == 16);
+const _: () = assert!(offset_of!(Order, quantity) == 24);
+const _: () = assert!(offset_of!(Order, submit_time_ns) == 32);
+const _: () = assert!(offset_of!(Order, venue_id) == 40);
+const _: () = assert!(offset_of!(Order, is_active) == 41);
+const _: () = assert!(offset_of!(Order, _padding) == 42);
+
+// ExecutionReport: alignas(64), exactly one cache line
+const _: () = assert!(size_of::<ExecutionReport>() == 64);
+const _: () = assert!(align_of::<ExecutionReport>() == 64);
+const _: () = assert!(offset_of!(ExecutionReport, order_id) == 0);
+const _: () = assert!(offset_of!(ExecutionReport, exec_id) == 8);
+const _: () = assert!(offset_of!(ExecutionReport, asset_id) == 16);
+const _: () = assert!(offset_of!(ExecutionReport, side) == 20);
+const _: () = assert!(offset_of!(ExecutionReport, exec_type) == 21);
+const _: () = assert!(offset_of!(ExecutionReport, venue_id) == 22);
+const _: () = assert!(offset_of!(ExecutionReport, last_price) == 24);
+const _: () = assert!(offset_of!(ExecutionReport, last_quantity) == 32);
+const _: () = assert!(offset_of!(ExecutionReport, cum_quantity) == 40);
+const _: () = assert!(offset_of!(ExecutionReport, leaves_quantity) == 48);
+const _: () = assert!(offset_of!(ExecutionReport, transact_time_ns) == 56);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_fields_at_cpp_offsets() {
+        // Lay out bytes the way the C++ side would, then view them as a tick
+        let mut raw = [0u8; 448];
+        raw[0..8].copy_from_slice(&1_234i64.to_ne_bytes());
+        raw[16..24].copy_from_slice(&100.25f64.to_ne_bytes());
+        raw[56] = 1;
+        raw[60..64].copy_from_slice(&77u32.to_ne_bytes());
+        raw[64] = 3;
+        raw[152 + 8..152 + 16].copy_from_slice(&100.5f64.to_ne_bytes());
+        raw[312 + 72..312 + 80].copy_from_slice(&9u64.to_ne_bytes());
+        
+        let tick = unsafe { std::ptr::read_unaligned(raw.as_ptr() as *const MarketTick) };
+        assert_eq!(tick.timestamp_ns, 1_234);
+        assert_eq!(tick.ask_price, 100.25);
+        assert_eq!(tick.trade_side, 1);
+        assert_eq!(tick.asset_id, 77);
+        assert_eq!(tick.depth_levels, 3);
+        assert_eq!(tick.ask_prices[1], 100.5);
+        assert_eq!(tick.ask_sizes[9], 9);
+        
+        let order = Order { order_id: 5, side: 1, quantity: 300, venue_id: 2, is_active: true, ..Default::default() };
+        let bytes = unsafe { std::slice::from_raw_parts(&order as *const Order as *const u8, 48) };
+        assert_eq!(bytes[0..8], 5u64.to_ne_bytes());
+        assert_eq!(bytes[12], 1);
+        assert_eq!(bytes[24..32], 300u64.to_ne_bytes());
+        assert_eq!((bytes[40], bytes[41]), (2, 1));
+    }
+}
@@ -0,0 +1,149 @@
+// Per-asset risk state without locks
+// `AssetMap` is a fixed-capacity open-addressed table from `asset_id` to an
+// `AtomicI64`. Lookups are a hash and a short linear probe over atomics, so
+// pre-trade checks can read per-asset limits and exposure on the hot path
+// while a control thread updates them intraday.
+//
+// Entries are claimed with a CAS on the key and never removed; a value is
+// reset by storing the map's default. `u32::MAX` marks an empty slot and
+// can't be used as an asset id.
+
+use std::sync::atomic::{AtomicI64, AtomicU32, Ordering};
+
+const EMPTY: u32 = u32::MAX;
+
+pub struct AssetMap {
+    keys: Box<[AtomicU32]>,
+    values: Box<[AtomicI64]>,
+    default: i64,
+}
+
+impl AssetMap {
+    /// Room for `capacity` assets (rounded up to a power of two); absent
+    /// assets read as `default`
+    pub fn new(capacity: usize, default: i64) -> Self {
+        let slots = capacity.max(1).next_power_of_two();
+        Self {
+            keys: (0..slots).map(|_| AtomicU32::new(EMPTY)).collect(),
+            values: (0..slots).map(|_| AtomicI64::new(default)).collect(),
+            default,
+        }
+    }
+    
+    #[inline(always)]
+    fn home(&self, asset_id: u32) -> usize {
+        // Fibonacci hashing spreads sequential ids across the table
+        (asset_id.wrapping_mul(0x9E37_79B9) as usize) & (self.keys.len() - 1)
+    }
+    
+    /// The asset's value cell, if it has one
+    #[inline(always)]
+    pub fn slot(&self, asset_id: u32) -> Option<&AtomicI64> {
+        let mask = self.keys.len() - 1;
+        let mut i = self.home(asset_id);
+        for _ in 0..self.keys.len() {
+            match self.keys[i].load(Ordering::Acquire) {
+                key if key == asset_id => return Some(&self.values[i]),
+                EMPTY => return None,
+                _ => i = (i + 1) & mask,
+            }
+        }
+        None
+    }
+    
+    /// The asset's value cell, claiming one (holding `default`) if needed;
+    /// `None` once the table is full or for the reserved id
+    pub fn entry(&self, asset_id: u32) -> Option<&AtomicI64> {
+        if asset_id == EMPTY {
+            return None;
+        }
+        let mask = self.keys.len() - 1;
+        let mut i = self.home(asset_id);
+        for _ in 0..self.keys.len() {
+            match self.keys[i].compare_exchange(EMPTY, asset_id, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => return Some(&self.values[i]),
+                Err(key) if key == asset_id => return Some(&self.values[i]),
+                Err(_) => i = (i + 1) & mask,
+            }
+        }
+        None
+    }
+    
+    #[inline(always)]
+    pub fn get(&self, asset_id: u32) -> i64 {
+        self.slot(asset_id).map_or(self.default, |value| value.load(Ordering::Acquire))
+    }
+    
+    /// False if the asset can't be given a slot
+    pub fn set(&self, asset_id: u32, value: i64) -> bool {
+        match self.entry(asset_id) {
+            Some(slot) => {
+                slot.store(value, Ordering::Release);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    pub fn default_value(&self) -> i64 {
+        self.default
+    }
+    
+    /// Every asset that has a slot, with its current value
+    pub fn iter(&self) -> impl Iterator<Item = (u32, i64)> + '_ {
+        self.keys.iter().zip(self.values.iter()).filter_map(|(key, value)| {
+            let key = key.load(Ordering::Acquire);
+            (key != EMPTY).then(|| (key, value.load(Ordering::Acquire)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_get_set_and_default() {
+        let map = AssetMap::new(4, -1);
+        assert_eq!(map.get(7), -1);
+        assert!(map.slot(7).is_none());
+        
+        assert!(map.set(7, 100));
+        assert!(map.set(7, 50));
+        assert_eq!(map.get(7), 50);
+        map.entry(9).unwrap().fetch_add(3, Ordering::AcqRel);
+        assert_eq!(map.get(9), 2);
+        
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort();
+        assert_eq!(entries, vec![(7, 50), (9, 2)]);
+        assert!(!map.set(u32::MAX, 1));
+    }
+    
+    #[test]
+    fn test_full_table_and_collisions() {
+        let map = AssetMap::new(3, 0);  // Rounded up to 4 slots
+        for asset_id in [1, 5, 9, 13] {
+            assert!(map.set(asset_id, asset_id as i64));
+        }
+        assert!(!map.set(17, 1));
+        assert_eq!(map.get(17), 0);
+        assert_eq!([1, 5, 9, 13].map(|id| map.get(id)), [1, 5, 9, 13]);
+    }
+    
+    #[test]
+    fn test_concurrent_claims_agree() {
+        let map = AssetMap::new(64, 0);
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for asset_id in 0..32 {
+                        map.entry(asset_id).unwrap().fetch_add(1, Ordering::AcqRel);
+                    }
+                });
+            }
+        });
+        assert_eq!(map.iter().count(), 32);
+        assert!((0..32).all(|asset_id| map.get(asset_id) == 4));
+    }
+}
@@ -0,0 +1,372 @@
+// Engine-wide counters for operators: message rates, fill ratio, rejections
+// Every increment is a single relaxed fetch_add so it can sit on the hot path
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::histogram::LatencyHistogram;
+
+#[derive(Default)]
+pub struct Metrics {
+    ticks_processed: AtomicU64,
+    orders_submitted: AtomicU64,
+    orders_rejected: AtomicU64,
+    fills: AtomicU64,
+    cancels: AtomicU64,
+    position_mismatches: AtomicU64,
+    feed_gaps: AtomicU64,
+}
+
+/// Point-in-time copy of every counter
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    pub ticks_processed: u64,
+    pub orders_submitted: u64,
+    pub orders_rejected: u64,
+    pub fills: u64,
+    pub cancels: u64,
+    pub position_mismatches: u64,
+    pub feed_gaps: u64,
+}
+
+/// Per-second rates between two snapshots
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MetricsRates {
+    pub ticks_per_sec: f64,
+    pub orders_per_sec: f64,
+    pub rejects_per_sec: f64,
+    pub fills_per_sec: f64,
+    pub cancels_per_sec: f64,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    #[inline(always)]
+    pub fn inc_ticks_processed(&self) {
+        self.ticks_processed.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    #[inline(always)]
+    pub fn inc_orders_submitted(&self) {
+        self.orders_submitted.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    #[inline(always)]
+    pub fn inc_orders_rejected(&self) {
+        self.orders_rejected.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    #[inline(always)]
+    pub fn inc_fills(&self) {
+        self.fills.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    #[inline(always)]
+    pub fn inc_cancels(&self) {
+        self.cancels.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    /// Reconciliation found the internal position out of tolerance
+    #[inline(always)]
+    pub fn inc_position_mismatches(&self) {
+        self.position_mismatches.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    /// Feed timestamps went backwards or jumped past the allowed interval
+    #[inline(always)]
+    pub fn inc_feed_gaps(&self) {
+        self.feed_gaps.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    /// Counters are read individually, so a snapshot taken while the hot
+    /// path is running may be skewed by in-flight increments
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            ticks_processed: self.ticks_processed.load(Ordering::Relaxed),
+            orders_submitted: self.orders_submitted.load(Ordering::Relaxed),
+            orders_rejected: self.orders_rejected.load(Ordering::Relaxed),
+            fills: self.fills.load(Ordering::Relaxed),
+            cancels: self.cancels.load(Ordering::Relaxed),
+            position_mismatches: self.position_mismatches.load(Ordering::Relaxed),
+            feed_gaps: self.feed_gaps.load(Ordering::Relaxed),
+        }
+    }
+    
+    /// Rates since `prev`, which was taken `dt` ago
+    pub fn rate_since(&self, prev: &MetricsSnapshot, dt: Duration) -> MetricsRates {
+        self.snapshot().rate_since(prev, dt)
+    }
+}
+
+impl MetricsSnapshot {
+    pub fn rate_since(&self, prev: &MetricsSnapshot, dt: Duration) -> MetricsRates {
+        let secs = dt.as_secs_f64();
+        if secs <= 0.0 {
+            return MetricsRates::default();
+        }
+        let rate = |now: u64, then: u64| now.saturating_sub(then) as f64 / secs;
+        
+        MetricsRates {
+            ticks_per_sec: rate(self.ticks_processed, prev.ticks_processed),
+            orders_per_sec: rate(self.orders_submitted, prev.orders_submitted),
+            rejects_per_sec: rate(self.orders_rejected, prev.orders_rejected),
+            fills_per_sec: rate(self.fills, prev.fills),
+            cancels_per_sec: rate(self.cancels, prev.cancels),
+        }
+    }
+    
+    /// Fills per submitted order (0 when nothing was submitted)
+    pub fn fill_ratio(&self) -> f64 {
+        if self.orders_submitted == 0 {
+            return 0.0;
+        }
+        self.fills as f64 / self.orders_submitted as f64
+    }
+    
+    /// Rejections per submitted order (0 when nothing was submitted)
+    pub fn reject_ratio(&self) -> f64 {
+        if self.orders_submitted == 0 {
+            return 0.0;
+        }
+        self.orders_rejected as f64 / self.orders_submitted as f64
+    }
+}
+
+// Prometheus text exposition (format 0.0.4)
+
+const PREFIX: &str = "see";
+
+// (name, help, value) for every counter in a snapshot
+fn counters(snap: &MetricsSnapshot) -> [(&'static str, &'static str, u64); 7] {
+    [
+        ("ticks_processed_total", "Market data ticks processed", snap.ticks_processed),
+        ("orders_submitted_total", "Orders submitted to venues", snap.orders_submitted),
+        ("orders_rejected_total", "Orders rejected by pre-trade risk", snap.orders_rejected),
+        ("fills_total", "Fills received", snap.fills),
+        ("cancels_total", "Cancels sent", snap.cancels),
+        ("position_mismatches_total", "Reconciliations that found a position discrepancy", snap.position_mismatches),
+        ("feed_gaps_total", "Feed timestamp reversals or gaps detected", snap.feed_gaps),
+    ]
+}
+
+fn gauges(snap: &MetricsSnapshot) -> [(&'static str, &'static str, f64); 2] {
+    [
+        ("fill_ratio", "Fills per submitted order", snap.fill_ratio()),
+        ("reject_ratio", "Rejections per submitted order", snap.reject_ratio()),
+    ]
+}
+
+fn write_samples(out: &mut String, series: &[(String, MetricsSnapshot)]) {
+    let Some((_, first)) = series.first() else {
+        return;
+    };
+    for (i, (name, help, _)) in counters(first).iter().enumerate() {
+        let _ = writeln!(out, "# HELP {PREFIX}_{name} {help}");
+        let _ = writeln!(out, "# TYPE {PREFIX}_{name} counter");
+        for (labels, snap) in series {
+            let _ = writeln!(out, "{PREFIX}_{name}{labels} {}", counters(snap)[i].2);
+        }
+    }
+    for (i, (name, help, _)) in gauges(first).iter().enumerate() {
+        let _ = writeln!(out, "# HELP {PREFIX}_{name} {help}");
+        let _ = writeln!(out, "# TYPE {PREFIX}_{name} gauge");
+        for (labels, snap) in series {
+            let _ = writeln!(out, "{PREFIX}_{name}{labels} {}", gauges(snap)[i].2);
+        }
+    }
+}
+
+/// `le` bounds exported for latency histograms, in nanoseconds. Every scrape
+/// emits all of them, so no series appears or vanishes as the data shifts
+pub const LATENCY_BOUNDS_NS: [u64; 16] = [
+    10, 25, 50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000, 500_000, 1_000_000,
+];
+
+/// Cumulative `_bucket` series at `LATENCY_BOUNDS_NS`, plus `_sum`/`_count`.
+///
+/// An HDR bucket counts toward the first bound at or above its upper edge, so
+/// a bound inside a bucket under-reports by at most that bucket's width.
+fn write_histogram(out: &mut String, name: &str, hist: &LatencyHistogram) {
+    let _ = writeln!(out, "# HELP {PREFIX}_{name} Latency in nanoseconds");
+    let _ = writeln!(out, "# TYPE {PREFIX}_{name} histogram");
+    // One copy of the buckets feeds every series, so `+Inf` and `_count`
+    // match the last cumulative count even while recording continues
+    let snap = hist.snapshot();
+    let mut buckets = snap.buckets.iter().enumerate().filter(|&(_, &n)| n > 0).peekable();
+    let mut cumulative = 0u64;
+    for bound in LATENCY_BOUNDS_NS {
+        while let Some((_, &n)) = buckets.next_if(|&(index, _)| LatencyHistogram::bucket_range(index).1 <= bound) {
+            cumulative += n;
+        }
+        let _ = writeln!(out, "{PREFIX}_{name}_bucket{{le=\"{bound}\"}} {cumulative}");
+    }
+    let _ = writeln!(out, "{PREFIX}_{name}_bucket{{le=\"+Inf\"}} {}", snap.count);
+    let _ = writeln!(out, "{PREFIX}_{name}_sum {}", snap.sum);
+    let _ = writeln!(out, "{PREFIX}_{name}_count {}", snap.count);
+}
+
+impl Metrics {
+    /// Counters and ratios in Prometheus text format
+    pub fn to_prometheus(&self) -> String {
+        self.to_prometheus_with(None)
+    }
+    
+    /// As `to_prometheus`, plus `see_latency_ns` histogram series if given
+    pub fn to_prometheus_with(&self, latency: Option<&LatencyHistogram>) -> String {
+        let mut out = String::new();
+        write_samples(&mut out, &[(String::new(), self.snapshot())]);
+        if let Some(hist) = latency {
+            write_histogram(&mut out, "latency_ns", hist);
+        }
+        out
+    }
+    
+    /// One series per asset for engines that keep a `Metrics` per `asset_id`
+    pub fn to_prometheus_by_asset(per_asset: &[(u32, &Metrics)]) -> String {
+        let series: Vec<(String, MetricsSnapshot)> = per_asset
+            .iter()
+            .map(|(asset_id, metrics)| (format!("{{asset_id=\"{asset_id}\"}}"), metrics.snapshot()))
+            .collect();
+        let mut out = String::new();
+        write_samples(&mut out, &series);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Order, RiskControl};
+    use std::sync::Arc;
+    
+    #[test]
+    fn test_metrics_concurrent_increments() {
+        let metrics = Arc::new(Metrics::new());
+        
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..10_000 {
+                        metrics.inc_ticks_processed();
+                        metrics.inc_orders_submitted();
+                    }
+                    for _ in 0..2_500 {
+                        metrics.inc_fills();
+                        metrics.inc_cancels();
+                    }
+                });
+            }
+        });
+        
+        let snap = metrics.snapshot();
+        assert_eq!(snap.ticks_processed, 40_000);
+        assert_eq!(snap.orders_submitted, 40_000);
+        assert_eq!(snap.fills, 10_000);
+        assert_eq!(snap.cancels, 10_000);
+        assert_eq!(snap.fill_ratio(), 0.25);
+        
+        let rates = metrics.rate_since(&MetricsSnapshot::default(), Duration::from_secs(2));
+        assert_eq!(rates.ticks_per_sec, 20_000.0);
+        assert_eq!(rates.fills_per_sec, 5_000.0);
+    }
+    
+    // Checks every sample line is `name[{labels}] value` with a numeric value
+    fn parse_samples(text: &str) -> Vec<(String, f64)> {
+        text.lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(|line| {
+                let (name, value) = line.rsplit_once(' ').expect("sample has a value");
+                let value = if value == "+Inf" { f64::INFINITY } else { value.parse().expect("numeric value") };
+                (name.to_string(), value)
+            })
+            .collect()
+    }
+    
+    #[test]
+    fn test_prometheus_exposition() {
+        let metrics = Metrics::new();
+        for _ in 0..10 {
+            metrics.inc_orders_submitted();
+        }
+        for _ in 0..4 {
+            metrics.inc_fills();
+        }
+        metrics.inc_orders_rejected();
+        
+        let hist = LatencyHistogram::new();
+        for ns in [5, 5, 100, 2_000] {
+            hist.record_ns(ns);
+        }
+        
+        let text = metrics.to_prometheus_with(Some(&hist));
+        let samples = parse_samples(&text);
+        // 7 counters + 2 gauges, 16 bounds + Inf + sum + count
+        assert_eq!(samples.len(), 28);
+        assert_eq!(text.lines().filter(|l| l.starts_with("# TYPE")).count(), 10);
+        assert_eq!(samples.iter().filter(|(n, _)| n.ends_with("_total")).count(), 7);
+        
+        let get = |name: &str| samples.iter().find(|(n, _)| n == name).map(|s| s.1);
+        assert_eq!(get("see_orders_submitted_total"), Some(10.0));
+        assert_eq!(get("see_fills_total"), Some(4.0));
+        assert_eq!(get("see_fill_ratio"), Some(0.4));
+        assert_eq!(get("see_latency_ns_bucket{le=\"10\"}"), Some(2.0));
+        // 100 sits in the 100..=103 bucket, which only fits under 250
+        assert_eq!(get("see_latency_ns_bucket{le=\"100\"}"), Some(2.0));
+        assert_eq!(get("see_latency_ns_bucket{le=\"250\"}"), Some(3.0));
+        assert_eq!(get("see_latency_ns_bucket{le=\"2500\"}"), Some(4.0));
+        assert_eq!(get("see_latency_ns_bucket{le=\"1000000\"}"), Some(4.0));
+        assert_eq!(get("see_latency_ns_bucket{le=\"+Inf\"}"), Some(4.0));
+        assert_eq!(get("see_latency_ns_sum"), Some(2_110.0));
+        assert_eq!(get("see_latency_ns_count"), Some(4.0));
+        
+        let other = Metrics::new();
+        other.inc_ticks_processed();
+        let text = Metrics::to_prometheus_by_asset(&[(1, &metrics), (2, &other)]);
+        let samples = parse_samples(&text);
+        assert_eq!(samples.len(), 18);
+        assert!(samples.contains(&("see_ticks_processed_total{asset_id=\"2\"}".to_string(), 1.0)));
+    }
+    
+    #[test]
+    fn test_histogram_series_are_fixed() {
+        let bucket_names = |hist: &LatencyHistogram| -> Vec<String> {
+            parse_samples(&Metrics::new().to_prometheus_with(Some(hist)))
+                .into_iter()
+                .filter(|(n, _)| n.contains("_bucket"))
+                .map(|(n, _)| n)
+                .collect()
+        };
+        let empty = LatencyHistogram::new();
+        let busy = LatencyHistogram::new();
+        for ns in [3, 700, 40_000, 5_000_000] {
+            busy.record_ns(ns);
+        }
+        assert_eq!(bucket_names(&empty), bucket_names(&busy));
+        assert_eq!(bucket_names(&empty).len(), LATENCY_BOUNDS_NS.len() + 1);
+        
+        let samples = parse_samples(&Metrics::new().to_prometheus_with(Some(&busy)));
+        let get = |name: &str| samples.iter().find(|(n, _)| n == name).map(|s| s.1);
+        // Beyond the last bound only +Inf has it
+        assert_eq!(get("see_latency_ns_bucket{le=\"1000000\"}"), Some(3.0));
+        assert_eq!(get("see_latency_ns_bucket{le=\"+Inf\"}"), get("see_latency_ns_count"));
+        assert_eq!(get("see_latency_ns_count"), Some(4.0));
+    }
+    
+    #[test]
+    fn test_risk_rejections_are_counted() {
+        let metrics = Arc::new(Metrics::new());
+        let risk = RiskControl::new(100).with_metrics(Arc::clone(&metrics));
+        let order = Order { quantity: 50, ..Default::default() };
+        
+        assert!(risk.check_pre_trade(&order, 0));
+        assert!(!risk.check_pre_trade(&order, 80));
+        risk.trigger_kill_switch();
+        assert!(!risk.check_pre_trade(&order, 0));
+        
+        assert_eq!(metrics.snapshot().orders_rejected, 2);
+    }
+}
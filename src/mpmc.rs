@@ -0,0 +1,263 @@
+// Bounded multi-producer/multi-consumer queue
+// Same fixed, allocation-free ring as `LockFreeSPSC`, but any number of
+// threads may push and pop concurrently (bounded MPMC after Vyukov).
+
+use std::cmp;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use crate::queue_stats::QueueCounters;
+use crate::seq_ring::SeqRing;
+use crate::sync::AtomicU64;
+use crate::wait::WaitStrategy;
+
+/// Bounded lock-free MPMC ring with all `CAPACITY` slots usable.
+///
+/// Memory-ordering contract:
+/// - Every slot has a sequence number (see `seq_ring`). Slot `i` starts at
+///   `i`. A producer claims ring position `p` when the slot reads `p`; a
+///   consumer claims it when it reads `p + 1`; the consumer hands it to the
+///   next lap by storing `p + CAPACITY`.
+/// - Sequence loads are `Acquire` and the stores that end a write or read
+///   are `Release`. So an item's bytes happen-before the pop that returns it,
+///   and a pop's read happens-before the next lap's write into that slot.
+/// - `head`/`tail` are only claim counters, advanced by a `Relaxed` CAS. The
+///   slot sequence carries all the synchronization.
+/// - Items are handed out in claim order. A producer or consumer preempted
+///   between claiming and finishing holds up its own slot only. Until it
+///   finishes, that slot reports empty to consumers or full to producers.
+pub struct LockFreeMPMC<T, const CAPACITY: usize> {
+    slots: SeqRing<T, CAPACITY>,
+    head: AtomicU64,  // Next position to pop
+    counters: QueueCounters,
+}
+
+impl<T: Copy, const CAPACITY: usize> LockFreeMPMC<T, CAPACITY> {
+    pub fn new() -> Self {
+        Self {
+            slots: SeqRing::new(),
+            head: AtomicU64::new(0),
+            counters: QueueCounters::new(),
+        }
+    }
+    
+    /// Push from any thread (returns false if full)
+    #[inline(always)]
+    pub fn push(&self, item: T) -> bool {
+        match self.slots.push(item) {
+            Some(pos) => {
+                self.counters.pushed(1, || (pos + 1).saturating_sub(self.head.load(Ordering::Relaxed)));
+                true
+            }
+            None => {
+                self.counters.failed(1);
+                false
+            }
+        }
+    }
+    
+    /// Pop from any thread (returns None if empty)
+    #[inline(always)]
+    pub fn pop(&self) -> Option<T> {
+        let mut pos = self.head.load(Ordering::Relaxed);
+        loop {
+            match self.slots.state(pos) {
+                cmp::Ordering::Equal => {
+                    match self.head.compare_exchange_weak(pos, pos + 1, Ordering::Relaxed, Ordering::Relaxed) {
+                        Ok(_) => {
+                            // Winning the CAS makes `pos` ours alone
+                            let item = unsafe { self.slots.take(pos) };
+                            self.counters.popped(1);
+                            return Some(item);
+                        }
+                        Err(current) => pos = current,
+                    }
+                }
+                // Not published yet
+                cmp::Ordering::Less => return None,
+                // Another consumer took it first
+                cmp::Ordering::Greater => pos = self.head.load(Ordering::Relaxed),
+            }
+        }
+    }
+    
+    /// Block until an item arrives, backing off per `strategy`
+    #[inline(always)]
+    pub fn pop_wait(&self, strategy: WaitStrategy) -> T {
+        strategy.wait(|| self.pop())
+    }
+    
+    /// As `pop_wait`, but `None` once `timeout` passes
+    pub fn pop_wait_timeout(&self, strategy: WaitStrategy, timeout: Duration) -> Option<T> {
+        strategy.wait_timeout(timeout, || self.pop())
+    }
+    
+    /// Pop up to the number of items queued as of this call; with other
+    /// consumers running it may yield fewer
+    #[inline(always)]
+    pub fn drain(&self) -> impl Iterator<Item = T> + '_ {
+        std::iter::from_fn(|| self.pop()).take(self.size())
+    }
+    
+    /// Occupancy hint for monitoring
+    pub fn size(&self) -> usize {
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.slots.tail();
+        (tail.saturating_sub(head) as usize).min(CAPACITY)
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.size() == 0
+    }
+    
+    /// Counter snapshot across all producers and consumers
+    #[cfg(feature = "queue_stats")]
+    pub fn stats(&self) -> crate::queue_stats::QueueStats {
+        self.counters.snapshot()
+    }
+}
+
+impl<T: Copy, const CAPACITY: usize> Default for LockFreeMPMC<T, CAPACITY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::Mutex;
+    
+    #[test]
+    fn test_mpmc_full_capacity_and_order() {
+        let queue: LockFreeMPMC<u64, 4> = LockFreeMPMC::new();
+        assert_eq!(queue.pop(), None);
+        for i in 0..4 {
+            assert!(queue.push(i));
+        }
+        assert!(!queue.push(4));
+        assert_eq!(queue.size(), 4);
+        
+        // Several laps through the ring
+        for i in 4..20 {
+            assert_eq!(queue.pop(), Some(i - 4));
+            assert!(queue.push(i));
+        }
+        let rest: Vec<u64> = std::iter::from_fn(|| queue.pop()).collect();
+        assert_eq!(rest, vec![16, 17, 18, 19]);
+        assert!(queue.is_empty());
+    }
+    
+    #[test]
+    fn test_mpmc_drain_takes_only_what_was_queued() {
+        let queue: LockFreeMPMC<u64, 8> = LockFreeMPMC::new();
+        assert_eq!(queue.drain().count(), 0);
+        for i in 0..3 {
+            assert!(queue.push(i));
+        }
+        
+        // Items pushed mid-drain wait for the next one
+        let mut drained = Vec::new();
+        for item in queue.drain() {
+            assert!(queue.push(10 + item));
+            drained.push(item);
+        }
+        assert_eq!(drained, vec![0, 1, 2]);
+        assert_eq!(queue.drain().collect::<Vec<_>>(), vec![10, 11, 12]);
+        assert!(queue.is_empty());
+    }
+    
+    #[test]
+    fn test_mpmc_concurrent_producers_and_consumers() {
+        const N: u64 = 10_000;
+        let queue: LockFreeMPMC<u64, 64> = LockFreeMPMC::new();
+        let done = AtomicBool::new(false);
+        let collected = Mutex::new(Vec::new());
+        
+        std::thread::scope(|s| {
+            let consumers: Vec<_> = (0..2)
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut mine = Vec::new();
+                        loop {
+                            match queue.pop() {
+                                Some(x) => mine.push(x),
+                                None if done.load(Ordering::Acquire) && queue.is_empty() => break,
+                                None => std::thread::yield_now(),
+                            }
+                        }
+                        // Each producer's items reach a given consumer in push order
+                        for t in 0..3 {
+                            let own: Vec<_> = mine.iter().filter(|&&x| x / N == t).collect();
+                            assert!(own.windows(2).all(|w| w[0] < w[1]));
+                        }
+                        collected.lock().unwrap().extend(mine);
+                    })
+                })
+                .collect();
+            
+            let producers: Vec<_> = (0..3u64)
+                .map(|t| {
+                    let queue = &queue;
+                    s.spawn(move || {
+                        for i in 0..N {
+                            while !queue.push(t * N + i) {
+                                std::thread::yield_now();
+                            }
+                        }
+                    })
+                })
+                .collect();
+            for p in producers {
+                p.join().unwrap();
+            }
+            done.store(true, Ordering::Release);
+            for c in consumers {
+                c.join().unwrap();
+            }
+        });
+        
+        let mut all = collected.into_inner().unwrap();
+        all.sort_unstable();
+        assert_eq!(all, (0..3 * N).collect::<Vec<_>>());
+    }
+}
+
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use super::*;
+    use loom::sync::Arc;
+    use loom::thread;
+    
+    // Two producers racing one consumer: every item delivered exactly once
+    #[test]
+    fn loom_mpmc_two_producers() {
+        loom::model(|| {
+            let queue: Arc<LockFreeMPMC<u32, 2>> = Arc::new(LockFreeMPMC::new());
+            let producers: Vec<_> = (0..2)
+                .map(|t| {
+                    let queue = Arc::clone(&queue);
+                    thread::spawn(move || {
+                        while !queue.push(t) {
+                            thread::yield_now();
+                        }
+                    })
+                })
+                .collect();
+            
+            let mut popped = Vec::new();
+            while popped.len() < 2 {
+                match queue.pop() {
+                    Some(item) => popped.push(item),
+                    None => thread::yield_now(),
+                }
+            }
+            for p in producers {
+                p.join().unwrap();
+            }
+            popped.sort_unstable();
+            assert_eq!(popped, vec![0, 1]);
+        });
+    }
+}
@@ -0,0 +1,421 @@
+// Bounded multi-producer/single-consumer ring
+// Several feed-handler threads funnel into one strategy consumer.
+//
+// Producers share the sequence-numbered ring in `seq_ring` with the MPMC
+// queue; with a single consumer, popping needs no CAS: the consumer owns
+// every position up to the first unpublished one.
+//
+// `order_channel` puts strategies' order submission on one ring: every
+// lane gets an equal share of the slots, so one busy strategy can't starve
+// the others out of the gateway.
+
+use std::cmp;
+use std::collections::VecDeque;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::queue_stats::QueueCounters;
+use crate::seq_ring::SeqRing;
+use crate::sync::AtomicU64;
+use crate::wait::WaitStrategy;
+use crate::Order;
+
+pub struct MpscRing<T, const CAPACITY: usize> {
+    slots: SeqRing<T, CAPACITY>,
+    head: AtomicU64,  // Consumer's position, published for lane quotas
+    counters: QueueCounters,
+}
+
+impl<T: Copy, const CAPACITY: usize> MpscRing<T, CAPACITY> {
+    /// New ring; clone the producer for each feed handler. All `CAPACITY`
+    /// slots are usable
+    pub fn channel() -> (Producer<T, CAPACITY>, Consumer<T, CAPACITY>) {
+        let ring = Arc::new(Self {
+            slots: SeqRing::new(),
+            head: AtomicU64::new(0),
+            counters: QueueCounters::new(),
+        });
+        (Producer { ring: Arc::clone(&ring) }, Consumer { ring, head: 0 })
+    }
+}
+
+// Producer
+
+pub struct Producer<T, const CAPACITY: usize> {
+    ring: Arc<MpscRing<T, CAPACITY>>,
+}
+
+impl<T: Copy, const CAPACITY: usize> Producer<T, CAPACITY> {
+    /// Push from any thread (returns false if full)
+    #[inline(always)]
+    pub fn push(&self, item: T) -> bool {
+        self.claim_and_write(item).is_some()
+    }
+    
+    /// `push` that reports the ring position the item landed at
+    #[inline(always)]
+    fn claim_and_write(&self, item: T) -> Option<u64> {
+        let ring = &*self.ring;
+        let pos = ring.slots.push(item);
+        match pos {
+            Some(pos) => ring.counters.pushed(1, || (pos + 1).saturating_sub(ring.head.load(Ordering::Relaxed))),
+            None => ring.counters.failed(1),
+        }
+        pos
+    }
+}
+
+impl<T, const CAPACITY: usize> Clone for Producer<T, CAPACITY> {
+    fn clone(&self) -> Self {
+        Self { ring: Arc::clone(&self.ring) }
+    }
+}
+
+// Consumer
+
+pub struct Consumer<T, const CAPACITY: usize> {
+    ring: Arc<MpscRing<T, CAPACITY>>,
+    head: u64,
+}
+
+impl<T: Copy, const CAPACITY: usize> Consumer<T, CAPACITY> {
+    /// Pop in claim order (returns None if empty). A producer that claimed
+    /// the head position but hasn't finished writing holds back later items
+    /// until it publishes
+    #[inline(always)]
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        
+        // Sole consumer: a published head is ours to take
+        let item = unsafe { self.ring.slots.take(self.head) };
+        self.head += 1;
+        self.ring.head.store(self.head, Ordering::Release);
+        self.ring.counters.popped(1);
+        Some(item)
+    }
+    
+    /// Next item without consuming it; producers can't touch a published
+    /// slot until it is popped, so the reference stays valid
+    #[inline(always)]
+    pub fn peek(&self) -> Option<&T> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(unsafe { self.ring.slots.get(self.head) })
+    }
+    
+    /// Pop everything published as of this call (stops at the first
+    /// claimed-but-unwritten position)
+    #[inline(always)]
+    pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
+        let queued = self.len();
+        std::iter::from_fn(move || self.pop()).take(queued)
+    }
+    
+    /// Block until an item arrives, backing off per `strategy`
+    #[inline(always)]
+    pub fn pop_wait(&mut self, strategy: WaitStrategy) -> T {
+        strategy.wait(|| self.pop())
+    }
+    
+    /// As `pop_wait`, but `None` once `timeout` passes
+    pub fn pop_wait_timeout(&mut self, strategy: WaitStrategy, timeout: Duration) -> Option<T> {
+        strategy.wait_timeout(timeout, || self.pop())
+    }
+    
+    /// Whether the next item is published (a claimed-but-unwritten head counts as empty)
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.ring.slots.state(self.head) != cmp::Ordering::Equal
+    }
+    
+    /// Positions claimed by producers and not yet popped (includes unpublished ones)
+    pub fn len(&self) -> usize {
+        (self.ring.slots.tail() - self.head) as usize
+    }
+    
+    /// Counter snapshot for the whole ring (all producers)
+    #[cfg(feature = "queue_stats")]
+    pub fn stats(&self) -> crate::queue_stats::QueueStats {
+        self.ring.counters.snapshot()
+    }
+}
+
+// Order Submission Lanes
+
+/// One ring shared by `lanes` strategies, each limited to
+/// `CAPACITY / lanes` orders in flight; the gateway drains the consumer in
+/// submission order
+pub fn order_channel<const CAPACITY: usize>(lanes: usize) -> (Vec<OrderLane<CAPACITY>>, Consumer<Order, CAPACITY>) {
+    assert!(lanes > 0 && lanes <= CAPACITY, "need between 1 and CAPACITY lanes");
+    let (producer, consumer) = MpscRing::<Order, CAPACITY>::channel();
+    let quota = CAPACITY / lanes;
+    let lanes = (0..lanes)
+        .map(|_| OrderLane { producer: producer.clone(), claimed: VecDeque::with_capacity(quota), quota })
+        .collect();
+    (lanes, consumer)
+}
+
+/// A strategy's handle for submitting orders; move it to the strategy's thread
+pub struct OrderLane<const CAPACITY: usize> {
+    producer: Producer<Order, CAPACITY>,
+    claimed: VecDeque<u64>,  // Ring positions of our orders, oldest first
+    quota: usize,
+}
+
+impl<const CAPACITY: usize> OrderLane<CAPACITY> {
+    /// Queue an order for the gateway; false if this lane already has
+    /// `quota` orders the gateway hasn't taken yet. While under quota the
+    /// ring always has room, whatever the other lanes are doing
+    #[inline(always)]
+    pub fn submit(&mut self, order: Order) -> bool {
+        if self.in_flight() >= self.quota {
+            return false;
+        }
+        match self.producer.claim_and_write(order) {
+            Some(pos) => {
+                self.claimed.push_back(pos);
+                true
+            }
+            None => {
+                debug_assert!(false, "lane under quota found the ring full");
+                false
+            }
+        }
+    }
+    
+    /// Our orders not yet popped by the gateway
+    #[inline(always)]
+    pub fn in_flight(&mut self) -> usize {
+        let head = self.producer.ring.head.load(Ordering::Acquire);
+        while self.claimed.front().is_some_and(|&pos| pos < head) {
+            self.claimed.pop_front();
+        }
+        self.claimed.len()
+    }
+    
+    pub fn quota(&self) -> usize {
+        self.quota
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_mpsc_full_and_wraparound() {
+        let (producer, mut consumer) = MpscRing::<u64, 4>::channel();
+        assert_eq!(consumer.pop(), None);
+        
+        for i in 0..4 {
+            assert!(producer.push(i));
+        }
+        assert!(!producer.push(99));
+        assert_eq!(consumer.len(), 4);
+        
+        for lap in 0..3 {
+            assert_eq!(consumer.pop(), Some(lap));
+            assert!(producer.push(4 + lap));
+        }
+        let rest: Vec<u64> = std::iter::from_fn(|| consumer.pop()).collect();
+        assert_eq!(rest, vec![3, 4, 5, 6]);
+        assert!(consumer.is_empty());
+    }
+    
+    #[test]
+    fn test_mpsc_peek_and_drain() {
+        let (producer, mut consumer) = MpscRing::<u64, 8>::channel();
+        assert_eq!(consumer.peek(), None);
+        assert_eq!(consumer.drain().count(), 0);
+        for i in 0..3 {
+            assert!(producer.push(i));
+        }
+        assert_eq!(consumer.peek(), Some(&0));
+        assert_eq!(consumer.peek(), Some(&0));
+        assert_eq!(consumer.len(), 3);
+        
+        // Items pushed mid-drain wait for the next one
+        let mut drained = Vec::new();
+        for item in consumer.drain() {
+            assert!(producer.push(10 + item));
+            drained.push(item);
+        }
+        assert_eq!(drained, vec![0, 1, 2]);
+        assert_eq!(consumer.peek(), Some(&10));
+        assert_eq!(consumer.drain().collect::<Vec<_>>(), vec![10, 11, 12]);
+        assert_eq!(consumer.peek(), None);
+    }
+    
+    #[test]
+    #[should_panic(expected = "at least 2")]
+    fn test_single_slot_ring_is_refused() {
+        let _ = MpscRing::<u64, 1>::channel();
+    }
+    
+    #[test]
+    fn test_mpsc_four_producers_no_loss_no_duplicates() {
+        const N: u64 = 20_000;
+        let (producer, mut consumer) = MpscRing::<u64, 256>::channel();
+        
+        std::thread::scope(|s| {
+            for t in 0..4u64 {
+                let producer = producer.clone();
+                s.spawn(move || {
+                    for i in 0..N {
+                        while !producer.push(t * N + i) {
+                            std::thread::yield_now();
+                        }
+                    }
+                });
+            }
+            
+            let mut seen = vec![false; 4 * N as usize];
+            let mut last = [None::<u64>; 4];
+            let mut received = 0;
+            while received < 4 * N {
+                match consumer.pop() {
+                    Some(value) => {
+                        assert!(!std::mem::replace(&mut seen[value as usize], true), "duplicate {}", value);
+                        // Each producer's own items arrive in its push order
+                        let t = (value / N) as usize;
+                        assert!(last[t].is_none_or(|l| value > l));
+                        last[t] = Some(value);
+                        received += 1;
+                    }
+                    None => std::thread::yield_now(),
+                }
+            }
+            assert!(seen.iter().all(|&s| s));
+        });
+        assert_eq!(consumer.pop(), None);
+    }
+    
+    fn order(id: u64) -> Order {
+        Order { order_id: id, quantity: 1, ..Default::default() }
+    }
+    
+    #[test]
+    fn test_order_lanes_share_fairly() {
+        let (mut lanes, mut gateway) = order_channel::<8>(2);
+        assert_eq!(lanes[0].quota(), 4);
+        
+        // A busy lane stops at its share...
+        let submitted = (0..10).filter(|&i| lanes[0].submit(order(i))).count();
+        assert_eq!(submitted, 4);
+        assert_eq!(lanes[0].in_flight(), 4);
+        // ...leaving the rest of the ring to the quiet one
+        for i in 100..104 {
+            assert!(lanes[1].submit(order(i)));
+        }
+        assert!(!lanes[1].submit(order(104)));
+        
+        // Draining frees quota for whoever owned the popped orders
+        assert_eq!(gateway.pop().map(|o| o.order_id), Some(0));
+        assert_eq!(gateway.pop().map(|o| o.order_id), Some(1));
+        assert_eq!(lanes[0].in_flight(), 2);
+        assert_eq!(lanes[1].in_flight(), 4);
+        assert!(lanes[0].submit(order(4)));
+        assert!(!lanes[1].submit(order(104)));
+        
+        let rest: Vec<u64> = std::iter::from_fn(|| gateway.pop().map(|o| o.order_id)).collect();
+        assert_eq!(rest, vec![2, 3, 100, 101, 102, 103, 4]);
+        assert_eq!(lanes[1].in_flight(), 0);
+    }
+    
+    #[test]
+    fn test_order_lanes_threads() {
+        const N: u64 = 5_000;
+        let (lanes, mut gateway) = order_channel::<64>(3);
+        
+        std::thread::scope(|s| {
+            for (t, mut lane) in lanes.into_iter().enumerate() {
+                s.spawn(move || {
+                    for i in 0..N {
+                        while !lane.submit(order(t as u64 * N + i)) {
+                            std::thread::yield_now();
+                        }
+                        assert!(lane.in_flight() <= lane.quota());
+                    }
+                });
+            }
+            
+            let mut next = [0u64; 3];
+            for _ in 0..3 * N {
+                let order = gateway.pop_wait(WaitStrategy::SpinThenYield { spins: 16 });
+                let t = (order.order_id / N) as usize;
+                assert_eq!(order.order_id, t as u64 * N + next[t]);
+                next[t] += 1;
+            }
+            assert_eq!(next, [N; 3]);
+        });
+    }
+}
+
+#[cfg(all(test, loom))]
+mod loom_tests {
+    use super::*;
+    use loom::thread;
+    
+    // Two producers racing the consumer for claims and publication: every
+    // item delivered exactly once
+    #[test]
+    fn loom_mpsc_two_producers() {
+        loom::model(|| {
+            let (producer, mut consumer) = MpscRing::<u32, 2>::channel();
+            let producers: Vec<_> = (0..2)
+                .map(|t| {
+                    let producer = producer.clone();
+                    thread::spawn(move || {
+                        while !producer.push(t) {
+                            thread::yield_now();
+                        }
+                    })
+                })
+                .collect();
+            
+            let mut popped = Vec::new();
+            while popped.len() < 2 {
+                match consumer.pop() {
+                    Some(item) => popped.push(item),
+                    None => thread::yield_now(),
+                }
+            }
+            for p in producers {
+                p.join().unwrap();
+            }
+            assert_eq!(consumer.pop(), None);
+            popped.sort_unstable();
+            assert_eq!(popped, vec![0, 1]);
+        });
+    }
+    
+    // Full ring: the producer may only rewrite a slot after the pop has read it
+    #[test]
+    fn loom_mpsc_push_waits_for_pop() {
+        loom::model(|| {
+            let (producer, mut consumer) = MpscRing::<u32, 2>::channel();
+            assert!(producer.push(0));
+            assert!(producer.push(1));
+            let handle = thread::spawn(move || {
+                while !producer.push(2) {
+                    thread::yield_now();
+                }
+            });
+            
+            let mut popped = Vec::new();
+            while popped.len() < 3 {
+                match consumer.pop() {
+                    Some(item) => popped.push(item),
+                    None => thread::yield_now(),
+                }
+            }
+            handle.join().unwrap();
+            assert_eq!(popped, vec![0, 1, 2]);
+            assert!(consumer.is_empty());
+        });
+    }
+}
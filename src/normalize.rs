@@ -0,0 +1,138 @@
+// Cross-venue tick normalization
+// Venues quote in their own price precision and lot size; this rescales ticks
+// to canonical units per asset_id so prices and sizes compare across assets.
+// Normalizing returns a rescaled copy as a `NormalizedTick`, which can't be
+// fed back in, so the scale can never be applied twice.
+
+use std::collections::HashMap;
+use std::ops::Deref;
+
+use crate::MarketTick;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scale {
+    pub price_multiplier: f64,
+    pub size_multiplier: f64,
+}
+
+/// A tick in canonical units; only `Normalizer::normalize` makes one
+#[derive(Clone, Copy)]
+pub struct NormalizedTick(MarketTick);
+
+impl NormalizedTick {
+    #[inline(always)]
+    pub fn into_inner(self) -> MarketTick {
+        self.0
+    }
+}
+
+impl Deref for NormalizedTick {
+    type Target = MarketTick;
+    
+    #[inline(always)]
+    fn deref(&self) -> &MarketTick {
+        &self.0
+    }
+}
+
+#[derive(Default)]
+pub struct Normalizer {
+    scales: HashMap<u32, Scale>,
+}
+
+impl Normalizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Register (or replace) the scale for one asset
+    pub fn with_asset(mut self, asset_id: u32, price_multiplier: f64, size_multiplier: f64) -> Self {
+        self.set_scale(asset_id, Scale { price_multiplier, size_multiplier });
+        self
+    }
+    
+    pub fn set_scale(&mut self, asset_id: u32, scale: Scale) {
+        self.scales.insert(asset_id, scale);
+    }
+    
+    pub fn scale(&self, asset_id: u32) -> Option<Scale> {
+        self.scales.get(&asset_id).copied()
+    }
+    
+    /// Copy of `tick` with every price and size field (depth included)
+    /// rescaled; None if the asset has no scale
+    pub fn normalize(&self, tick: &MarketTick) -> Option<NormalizedTick> {
+        let scale = self.scale(tick.asset_id)?;
+        let mut tick = *tick;
+        
+        let price = |p: &mut f64| *p *= scale.price_multiplier;
+        let size = |s: &mut u64| *s = (*s as f64 * scale.size_multiplier).round() as u64;
+        
+        price(&mut tick.bid_price);
+        price(&mut tick.ask_price);
+        price(&mut tick.mid_price);
+        tick.bid_prices.iter_mut().chain(tick.ask_prices.iter_mut()).for_each(price);
+        
+        size(&mut tick.bid_size);
+        size(&mut tick.ask_size);
+        size(&mut tick.trade_volume);
+        tick.bid_sizes.iter_mut().chain(tick.ask_sizes.iter_mut()).for_each(size);
+        
+        Some(NormalizedTick(tick))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn venue_tick(asset_id: u32, bid: f64, ask: f64, bid_size: u64, ask_size: u64) -> MarketTick {
+        let mut tick = MarketTick {
+            asset_id,
+            bid_price: bid,
+            ask_price: ask,
+            mid_price: (bid + ask) / 2.0,
+            bid_size,
+            ask_size,
+            depth_levels: 2,
+            ..Default::default()
+        };
+        tick.bid_prices[..2].copy_from_slice(&[bid, bid - 1.0]);
+        tick.ask_prices[..2].copy_from_slice(&[ask, ask + 1.0]);
+        tick.bid_sizes[..2].copy_from_slice(&[bid_size, bid_size * 2]);
+        tick.ask_sizes[..2].copy_from_slice(&[ask_size, ask_size * 2]);
+        tick
+    }
+    
+    #[test]
+    fn test_normalize_two_assets_depth_consistent() {
+        // Asset 1 quotes in cents with 100-share lots; asset 2 in dollars, single shares
+        let normalizer = Normalizer::new().with_asset(1, 0.01, 100.0).with_asset(2, 1.0, 1.0);
+        
+        let a = normalizer.normalize(&venue_tick(1, 10_000.0, 10_002.0, 3, 4)).unwrap();
+        let b = normalizer.normalize(&venue_tick(2, 100.0, 100.02, 300, 400)).unwrap();
+        
+        for tick in [&a, &b] {
+            assert_eq!(tick.bid_prices[0], tick.bid_price);
+            assert_eq!(tick.ask_prices[0], tick.ask_price);
+            assert_eq!(tick.bid_sizes[0], tick.bid_size);
+            assert_eq!(tick.ask_sizes[1], tick.ask_size * 2);
+        }
+        assert!((a.mid_price - b.mid_price).abs() < 1e-9);
+        assert!((a.bid_prices[1] - 99.99).abs() < 1e-9);
+        assert_eq!((a.bid_size, a.ask_size), (b.bid_size, b.ask_size));
+    }
+    
+    #[test]
+    fn test_normalize_copies_and_skips_unknown_assets() {
+        let normalizer = Normalizer::new().with_asset(1, 0.01, 100.0);
+        let venue = venue_tick(1, 10_000.0, 10_002.0, 3, 4);
+        let tick = normalizer.normalize(&venue).unwrap();
+        assert_eq!(tick.bid_price, 100.0);
+        assert_eq!(tick.into_inner().ask_sizes[1], 800);
+        // The venue tick is left as it was, so it can't end up scaled twice
+        assert_eq!((venue.bid_price, venue.bid_size), (10_000.0, 3));
+        
+        assert!(normalizer.normalize(&venue_tick(9, 1.0, 2.0, 1, 1)).is_none());
+    }
+}
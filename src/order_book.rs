@@ -0,0 +1,128 @@
+// Order Book Reconstruction
+// Maintains the latest top-of-book and 10-level ladder per asset from MarketTick snapshots
+
+use std::collections::HashMap;
+
+use crate::MarketTick;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookSide {
+    Bid,
+    Ask,
+}
+
+/// Latest book snapshot per `asset_id`.
+///
+/// Ticks are full snapshots, so applying one replaces the asset's ladder.
+/// A tick older than the one already held is rejected and counted.
+pub struct OrderBook {
+    books: HashMap<u32, MarketTick>,
+    rejected: u64,
+}
+
+impl OrderBook {
+    pub fn new() -> Self {
+        Self {
+            books: HashMap::new(),
+            rejected: 0,
+        }
+    }
+    
+    /// Apply a snapshot; returns false if it was older than the held book
+    pub fn apply(&mut self, tick: &MarketTick) -> bool {
+        if let Some(current) = self.books.get(&tick.asset_id) {
+            if tick.timestamp_ns < current.timestamp_ns {
+                self.rejected += 1;
+                return false;
+            }
+        }
+        self.books.insert(tick.asset_id, *tick);
+        true
+    }
+    
+    pub fn best_bid(&self, asset_id: u32) -> Option<f64> {
+        self.books.get(&asset_id).map(|t| t.bid_price).filter(|&p| p > 0.0)
+    }
+    
+    pub fn best_ask(&self, asset_id: u32) -> Option<f64> {
+        self.books.get(&asset_id).map(|t| t.ask_price).filter(|&p| p > 0.0)
+    }
+    
+    pub fn spread(&self, asset_id: u32) -> Option<f64> {
+        Some(self.best_ask(asset_id)? - self.best_bid(asset_id)?)
+    }
+    
+    /// `(price, size)` at depth `i`, limited to the tick's `depth_levels`
+    pub fn level(&self, asset_id: u32, side: BookSide, i: usize) -> Option<(f64, u64)> {
+        let tick = self.books.get(&asset_id)?;
+        match side {
+            BookSide::Bid => tick.bids().nth(i),
+            BookSide::Ask => tick.asks().nth(i),
+        }
+    }
+    
+    /// Latest snapshot held for an asset
+    pub fn snapshot(&self, asset_id: u32) -> Option<&MarketTick> {
+        self.books.get(&asset_id)
+    }
+    
+    /// Out-of-order updates rejected across all assets
+    pub fn rejected_updates(&self) -> u64 {
+        self.rejected
+    }
+}
+
+impl Default for OrderBook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn tick(asset_id: u32, timestamp_ns: i64, bid: f64, ask: f64) -> MarketTick {
+        let mut t = MarketTick {
+            asset_id,
+            timestamp_ns,
+            bid_price: bid,
+            ask_price: ask,
+            depth_levels: 2,
+            ..Default::default()
+        };
+        t.bid_prices[0] = bid;
+        t.bid_prices[1] = bid - 0.01;
+        t.ask_prices[0] = ask;
+        t.ask_prices[1] = ask + 0.01;
+        t.bid_sizes[..2].copy_from_slice(&[100, 200]);
+        t.ask_sizes[..2].copy_from_slice(&[150, 250]);
+        t
+    }
+    
+    #[test]
+    fn test_book_reflects_newer_tick() {
+        let mut book = OrderBook::new();
+        assert!(book.apply(&tick(7, 1_000, 100.00, 100.02)));
+        assert!(book.apply(&tick(7, 2_000, 100.01, 100.04)));
+        
+        assert_eq!(book.best_bid(7), Some(100.01));
+        assert_eq!(book.best_ask(7), Some(100.04));
+        assert!((book.spread(7).unwrap() - 0.03).abs() < 1e-9);
+        let (price, size) = book.level(7, BookSide::Ask, 1).unwrap();
+        assert!((price - 100.05).abs() < 1e-9);
+        assert_eq!(size, 250);
+        assert_eq!(book.level(7, BookSide::Bid, 2), None);  // Beyond depth_levels
+        assert_eq!(book.best_bid(8), None);
+    }
+    
+    #[test]
+    fn test_book_ignores_older_tick() {
+        let mut book = OrderBook::new();
+        book.apply(&tick(7, 2_000, 100.01, 100.04));
+        assert!(!book.apply(&tick(7, 1_000, 99.00, 99.02)));
+        
+        assert_eq!(book.best_bid(7), Some(100.01));
+        assert_eq!(book.rejected_updates(), 1);
+    }
+}
@@ -0,0 +1,198 @@
+// Order lifecycle tracking
+// `Order::is_active` only says live/not-live; this keeps the full state per order_id
+// and rejects transitions the venue protocol does not allow
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::Order;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderState {
+    New,
+    PendingNew,
+    Acked,
+    PartiallyFilled { filled: u64 },
+    Filled,
+    Cancelled,
+    Rejected,
+}
+
+impl OrderState {
+    /// No further events are accepted once an order is terminal
+    #[inline(always)]
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, OrderState::Filled | OrderState::Cancelled | OrderState::Rejected)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderEvent {
+    Submit,
+    Ack,
+    Fill { quantity: u64 },
+    Cancel,
+    Reject,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateError {
+    UnknownOrder(u64),
+    AlreadyTracked(u64),
+    Illegal { from: OrderState, event: OrderEvent },
+    Overfill { remaining: u64, fill: u64 },
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateError::UnknownOrder(id) => write!(f, "unknown order {}", id),
+            StateError::AlreadyTracked(id) => write!(f, "order {} is already tracked", id),
+            StateError::Illegal { from, event } => write!(f, "illegal {:?} in state {:?}", event, from),
+            StateError::Overfill { remaining, fill } => write!(f, "fill of {} exceeds remaining {}", fill, remaining),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+struct Tracked {
+    quantity: u64,
+    state: OrderState,
+}
+
+// Order Tracker
+
+#[derive(Default)]
+pub struct OrderTracker {
+    orders: HashMap<u64, Tracked>,
+}
+
+impl OrderTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Start tracking an order in `New`
+    pub fn track(&mut self, order: &Order) -> Result<(), StateError> {
+        if self.orders.contains_key(&order.order_id) {
+            return Err(StateError::AlreadyTracked(order.order_id));
+        }
+        self.orders.insert(order.order_id, Tracked { quantity: order.quantity, state: OrderState::New });
+        Ok(())
+    }
+    
+    /// Apply an event; the state is left unchanged on error
+    pub fn transition(&mut self, order_id: u64, event: OrderEvent) -> Result<OrderState, StateError> {
+        let tracked = self.orders.get_mut(&order_id).ok_or(StateError::UnknownOrder(order_id))?;
+        let from = tracked.state;
+        let illegal = StateError::Illegal { from, event };
+        
+        let next = match (from, event) {
+            (OrderState::New, OrderEvent::Submit) => OrderState::PendingNew,
+            (OrderState::PendingNew, OrderEvent::Ack) => OrderState::Acked,
+            (OrderState::PendingNew, OrderEvent::Reject) => OrderState::Rejected,
+            (OrderState::PendingNew | OrderState::Acked | OrderState::PartiallyFilled { .. }, OrderEvent::Cancel) => {
+                OrderState::Cancelled
+            }
+            (OrderState::Acked | OrderState::PartiallyFilled { .. }, OrderEvent::Fill { quantity }) => {
+                let filled = match from {
+                    OrderState::PartiallyFilled { filled } => filled,
+                    _ => 0,
+                };
+                let remaining = tracked.quantity - filled;
+                if quantity == 0 {
+                    return Err(illegal);
+                }
+                if quantity > remaining {
+                    return Err(StateError::Overfill { remaining, fill: quantity });
+                }
+                if quantity == remaining {
+                    OrderState::Filled
+                } else {
+                    OrderState::PartiallyFilled { filled: filled + quantity }
+                }
+            }
+            _ => return Err(illegal),
+        };
+        
+        tracked.state = next;
+        Ok(next)
+    }
+    
+    pub fn state(&self, order_id: u64) -> Option<OrderState> {
+        self.orders.get(&order_id).map(|t| t.state)
+    }
+    
+    /// Quantity still working at the venue; 0 once the order is terminal
+    pub fn remaining_qty(&self, order_id: u64) -> Option<u64> {
+        self.orders.get(&order_id).map(|t| match t.state {
+            OrderState::PartiallyFilled { filled } => t.quantity - filled,
+            state if state.is_terminal() => 0,
+            _ => t.quantity,
+        })
+    }
+    
+    /// Stop tracking an order (typically once terminal)
+    pub fn remove(&mut self, order_id: u64) -> Option<OrderState> {
+        self.orders.remove(&order_id).map(|t| t.state)
+    }
+    
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn order(order_id: u64, quantity: u64) -> Order {
+        Order { order_id, quantity, ..Default::default() }
+    }
+    
+    #[test]
+    fn test_normal_lifecycle() {
+        let mut tracker = OrderTracker::new();
+        tracker.track(&order(1, 100)).unwrap();
+        assert_eq!(tracker.track(&order(1, 100)), Err(StateError::AlreadyTracked(1)));
+        
+        assert_eq!(tracker.transition(1, OrderEvent::Submit), Ok(OrderState::PendingNew));
+        assert_eq!(tracker.transition(1, OrderEvent::Ack), Ok(OrderState::Acked));
+        assert_eq!(tracker.remaining_qty(1), Some(100));
+        
+        assert_eq!(tracker.transition(1, OrderEvent::Fill { quantity: 30 }), Ok(OrderState::PartiallyFilled { filled: 30 }));
+        assert_eq!(tracker.remaining_qty(1), Some(70));
+        assert_eq!(
+            tracker.transition(1, OrderEvent::Fill { quantity: 80 }),
+            Err(StateError::Overfill { remaining: 70, fill: 80 })
+        );
+        assert_eq!(tracker.transition(1, OrderEvent::Fill { quantity: 70 }), Ok(OrderState::Filled));
+        assert_eq!(tracker.remaining_qty(1), Some(0));
+    }
+    
+    #[test]
+    fn test_illegal_transitions_rejected() {
+        let mut tracker = OrderTracker::new();
+        tracker.track(&order(2, 50)).unwrap();
+        
+        // Can't fill before the venue has acked
+        assert!(matches!(tracker.transition(2, OrderEvent::Fill { quantity: 1 }), Err(StateError::Illegal { .. })));
+        
+        tracker.transition(2, OrderEvent::Submit).unwrap();
+        tracker.transition(2, OrderEvent::Ack).unwrap();
+        assert_eq!(tracker.transition(2, OrderEvent::Cancel), Ok(OrderState::Cancelled));
+        
+        assert_eq!(
+            tracker.transition(2, OrderEvent::Fill { quantity: 10 }),
+            Err(StateError::Illegal { from: OrderState::Cancelled, event: OrderEvent::Fill { quantity: 10 } })
+        );
+        assert_eq!(tracker.state(2), Some(OrderState::Cancelled));
+        assert_eq!(tracker.remaining_qty(2), Some(0));
+        assert_eq!(tracker.transition(99, OrderEvent::Ack), Err(StateError::UnknownOrder(99)));
+    }
+}
@@ -0,0 +1,302 @@
+// Fixed-size object pools so order bursts never touch the global allocator
+// Free slots are tracked by a tagged Treiber stack of indices
+
+use std::cell::UnsafeCell;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+use crate::Order;
+
+const NIL: u32 = u32::MAX;
+
+#[inline(always)]
+fn pack(tag: u32, index: u32) -> u64 {
+    ((tag as u64) << 32) | index as u64
+}
+
+#[inline(always)]
+fn unpack(word: u64) -> (u32, u32) {
+    ((word >> 32) as u32, word as u32)
+}
+
+// Object Pool
+
+/// Preallocated pool of `T` slots (orders, execution reports, tick buffers).
+///
+/// `acquire` and release never block or allocate: each is a single CAS on
+/// the free-list head that only retries when another thread won the race.
+/// The head carries a generation tag so a slot recycled between a load and
+/// the CAS can't corrupt the list (ABA).
+///
+/// Every slot also counts how often it has been released. A `Pooled` can be
+/// detached into a plain `PoolHandle` (e.g. to travel through a `Copy`
+/// queue) and turned back with `reclaim`, which refuses handles from an
+/// earlier generation or ones already reclaimed; debug builds panic there,
+/// since it always means a use-after-release.
+pub struct ObjectPool<T> {
+    slots: Box<[UnsafeCell<T>]>,
+    next: Box<[AtomicU32]>,
+    // Per slot: generation << 1 | detached
+    states: Box<[AtomicU32]>,
+    head: AtomicU64,
+    available: AtomicU32,
+    reset: fn(&mut T),
+}
+
+/// Order pool; slots are handed out zeroed
+pub type OrderPool = ObjectPool<Order>;
+pub type PooledOrder<'a> = Pooled<'a, Order>;
+
+impl<T: Default> ObjectPool<T> {
+    /// Pool whose slots are reset to `T::default()` on every `acquire`
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0 && capacity < NIL as usize, "Pool capacity out of range");
+        
+        let next = (0..capacity)
+            .map(|i| AtomicU32::new(if i + 1 < capacity { i as u32 + 1 } else { NIL }))
+            .collect();
+        
+        Self {
+            slots: (0..capacity).map(|_| UnsafeCell::new(T::default())).collect(),
+            next,
+            states: (0..capacity).map(|_| AtomicU32::new(0)).collect(),
+            head: AtomicU64::new(pack(0, 0)),
+            available: AtomicU32::new(capacity as u32),
+            reset: |value| *value = T::default(),
+        }
+    }
+}
+
+impl<T> ObjectPool<T> {
+    /// Replace the per-acquire reset, e.g. `Vec::clear` to keep a buffer's
+    /// allocation instead of dropping it
+    pub fn with_reset(mut self, reset: fn(&mut T)) -> Self {
+        self.reset = reset;
+        self
+    }
+    
+    /// Take a freshly reset slot, or `None` if the pool is exhausted
+    #[inline(always)]
+    pub fn acquire(&self) -> Option<Pooled<'_, T>> {
+        let mut head = self.head.load(Ordering::Acquire);
+        loop {
+            let (tag, index) = unpack(head);
+            if index == NIL {
+                return None;
+            }
+            let next = self.next[index as usize].load(Ordering::Relaxed);
+            match self.head.compare_exchange_weak(
+                head,
+                pack(tag.wrapping_add(1), next),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    self.available.fetch_sub(1, Ordering::Relaxed);
+                    // Exclusive: the slot is off the free list until released
+                    unsafe { (self.reset)(&mut *self.slots[index as usize].get()) };
+                    return Some(Pooled { pool: self, index });
+                }
+                Err(current) => head = current,
+            }
+        }
+    }
+    
+    #[inline(always)]
+    fn release(&self, index: u32) {
+        // New generation: any handle still pointing here is now stale
+        let state = &self.states[index as usize];
+        state.store((state.load(Ordering::Relaxed) | 1).wrapping_add(1), Ordering::Relaxed);
+        
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            let (tag, top) = unpack(head);
+            self.next[index as usize].store(top, Ordering::Relaxed);
+            match self.head.compare_exchange_weak(
+                head,
+                pack(tag.wrapping_add(1), index),
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    self.available.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+                Err(current) => head = current,
+            }
+        }
+    }
+    
+    /// Turn a detached handle back into exclusive access. `None` if the
+    /// handle is stale (its slot was released since) or already reclaimed
+    #[inline(always)]
+    pub fn reclaim(&self, handle: PoolHandle) -> Option<Pooled<'_, T>> {
+        let state = self.states.get(handle.index as usize)?;
+        let detached = handle.generation << 1 | 1;
+        // Acquire pairs with `into_handle`'s Release: the detaching thread's writes are visible
+        match state.compare_exchange(detached, detached & !1, Ordering::Acquire, Ordering::Relaxed) {
+            Ok(_) => Some(Pooled { pool: self, index: handle.index }),
+            Err(_) => {
+                debug_assert!(false, "use after release: stale or duplicate {:?}", handle);
+                None
+            }
+        }
+    }
+    
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+    
+    /// Free slots (approximate while other threads are acquiring/releasing)
+    pub fn available(&self) -> usize {
+        self.available.load(Ordering::Relaxed) as usize
+    }
+}
+
+unsafe impl<T: Send> Send for ObjectPool<T> {}
+unsafe impl<T: Send> Sync for ObjectPool<T> {}
+
+/// Exclusive handle to a pooled value; returns the slot on drop
+pub struct Pooled<'a, T> {
+    pool: &'a ObjectPool<T>,
+    index: u32,
+}
+
+impl<T> Pooled<'_, T> {
+    pub fn slot_index(&self) -> usize {
+        self.index as usize
+    }
+    
+    /// Give up the borrow without releasing the slot; the value stays put
+    /// until `ObjectPool::reclaim` turns the handle back into a `Pooled`
+    #[inline(always)]
+    pub fn into_handle(self) -> PoolHandle {
+        let state = &self.pool.states[self.index as usize];
+        let generation = state.load(Ordering::Relaxed) >> 1;
+        state.store(generation << 1 | 1, Ordering::Release);
+        let handle = PoolHandle { index: self.index, generation };
+        std::mem::forget(self);
+        handle
+    }
+}
+
+impl<T> Deref for Pooled<'_, T> {
+    type Target = T;
+    
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        unsafe { &*self.pool.slots[self.index as usize].get() }
+    }
+}
+
+impl<T> DerefMut for Pooled<'_, T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.pool.slots[self.index as usize].get() }
+    }
+}
+
+impl<T> Drop for Pooled<'_, T> {
+    fn drop(&mut self) {
+        self.pool.release(self.index);
+    }
+}
+
+/// Detached, copyable reference to a pooled slot (see `Pooled::into_handle`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolHandle {
+    index: u32,
+    generation: u32,
+}
+
+impl PoolHandle {
+    pub fn slot_index(&self) -> usize {
+        self.index as usize
+    }
+    
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_pool_exhaustion_and_reuse() {
+        let pool = OrderPool::new(4);
+        let mut held: Vec<PooledOrder> = (0..4).map(|_| pool.acquire().unwrap()).collect();
+        assert_eq!(pool.available(), 0);
+        assert!(pool.acquire().is_none());
+        
+        held[2].order_id = 99;
+        let freed_slot = held[2].slot_index();
+        held.remove(2);
+        assert_eq!(pool.available(), 1);
+        
+        let reacquired = pool.acquire().unwrap();
+        assert_eq!(reacquired.slot_index(), freed_slot);
+        assert_eq!(reacquired.order_id, 0);  // Slots are handed out zeroed
+        assert!(pool.acquire().is_none());
+    }
+    
+    #[test]
+    fn test_pool_concurrent_churn() {
+        let pool = OrderPool::new(8);
+        
+        std::thread::scope(|s| {
+            for t in 0..4u64 {
+                let pool = &pool;
+                s.spawn(move || {
+                    for i in 0..10_000u64 {
+                        if let Some(mut order) = pool.acquire() {
+                            order.order_id = t * 1_000_000 + i;
+                            assert_eq!(order.order_id, t * 1_000_000 + i);
+                        }
+                    }
+                });
+            }
+        });
+        
+        assert_eq!(pool.available(), 8);
+        let all: Vec<_> = (0..8).map(|_| pool.acquire().unwrap()).collect();
+        let mut indices: Vec<_> = all.iter().map(|o| o.slot_index()).collect();
+        indices.sort_unstable();
+        assert_eq!(indices, (0..8).collect::<Vec<_>>());
+    }
+    
+    #[test]
+    fn test_handle_roundtrip_through_queue() {
+        let pool: ObjectPool<Vec<u8>> = ObjectPool::new(2).with_reset(Vec::clear);
+        let queue: crate::LockFreeSPSC<PoolHandle, 4> = crate::LockFreeSPSC::new();
+        
+        let mut buf = pool.acquire().unwrap();
+        buf.extend_from_slice(b"tick");
+        let capacity = buf.capacity();
+        assert!(queue.push(buf.into_handle()));
+        assert_eq!(pool.available(), 1);  // Still out while detached
+        
+        let buf = pool.reclaim(queue.pop().unwrap()).unwrap();
+        assert_eq!(&buf[..], b"tick");
+        drop(buf);
+        
+        // Reset kept the allocation
+        let again = pool.acquire().unwrap();
+        assert!(again.is_empty());
+        assert!(again.capacity() >= capacity);
+    }
+    
+    #[test]
+    #[cfg_attr(debug_assertions, should_panic(expected = "use after release"))]
+    fn test_stale_handle_rejected() {
+        let pool = OrderPool::new(1);
+        let handle = pool.acquire().unwrap().into_handle();
+        assert_eq!(handle.generation(), 0);
+        drop(pool.reclaim(handle).unwrap());
+        
+        // Slot released and handed out again: the old handle must not reach it
+        let _current = pool.acquire().unwrap();
+        assert!(pool.reclaim(handle).is_none());
+    }
+}
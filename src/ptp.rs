@@ -0,0 +1,225 @@
+// NIC hardware timestamps and the PTP clock
+// A NIC with hardware timestamping stamps each packet on its own PTP hardware
+// clock (PHC), disciplined by ptp4l, usually to TAI. Local stamps come from
+// the TSC via `HiResTimer`. `PtpOffset` tracks the gap between the two so a
+// hardware receive stamp can be placed on the local timeline, and one-way
+// latency can be split into exchange->NIC and NIC->strategy.
+
+/// Estimated `phc - local` clock offset from bracketed readings.
+///
+/// Each sample is a PHC reading taken between two local readings. The
+/// midpoint of the bracket is the best guess for the local time of the PHC
+/// read, and half the bracket bounds the error. Samples wider than
+/// `max_bracket_ns` (preempted reads) are counted and ignored.
+#[derive(Debug, Clone, Copy)]
+pub struct PtpOffset {
+    offset_ns: Option<i64>,
+    uncertainty_ns: u64,
+    max_bracket_ns: u64,
+    rejected: u64,
+}
+
+impl PtpOffset {
+    pub const DEFAULT_MAX_BRACKET_NS: u64 = 10_000;
+    
+    pub fn new() -> Self {
+        Self { offset_ns: None, uncertainty_ns: 0, max_bracket_ns: Self::DEFAULT_MAX_BRACKET_NS, rejected: 0 }
+    }
+    
+    pub fn with_max_bracket(mut self, max_bracket_ns: u64) -> Self {
+        self.max_bracket_ns = max_bracket_ns;
+        self
+    }
+    
+    /// Feed one `(local, phc, local)` bracket; returns whether it was used
+    pub fn observe(&mut self, local_before_ns: i64, phc_ns: i64, local_after_ns: i64) -> bool {
+        let bracket = local_after_ns.saturating_sub(local_before_ns);
+        if bracket < 0 || bracket as u64 > self.max_bracket_ns {
+            self.rejected += 1;
+            return false;
+        }
+        let local_mid = local_before_ns + bracket / 2;
+        self.offset_ns = Some(phc_ns - local_mid);
+        self.uncertainty_ns = bracket as u64 / 2;
+        true
+    }
+    
+    /// `phc - local` in nanoseconds; `None` until a sample is accepted
+    pub fn offset_ns(&self) -> Option<i64> {
+        self.offset_ns
+    }
+    
+    /// Half-width of the bracket behind the current offset
+    pub fn uncertainty_ns(&self) -> u64 {
+        self.uncertainty_ns
+    }
+    
+    /// Samples dropped for being wider than `max_bracket_ns`
+    pub fn rejected(&self) -> u64 {
+        self.rejected
+    }
+    
+    /// A PHC stamp moved onto the local (`HiResTimer::now_ns`) timeline
+    #[inline(always)]
+    pub fn phc_to_local_ns(&self, phc_ns: i64) -> Option<i64> {
+        self.offset_ns.map(|offset| phc_ns - offset)
+    }
+}
+
+impl Default for PtpOffset {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Receive stamps delivered with a packet under `SO_TIMESTAMPING`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RxTimestamps {
+    /// Kernel stamp on the system clock
+    pub software_ns: Option<i64>,
+    /// NIC stamp on its PHC
+    pub hardware_ns: Option<i64>,
+}
+
+/// Decode the `SCM_TIMESTAMPING` control message payload: three
+/// `struct timespec` (software, deprecated, raw hardware); all-zero entries
+/// are absent. `None` if `data` is too short
+pub fn parse_scm_timestamping(data: &[u8]) -> Option<RxTimestamps> {
+    const TIMESPEC: usize = 16;
+    if data.len() < 3 * TIMESPEC {
+        return None;
+    }
+    let stamp = |i: usize| {
+        let field = |at: usize| i64::from_ne_bytes(data[at..at + 8].try_into().unwrap());
+        let (sec, nsec) = (field(i * TIMESPEC), field(i * TIMESPEC + 8));
+        (sec != 0 || nsec != 0).then(|| sec.saturating_mul(1_000_000_000).saturating_add(nsec))
+    };
+    Some(RxTimestamps { software_ns: stamp(0), hardware_ns: stamp(2) })
+}
+
+#[cfg(target_os = "linux")]
+pub use self::linux::{enable_rx_timestamping, PhcClock};
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::ffi::{c_int, c_void, CString};
+    use std::io;
+    
+    use crate::HiResTimer;
+    
+    const SOL_SOCKET: c_int = 1;
+    const SO_TIMESTAMPING: c_int = 37;
+    const SOF_TIMESTAMPING_RX_HARDWARE: u32 = 1 << 2;
+    const SOF_TIMESTAMPING_RX_SOFTWARE: u32 = 1 << 3;
+    const SOF_TIMESTAMPING_SOFTWARE: u32 = 1 << 4;
+    const SOF_TIMESTAMPING_RAW_HARDWARE: u32 = 1 << 6;
+    const O_RDONLY: c_int = 0;
+    
+    #[repr(C)]
+    struct Timespec {
+        tv_sec: i64,
+        tv_nsec: i64,
+    }
+    
+    extern "C" {
+        fn setsockopt(fd: c_int, level: c_int, name: c_int, value: *const c_void, len: u32) -> c_int;
+        fn open(path: *const std::ffi::c_char, flags: c_int) -> c_int;
+        fn close(fd: c_int) -> c_int;
+        fn clock_gettime(clock: c_int, ts: *mut Timespec) -> c_int;
+    }
+    
+    /// Ask the kernel for software and (with `hardware`) NIC receive stamps
+    /// on `fd`; read them back with `recvmsg` and `parse_scm_timestamping`.
+    /// The NIC itself must also be configured (`SIOCSHWTSTAMP` / `hwstamp_ctl`)
+    pub fn enable_rx_timestamping(fd: c_int, hardware: bool) -> io::Result<()> {
+        let mut flags = SOF_TIMESTAMPING_RX_SOFTWARE | SOF_TIMESTAMPING_SOFTWARE;
+        if hardware {
+            flags |= SOF_TIMESTAMPING_RX_HARDWARE | SOF_TIMESTAMPING_RAW_HARDWARE;
+        }
+        let rc = unsafe { setsockopt(fd, SOL_SOCKET, SO_TIMESTAMPING, &flags as *const u32 as *const c_void, 4) };
+        if rc == 0 { Ok(()) } else { Err(io::Error::last_os_error()) }
+    }
+    
+    /// A NIC's PTP hardware clock (`/dev/ptpN`), read as a dynamic POSIX clock
+    pub struct PhcClock {
+        fd: c_int,
+    }
+    
+    impl PhcClock {
+        pub fn open(path: &str) -> io::Result<Self> {
+            let path = CString::new(path).map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+            match unsafe { open(path.as_ptr(), O_RDONLY) } {
+                -1 => Err(io::Error::last_os_error()),
+                fd => Ok(Self { fd }),
+            }
+        }
+        
+        pub fn now_ns(&self) -> io::Result<i64> {
+            // FD_TO_CLOCKID from the kernel's posix-timers
+            let clock = (!self.fd << 3) | 3;
+            let mut ts = Timespec { tv_sec: 0, tv_nsec: 0 };
+            if unsafe { clock_gettime(clock, &mut ts) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(ts.tv_sec * 1_000_000_000 + ts.tv_nsec)
+        }
+        
+        /// Take a PHC reading bracketed by local ones and feed it to `offset`
+        pub fn sample(&self, offset: &mut super::PtpOffset) -> io::Result<bool> {
+            let before = HiResTimer::now_ns();
+            let phc = self.now_ns()?;
+            let after = HiResTimer::now_ns();
+            Ok(offset.observe(before, phc, after))
+        }
+    }
+    
+    impl Drop for PhcClock {
+        fn drop(&mut self) {
+            unsafe { close(self.fd) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // PHC running TAI, 37s ahead of UTC
+    const TAI_UTC_NS: i64 = 37_000_000_000;
+    
+    #[test]
+    fn test_offset_from_brackets() {
+        let mut offset = PtpOffset::new();
+        assert_eq!(offset.phc_to_local_ns(0), None);
+        
+        let local = 1_700_000_000_000_000_000;
+        assert!(offset.observe(local, local + 400 + TAI_UTC_NS, local + 800));
+        assert_eq!(offset.offset_ns(), Some(TAI_UTC_NS));
+        assert_eq!(offset.uncertainty_ns(), 400);
+        
+        // Preempted read: ignored, previous estimate kept
+        assert!(!offset.observe(local, local + TAI_UTC_NS, local + 1_000_000));
+        assert_eq!(offset.rejected(), 1);
+        assert_eq!(offset.phc_to_local_ns(local + TAI_UTC_NS + 5), Some(local + 5));
+    }
+    
+    #[test]
+    fn test_parse_scm_timestamping() {
+        let mut data = [0u8; 48];
+        data[0..8].copy_from_slice(&5i64.to_ne_bytes());
+        data[8..16].copy_from_slice(&7i64.to_ne_bytes());
+        data[32..40].copy_from_slice(&6i64.to_ne_bytes());
+        assert_eq!(
+            parse_scm_timestamping(&data),
+            Some(RxTimestamps { software_ns: Some(5_000_000_007), hardware_ns: Some(6_000_000_000) })
+        );
+        assert_eq!(parse_scm_timestamping(&[0u8; 48]), Some(RxTimestamps::default()));
+        assert_eq!(parse_scm_timestamping(&data[..40]), None);
+    }
+    
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_phc_missing_device() {
+        assert!(PhcClock::open("/dev/ptp-does-not-exist").is_err());
+    }
+}
@@ -0,0 +1,185 @@
+// Queue occupancy and drop counters
+// Built into the queues when the `queue_stats` feature is on; otherwise the
+// counters are a zero-sized no-op and the hot paths compile exactly as before.
+// Producer-side and consumer-side counters live on separate cache lines so
+// counting doesn't add false sharing between the two threads.
+
+#[cfg(feature = "queue_stats")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "queue_stats")]
+use crate::sync::CachePadded;
+
+/// Counter snapshot returned by the queues' `stats()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct QueueStats {
+    pub pushed: u64,
+    pub popped: u64,
+    /// Items the queue refused because it was full
+    pub failed_pushes: u64,
+    /// Items removed by the producer under `push_evict` on a `DropOldest` ring
+    pub evicted: u64,
+    /// Largest occupancy observed right after a push
+    pub high_water: u64,
+}
+
+#[cfg(feature = "queue_stats")]
+#[derive(Default)]
+struct ProducerCounters {
+    pushed: AtomicU64,
+    failed: AtomicU64,
+    evicted: AtomicU64,
+    high_water: AtomicU64,
+}
+
+#[cfg(feature = "queue_stats")]
+#[derive(Default)]
+pub(crate) struct QueueCounters {
+    producer: CachePadded<ProducerCounters>,
+    popped: CachePadded<AtomicU64>,
+}
+
+#[cfg(not(feature = "queue_stats"))]
+pub(crate) struct QueueCounters;
+
+#[cfg(feature = "queue_stats")]
+impl QueueCounters {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+    
+    /// `n` items pushed; `occupancy` is only evaluated here, never when counting is off
+    #[inline(always)]
+    pub(crate) fn pushed(&self, n: u64, occupancy: impl FnOnce() -> u64) {
+        self.producer.pushed.fetch_add(n, Ordering::Relaxed);
+        let occupancy = occupancy();
+        // Plain load first: the max only changes while a backlog is building
+        if occupancy > self.producer.high_water.load(Ordering::Relaxed) {
+            self.producer.high_water.fetch_max(occupancy, Ordering::Relaxed);
+        }
+    }
+    
+    #[inline(always)]
+    pub(crate) fn failed(&self, n: u64) {
+        self.producer.failed.fetch_add(n, Ordering::Relaxed);
+    }
+    
+    #[inline(always)]
+    pub(crate) fn evicted(&self) {
+        self.producer.evicted.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    #[inline(always)]
+    pub(crate) fn popped(&self, n: u64) {
+        self.popped.fetch_add(n, Ordering::Relaxed);
+    }
+    
+    pub(crate) fn snapshot(&self) -> QueueStats {
+        QueueStats {
+            pushed: self.producer.pushed.load(Ordering::Relaxed),
+            popped: self.popped.load(Ordering::Relaxed),
+            failed_pushes: self.producer.failed.load(Ordering::Relaxed),
+            evicted: self.producer.evicted.load(Ordering::Relaxed),
+            high_water: self.producer.high_water.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(not(feature = "queue_stats"))]
+impl QueueCounters {
+    pub(crate) fn new() -> Self {
+        Self
+    }
+    
+    #[inline(always)]
+    pub(crate) fn pushed(&self, _n: u64, _occupancy: impl FnOnce() -> u64) {}
+    
+    #[inline(always)]
+    pub(crate) fn failed(&self, _n: u64) {}
+    
+    #[inline(always)]
+    pub(crate) fn evicted(&self) {}
+    
+    #[inline(always)]
+    pub(crate) fn popped(&self, _n: u64) {}
+}
+
+#[cfg(all(test, feature = "queue_stats"))]
+mod tests {
+    use super::*;
+    use crate::mpmc::LockFreeMPMC;
+    use crate::mpsc::MpscRing;
+    use crate::{DropOldestSPSC, FullStrategy, StatsSPSC};
+    
+    #[test]
+    fn test_spsc_stats() {
+        let queue: DropOldestSPSC<u64, 4> = DropOldestSPSC::new();
+        for i in 0..4 {
+            assert!(queue.push(i));
+        }
+        assert!(!queue.push(4));
+        assert_eq!(queue.push_batch(&[5, 6]), 0);
+        queue.push_evict(7);
+        // Spinning retries count once, and only if they give up
+        queue.push_or(8, FullStrategy::SpinRetry { max_spins: 3 });
+        let mut out = [0u64; 2];
+        assert_eq!(queue.pop_batch(&mut out), 2);
+        queue.pop();
+        
+        assert_eq!(
+            queue.stats(),
+            QueueStats { pushed: 5, popped: 3, failed_pushes: 4, evicted: 1, high_water: 4 }
+        );
+    }
+    
+    #[test]
+    fn test_spsc_high_water_mark() {
+        let queue: StatsSPSC<u64, 16> = StatsSPSC::new();
+        
+        for i in 0..12 {
+            assert!(queue.push(i));
+        }
+        for _ in 0..10 {
+            queue.pop().unwrap();
+        }
+        for i in 0..5 {
+            assert!(queue.push(i));
+        }
+        assert_eq!(queue.size(), 7);
+        assert_eq!(queue.high_water_mark(), 12);
+        
+        // Fill to capacity, then overflow
+        for i in 0..9 {
+            assert!(queue.push(i));
+        }
+        assert!(!queue.push(99));
+        assert!(!queue.push(99));
+        assert_eq!(queue.high_water_mark(), 16);
+        assert_eq!(queue.total_pushed(), 26);
+        assert_eq!(queue.total_popped(), 10);
+        assert_eq!(queue.total_dropped(), 2);
+        assert_eq!(
+            queue.stats(),
+            QueueStats { pushed: 26, popped: 10, failed_pushes: 2, evicted: 0, high_water: 16 }
+        );
+    }
+    
+    #[test]
+    fn test_mpmc_and_mpsc_stats() {
+        let queue: LockFreeMPMC<u32, 2> = LockFreeMPMC::new();
+        assert!(queue.push(1));
+        assert!(queue.push(2));
+        assert!(!queue.push(3));
+        queue.pop();
+        assert_eq!(queue.stats(), QueueStats { pushed: 2, popped: 1, failed_pushes: 1, evicted: 0, high_water: 2 });
+        
+        let (producer, mut consumer) = MpscRing::<u32, 4>::channel();
+        let other = producer.clone();
+        for i in 0..3 {
+            producer.push(i);
+            other.push(i);
+        }
+        while consumer.pop().is_some() {}
+        assert_eq!(consumer.stats(), QueueStats { pushed: 4, popped: 4, failed_pushes: 2, evicted: 0, high_water: 4 });
+    }
+}
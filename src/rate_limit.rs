@@ -0,0 +1,257 @@
+// Exchange message throttling
+// Token bucket in its GCRA form: one atomic "theoretical arrival time" per
+// limiter, so `try_acquire` is a load and a CAS with no refill timer and no
+// lock, at nanosecond resolution.
+// Each message pushes the arrival time one emission interval further out;
+// a message is allowed while that stays within `burst` intervals of now.
+
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+use crate::clock::{self, Clock};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Nanoseconds between messages at the sustained rate
+    pub interval_ns: u64,
+    /// Messages that may go back-to-back after an idle spell
+    pub burst: u32,
+}
+
+impl RateLimit {
+    /// `sustained` messages per second, with bursts of up to `burst`
+    pub fn per_second(sustained: u64, burst: u32) -> Self {
+        assert!(sustained > 0, "Sustained rate must be positive");
+        assert!(burst > 0, "Burst must be at least one message");
+        Self { interval_ns: (1_000_000_000 / sustained).max(1), burst }
+    }
+}
+
+/// Session-wide limiter; shareable across threads
+pub struct RateLimiter {
+    limit: RateLimit,
+    tat_ns: AtomicI64,  // Theoretical arrival time of the next message
+    clock: Arc<dyn Clock>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: RateLimit) -> Self {
+        Self { limit, tat_ns: AtomicI64::new(i64::MIN), clock: clock::system() }
+    }
+    
+    /// Time source for `try_acquire` (default: `HiResTimer`)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    /// Take one message credit now; false means send later
+    #[inline(always)]
+    pub fn try_acquire(&self) -> bool {
+        self.try_acquire_n_at(1, self.clock.now_ns())
+    }
+    
+    /// Take `n` credits at once (e.g. a quote pair), all or nothing
+    #[inline(always)]
+    pub fn try_acquire_n(&self, n: u32) -> bool {
+        self.try_acquire_n_at(n, self.clock.now_ns())
+    }
+    
+    #[inline(always)]
+    pub fn try_acquire_n_at(&self, n: u32, now_ns: i64) -> bool {
+        let cost = n as i64 * self.limit.interval_ns as i64;
+        let window = self.limit.burst as i64 * self.limit.interval_ns as i64;
+        let mut tat = self.tat_ns.load(Ordering::Relaxed);
+        loop {
+            let next = tat.max(now_ns).saturating_add(cost);
+            if next - now_ns > window {
+                return false;
+            }
+            match self.tat_ns.compare_exchange_weak(tat, next, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => return true,
+                Err(current) => tat = current,
+            }
+        }
+    }
+    
+    /// Give back `n` credits taken for messages that in the end weren't sent
+    pub fn refund_n(&self, n: u32) {
+        self.refund_n_at(n, self.clock.now_ns())
+    }
+    
+    /// Refunds never bank more than a full burst: the arrival time stops at
+    /// `now_ns` minus the burst window, and one that isn't set yet stays unset
+    pub fn refund_n_at(&self, n: u32, now_ns: i64) {
+        let credit = (n as i64).checked_mul(self.limit.interval_ns as i64).unwrap_or(i64::MAX);
+        let floor = now_ns.saturating_sub(self.limit.burst as i64 * self.limit.interval_ns as i64);
+        let mut tat = self.tat_ns.load(Ordering::Relaxed);
+        loop {
+            if tat <= floor {
+                return;
+            }
+            let refunded = tat.saturating_sub(credit).max(floor);
+            match self.tat_ns.compare_exchange_weak(tat, refunded, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => return,
+                Err(current) => tat = current,
+            }
+        }
+    }
+    
+    /// Nanoseconds until `n` credits would be granted at `now_ns` (0 if now)
+    pub fn wait_ns_at(&self, n: u32, now_ns: i64) -> u64 {
+        let cost = n as i64 * self.limit.interval_ns as i64;
+        let window = self.limit.burst as i64 * self.limit.interval_ns as i64;
+        let next = self.tat_ns.load(Ordering::Relaxed).max(now_ns).saturating_add(cost);
+        (next - now_ns - window).max(0) as u64
+    }
+    
+    pub fn limit(&self) -> RateLimit {
+        self.limit
+    }
+}
+
+/// One `RateLimiter` per `venue_id`; venues without a configured limit are unthrottled
+pub struct VenueRateLimiter {
+    venues: Box<[Option<RateLimiter>]>,
+    clock: Arc<dyn Clock>,
+}
+
+impl VenueRateLimiter {
+    pub fn new() -> Self {
+        Self { venues: (0..=u8::MAX).map(|_| None).collect(), clock: clock::system() }
+    }
+    
+    pub fn with_venue(mut self, venue_id: u8, limit: RateLimit) -> Self {
+        self.venues[venue_id as usize] = Some(RateLimiter::new(limit));
+        self
+    }
+    
+    /// Time source shared by every venue (default: `HiResTimer`)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    #[inline(always)]
+    pub fn try_acquire(&self, venue_id: u8) -> bool {
+        self.try_acquire_n_at(venue_id, 1, self.clock.now_ns())
+    }
+    
+    #[inline(always)]
+    pub fn try_acquire_n_at(&self, venue_id: u8, n: u32, now_ns: i64) -> bool {
+        match &self.venues[venue_id as usize] {
+            Some(limiter) => limiter.try_acquire_n_at(n, now_ns),
+            None => true,
+        }
+    }
+    
+    pub fn refund_n(&self, venue_id: u8, n: u32) {
+        self.refund_n_at(venue_id, n, self.clock.now_ns())
+    }
+    
+    pub fn refund_n_at(&self, venue_id: u8, n: u32, now_ns: i64) {
+        if let Some(limiter) = &self.venues[venue_id as usize] {
+            limiter.refund_n_at(n, now_ns);
+        }
+    }
+    
+    /// Nanoseconds until `n` credits would be granted on the venue at `now_ns`
+    pub fn wait_ns_at(&self, venue_id: u8, n: u32, now_ns: i64) -> u64 {
+        self.venues[venue_id as usize].as_ref().map_or(0, |limiter| limiter.wait_ns_at(n, now_ns))
+    }
+    
+    pub fn limit(&self, venue_id: u8) -> Option<RateLimit> {
+        self.venues[venue_id as usize].as_ref().map(RateLimiter::limit)
+    }
+}
+
+impl Default for VenueRateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    
+    const MS: i64 = 1_000_000;
+    
+    #[test]
+    fn test_burst_then_sustained_rate() {
+        // 100 msg/s sustained, bursts of 5
+        let limiter = RateLimiter::new(RateLimit::per_second(100, 5));
+        let t0 = 1_700_000_000_000_000_000;
+        assert_eq!((0..10).filter(|_| limiter.try_acquire_n_at(1, t0)).count(), 5);
+        
+        // One credit every 10ms after that
+        assert!(!limiter.try_acquire_n_at(1, t0 + 9 * MS));
+        assert_eq!(limiter.wait_ns_at(1, t0 + 9 * MS), MS as u64);
+        assert!(limiter.try_acquire_n_at(1, t0 + 10 * MS));
+        assert!(!limiter.try_acquire_n_at(1, t0 + 10 * MS));
+        
+        // Idle for a second: the full burst is back, and no more
+        assert!(!limiter.try_acquire_n_at(6, t0 + 1_000 * MS));
+        assert!(limiter.try_acquire_n_at(5, t0 + 1_000 * MS));
+        
+        // Refunded credits can be taken again
+        limiter.refund_n_at(2, t0 + 1_000 * MS);
+        assert!(limiter.try_acquire_n_at(2, t0 + 1_000 * MS));
+        assert!(!limiter.try_acquire_n_at(1, t0 + 1_000 * MS));
+    }
+    
+    #[test]
+    fn test_refunds_never_exceed_a_burst() {
+        let limiter = RateLimiter::new(RateLimit::per_second(100, 5));
+        let t0 = 1_700_000_000_000_000_000;
+        
+        // Before any acquire the arrival time is unset; a refund mustn't wrap it
+        limiter.refund_n_at(3, t0);
+        assert_eq!(limiter.wait_ns_at(1, t0), 0);
+        assert_eq!((0..10).filter(|_| limiter.try_acquire_n_at(1, t0)).count(), 5);
+        
+        // Over-refunding gives back the burst and no more
+        limiter.refund_n_at(u32::MAX, t0);
+        assert!(!limiter.try_acquire_n_at(6, t0));
+        assert!(limiter.try_acquire_n_at(5, t0));
+    }
+    
+    #[test]
+    fn test_venue_limits_and_clock() {
+        let clock = Arc::new(MockClock::new(0));
+        let limits = VenueRateLimiter::new()
+            .with_venue(1, RateLimit::per_second(1_000, 2))
+            .with_clock(clock.clone());
+        
+        assert!(limits.try_acquire(1));
+        assert!(limits.try_acquire(1));
+        assert!(!limits.try_acquire(1));
+        assert!((0..100).all(|_| limits.try_acquire(2)));  // Unconfigured venue
+        
+        clock.advance_ns(MS);
+        assert!(limits.try_acquire(1));
+        assert_eq!(limits.limit(1), Some(RateLimit { interval_ns: 1_000_000, burst: 2 }));
+        assert_eq!(limits.limit(2), None);
+        assert_eq!(limits.wait_ns_at(1, 1, clock.now_ns()), MS as u64);
+        assert_eq!(limits.wait_ns_at(2, 3, clock.now_ns()), 0);
+    }
+    
+    #[test]
+    fn test_concurrent_senders_share_budget() {
+        let limiter = RateLimiter::new(RateLimit::per_second(1, 64));
+        let granted = std::sync::atomic::AtomicUsize::new(0);
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..100 {
+                        if limiter.try_acquire_n_at(1, 0) {
+                            granted.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                });
+            }
+        });
+        assert_eq!(granted.into_inner(), 64);
+    }
+}
@@ -0,0 +1,320 @@
+// Market-data replay from capture files
+// Records are a little-endian u32 length prefix followed by MarketTick::to_bytes
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::{MarketTick, TickError};
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    /// Length prefix didn't match `MarketTick::WIRE_SIZE`
+    BadRecordLength { offset: u64, len: u32 },
+    /// File ended in the middle of a record
+    Truncated { offset: u64 },
+    Invalid(TickError),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "replay I/O error: {}", err),
+            ReplayError::BadRecordLength { offset, len } => {
+                write!(f, "bad record length {} at offset {}", len, offset)
+            }
+            ReplayError::Truncated { offset } => write!(f, "truncated record at offset {}", offset),
+            ReplayError::Invalid(err) => write!(f, "invalid tick: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> Self {
+        ReplayError::Io(err)
+    }
+}
+
+// Tick Writer (capture side)
+
+pub struct TickWriter {
+    out: BufWriter<File>,
+    written: u64,
+}
+
+impl TickWriter {
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, ReplayError> {
+        Ok(Self {
+            out: BufWriter::new(File::create(path)?),
+            written: 0,
+        })
+    }
+    
+    pub fn write(&mut self, tick: &MarketTick) -> Result<(), ReplayError> {
+        self.out.write_all(&(MarketTick::WIRE_SIZE as u32).to_le_bytes())?;
+        self.out.write_all(&tick.to_bytes())?;
+        self.written += 1;
+        Ok(())
+    }
+    
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+    
+    /// Flush buffered records to disk
+    pub fn finish(mut self) -> Result<(), ReplayError> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+// Tick Replay (backtest side)
+
+/// Iterates the ticks of a capture file in recorded order.
+///
+/// Iteration stops at the first I/O or framing error, which is then available
+/// from `error()`. Records failing `MarketTick::validate` are skipped and
+/// counted in `rejected()`, or surfaced as `ReplayError::Invalid` when strict.
+pub struct TickReplay {
+    input: BufReader<File>,
+    offset: u64,
+    pace: bool,
+    strict: bool,
+    anchor: Option<(i64, Instant)>,  // First tick timestamp and wall time it was emitted
+    rejected: u64,
+    error: Option<ReplayError>,
+}
+
+impl TickReplay {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ReplayError> {
+        Ok(Self {
+            input: BufReader::new(File::open(path)?),
+            offset: 0,
+            pace: false,
+            strict: false,
+            anchor: None,
+            rejected: 0,
+            error: None,
+        })
+    }
+    
+    /// Sleep between ticks so they are emitted at their recorded spacing
+    pub fn paced(mut self, pace: bool) -> Self {
+        self.pace = pace;
+        self
+    }
+    
+    /// Stop on the first invalid record instead of skipping it
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+    
+    pub fn rejected(&self) -> u64 {
+        self.rejected
+    }
+    
+    /// Error that ended iteration, if any
+    pub fn error(&self) -> Option<&ReplayError> {
+        self.error.as_ref()
+    }
+    
+    fn read_record(&mut self) -> Result<Option<MarketTick>, ReplayError> {
+        let record_offset = self.offset;
+        let mut len_buf = [0u8; 4];
+        
+        // A clean end of file falls between records; EOF inside the length
+        // prefix means the last write was cut off
+        let mut filled = 0;
+        while filled < len_buf.len() {
+            match self.input.read(&mut len_buf[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => return Err(ReplayError::Truncated { offset: record_offset }),
+                Ok(n) => filled += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        let len = u32::from_le_bytes(len_buf);
+        if len as usize != MarketTick::WIRE_SIZE {
+            return Err(ReplayError::BadRecordLength { offset: record_offset, len });
+        }
+        
+        let mut record = [0u8; MarketTick::WIRE_SIZE];
+        self.input.read_exact(&mut record).map_err(|err| match err.kind() {
+            io::ErrorKind::UnexpectedEof => ReplayError::Truncated { offset: record_offset },
+            _ => ReplayError::Io(err),
+        })?;
+        self.offset += 4 + len as u64;
+        
+        MarketTick::from_bytes(&record)
+            .map(Some)
+            .ok_or(ReplayError::Truncated { offset: record_offset })
+    }
+    
+    fn pace_to(&mut self, tick: &MarketTick) {
+        let (first_ts, started) = *self.anchor.get_or_insert((tick.timestamp_ns, Instant::now()));
+        let offset_ns = tick.timestamp_ns.saturating_sub(first_ts).max(0) as u64;
+        let due = started + Duration::from_nanos(offset_ns);
+        let now = Instant::now();
+        if due > now {
+            std::thread::sleep(due - now);
+        }
+    }
+}
+
+impl Iterator for TickReplay {
+    type Item = MarketTick;
+    
+    fn next(&mut self) -> Option<MarketTick> {
+        if self.error.is_some() {
+            return None;
+        }
+        
+        loop {
+            let tick = match self.read_record() {
+                Ok(Some(tick)) => tick,
+                Ok(None) => return None,
+                Err(err) => {
+                    self.error = Some(err);
+                    return None;
+                }
+            };
+            
+            if let Err(err) = tick.validate() {
+                self.rejected += 1;
+                if self.strict {
+                    self.error = Some(ReplayError::Invalid(err));
+                    return None;
+                }
+                continue;
+            }
+            
+            if self.pace {
+                self.pace_to(&tick);
+            }
+            return Some(tick);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn capture_path(tag: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("hft_replay_{}_{}.bin", tag, std::process::id()))
+    }
+    
+    fn tick(i: i64) -> MarketTick {
+        let mut t = MarketTick {
+            timestamp_ns: i * 1_000_000,
+            bid_price: 100.0 + i as f64 * 0.01,
+            ask_price: 100.02 + i as f64 * 0.01,
+            asset_id: 3,
+            depth_levels: 1,
+            ..Default::default()
+        };
+        t.mid_price = (t.bid_price + t.ask_price) / 2.0;
+        t.bid_sizes[0] = 100 + i as u64;
+        t
+    }
+    
+    #[test]
+    fn test_replay_roundtrip() {
+        let path = capture_path("roundtrip");
+        let mut writer = TickWriter::create(&path).unwrap();
+        for i in 0..5 {
+            writer.write(&tick(i)).unwrap();
+        }
+        assert_eq!(writer.written(), 5);
+        writer.finish().unwrap();
+        
+        let mut replay = TickReplay::open(&path).unwrap();
+        let replayed: Vec<MarketTick> = replay.by_ref().collect();
+        assert!(replay.error().is_none());
+        assert_eq!(replayed.len(), 5);
+        for (i, t) in replayed.iter().enumerate() {
+            assert_eq!(t.to_bytes(), tick(i as i64).to_bytes());
+        }
+        assert!(replayed.windows(2).all(|w| w[0].timestamp_ns < w[1].timestamp_ns));
+        
+        // Paced replay: 4ms of recorded spacing takes at least that long
+        let started = Instant::now();
+        assert_eq!(TickReplay::open(&path).unwrap().paced(true).count(), 5);
+        assert!(started.elapsed() >= Duration::from_millis(4));
+        
+        std::fs::remove_file(&path).unwrap();
+    }
+    
+    #[test]
+    fn test_replay_skips_invalid_and_stops_on_truncation() {
+        let path = capture_path("invalid");
+        let mut writer = TickWriter::create(&path).unwrap();
+        writer.write(&tick(0)).unwrap();
+        let mut crossed = tick(1);
+        crossed.bid_price = crossed.ask_price + 1.0;
+        writer.write(&crossed).unwrap();
+        writer.write(&tick(2)).unwrap();
+        writer.finish().unwrap();
+        
+        let mut replay = TickReplay::open(&path).unwrap();
+        let ts: Vec<i64> = replay.by_ref().map(|t| t.timestamp_ns).collect();
+        assert_eq!(ts, vec![0, 2_000_000]);
+        assert_eq!(replay.rejected(), 1);
+        
+        // Chop the last record in half
+        let len = std::fs::metadata(&path).unwrap().len();
+        let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(len - 100).unwrap();
+        
+        let mut replay = TickReplay::open(&path).unwrap().strict(true);
+        assert_eq!(replay.next().map(|t| t.timestamp_ns), Some(0));
+        assert!(replay.next().is_none());
+        assert!(matches!(replay.error(), Some(ReplayError::Invalid(TickError::CrossedBook { .. }))));
+        
+        let mut replay = TickReplay::open(&path).unwrap();
+        assert_eq!(replay.by_ref().count(), 1);
+        assert!(matches!(replay.error(), Some(ReplayError::Truncated { .. })));
+        
+        std::fs::remove_file(&path).unwrap();
+    }
+    
+    #[test]
+    fn test_partial_length_prefix_is_truncation() {
+        let path = capture_path("prefix");
+        let mut writer = TickWriter::create(&path).unwrap();
+        writer.write(&tick(0)).unwrap();
+        writer.write(&tick(1)).unwrap();
+        writer.finish().unwrap();
+        let one_record = 4 + MarketTick::WIRE_SIZE as u64;
+        
+        for tail in 1..=3 {
+            let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+            file.set_len(one_record + tail).unwrap();
+            
+            let mut replay = TickReplay::open(&path).unwrap();
+            assert_eq!(replay.by_ref().count(), 1);
+            assert!(
+                matches!(replay.error(), Some(ReplayError::Truncated { offset }) if *offset == one_record),
+                "tail of {} bytes: {:?}",
+                tail,
+                replay.error()
+            );
+        }
+        
+        // Ending exactly on a record boundary is a clean end
+        let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+        file.set_len(one_record).unwrap();
+        let mut replay = TickReplay::open(&path).unwrap();
+        assert_eq!(replay.by_ref().count(), 1);
+        assert!(replay.error().is_none());
+        
+        std::fs::remove_file(&path).unwrap();
+    }
+}
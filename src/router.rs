@@ -0,0 +1,190 @@
+// Smart order routing across venues
+// Allocates a parent order over per-venue top-of-book in best net price order
+// (price plus fee for buys, price minus fee for sells) and stamps each child's venue_id
+
+use std::collections::HashMap;
+
+use crate::breaker::VenueBreaker;
+use crate::fees::FeeSchedule;
+use crate::{Order, OrderIdGen};
+
+/// Top of book on one venue for the side we would take
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VenueQuote {
+    pub venue_id: u8,
+    pub price: f64,
+    pub available: u64,
+    pub fee_per_unit: f64,  // Price units per share/contract
+}
+
+impl VenueQuote {
+    /// Effective price after fees for a taker on `side` (0 = buy, 1 = sell)
+    #[inline(always)]
+    pub fn net_price(&self, side: u8) -> f64 {
+        if side == 0 {
+            self.price + self.fee_per_unit
+        } else {
+            self.price - self.fee_per_unit
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RouteResult {
+    /// `order_id` of the routed parent; children carry their own ids
+    pub parent_id: u64,
+    pub children: Vec<Order>,
+    /// Quantity that could not be placed with the liquidity offered
+    pub unfilled: u64,
+}
+
+#[derive(Default)]
+pub struct Router {
+    max_child_qty: HashMap<u8, u64>,
+    fees: Option<(FeeSchedule, bool)>,  // (schedule, children rest as maker)
+}
+
+impl Router {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Cap the size of any single child sent to `venue_id`
+    pub fn with_max_child_qty(mut self, venue_id: u8, max_qty: u64) -> Self {
+        self.max_child_qty.insert(venue_id, max_qty);
+        self
+    }
+    
+    /// Rank venues net of `fees`; `is_maker` selects maker rates (passive
+    /// children) instead of taker rates. Applied on top of `fee_per_unit`
+    pub fn with_fee_schedule(mut self, fees: FeeSchedule, is_maker: bool) -> Self {
+        self.fees = Some((fees, is_maker));
+        self
+    }
+    
+    /// Price used for ranking a quote for `side`
+    #[inline(always)]
+    pub fn effective_price(&self, quote: &VenueQuote, side: u8) -> f64 {
+        let price = quote.net_price(side);
+        match &self.fees {
+            Some((fees, is_maker)) => fees.net_price(quote.venue_id, price, side, *is_maker),
+            None => price,
+        }
+    }
+    
+    /// Split `parent.quantity` across venues, best net price first.
+    ///
+    /// Children copy the parent and get a fresh `order_id` from `ids`, the
+    /// venue's price, `venue_id` and their allocated quantity; at most one
+    /// child is produced per venue.
+    pub fn route(&self, parent: &Order, quotes: &[VenueQuote], ids: &OrderIdGen) -> RouteResult {
+        let mut ranked: Vec<&VenueQuote> = quotes.iter().filter(|q| q.available > 0).collect();
+        ranked.sort_by(|a, b| {
+            let (a, b) = (self.effective_price(a, parent.side), self.effective_price(b, parent.side));
+            if parent.side == 0 { a.total_cmp(&b) } else { b.total_cmp(&a) }
+        });
+        
+        let mut remaining = parent.quantity;
+        let mut children = Vec::new();
+        for quote in ranked {
+            if remaining == 0 {
+                break;
+            }
+            let cap = self.max_child_qty.get(&quote.venue_id).copied().unwrap_or(u64::MAX);
+            let quantity = remaining.min(quote.available).min(cap);
+            if quantity == 0 {
+                continue;
+            }
+            children.push(Order {
+                order_id: ids.next_id(),
+                venue_id: quote.venue_id,
+                price: quote.price,
+                quantity,
+                ..*parent
+            });
+            remaining -= quantity;
+        }
+        
+        RouteResult { parent_id: parent.order_id, children, unfilled: remaining }
+    }
+    
+    /// `route`, skipping venues whose breaker is open at `now_ns`
+    pub fn route_available(&self, parent: &Order, quotes: &[VenueQuote], breaker: &VenueBreaker, now_ns: i64, ids: &OrderIdGen) -> RouteResult {
+        let open: Vec<VenueQuote> = quotes
+            .iter()
+            .filter(|q| breaker.is_available_at(q.venue_id, now_ns))
+            .copied()
+            .collect();
+        self.route(parent, &open, ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn buy(quantity: u64) -> Order {
+        Order { order_id: 1, asset_id: 2, side: 0, quantity, ..Default::default() }
+    }
+    
+    const CHEAP: VenueQuote = VenueQuote { venue_id: 1, price: 100.00, available: 300, fee_per_unit: 0.001 };
+    const DEAR: VenueQuote = VenueQuote { venue_id: 2, price: 100.01, available: 1000, fee_per_unit: 0.0 };
+    
+    #[test]
+    fn test_cheap_venue_filled_first_then_spills() {
+        let result = Router::new().route(&buy(500), &[DEAR, CHEAP], &OrderIdGen::new(100));
+        
+        let split: Vec<(u8, u64, f64)> = result.children.iter().map(|c| (c.venue_id, c.quantity, c.price)).collect();
+        assert_eq!(split, vec![(1, 300, 100.00), (2, 200, 100.01)]);
+        assert_eq!(result.unfilled, 0);
+        assert!(result.children.iter().all(|c| c.asset_id == 2));
+        // Each venue's child is separately addressable; the parent id is kept aside
+        let ids: Vec<u64> = result.children.iter().map(|c| c.order_id).collect();
+        assert_eq!(ids, vec![100, 101]);
+        assert_eq!(result.parent_id, 1);
+    }
+    
+    #[test]
+    fn test_child_cap_and_unfilled_remainder() {
+        let router = Router::new().with_max_child_qty(1, 100);
+        let result = router.route(&buy(1500), &[CHEAP, DEAR], &OrderIdGen::new(100));
+        
+        let split: Vec<(u8, u64)> = result.children.iter().map(|c| (c.venue_id, c.quantity)).collect();
+        assert_eq!(split, vec![(1, 100), (2, 1000)]);
+        assert_eq!(result.unfilled, 400);
+        
+        // Sells rank by highest net price: fees now make the cheap venue worse
+        let sell = Order { side: 1, ..buy(50) };
+        let result = Router::new().route(&sell, &[CHEAP, DEAR], &OrderIdGen::new(100));
+        assert_eq!(result.children[0].venue_id, 2);
+    }
+    
+    #[test]
+    fn test_maker_rebate_beats_better_nominal_price() {
+        let plain = VenueQuote { venue_id: 1, price: 100.00, available: 100, fee_per_unit: 0.0 };
+        let rebate = VenueQuote { venue_id: 2, price: 100.002, available: 100, fee_per_unit: 0.0 };
+        
+        let nominal = Router::new().route(&buy(100), &[rebate, plain], &OrderIdGen::new(100));
+        assert_eq!(nominal.children[0].venue_id, 1);
+        
+        // -0.3 bps maker rebate on venue 2 outweighs its 0.002 worse price; venue 1 charges 0.1 bps
+        let fees = FeeSchedule::new().with_venue(1, 0.1, 0.3).with_venue(2, -0.3, 0.3);
+        let router = Router::new().with_fee_schedule(fees, true);
+        assert!(router.effective_price(&rebate, 0) < router.effective_price(&plain, 0));
+        let passive = router.route(&buy(100), &[plain, rebate], &OrderIdGen::new(100));
+        assert_eq!(passive.children[0].venue_id, 2);
+        assert_eq!(passive.children[0].price, 100.002);
+    }
+    
+    #[test]
+    fn test_tripped_venue_is_skipped() {
+        let mut breaker = VenueBreaker::default();
+        for _ in 0..5 {
+            breaker.record_failure_at(1, 0);
+        }
+        
+        let result = Router::new().route_available(&buy(500), &[CHEAP, DEAR], &breaker, 10, &OrderIdGen::new(100));
+        let split: Vec<(u8, u64)> = result.children.iter().map(|c| (c.venue_id, c.quantity)).collect();
+        assert_eq!(split, vec![(2, 500)]);
+    }
+}
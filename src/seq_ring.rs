@@ -0,0 +1,112 @@
+// Sequence-numbered ring shared by the MPMC and MPSC queues (bounded queue
+// after Vyukov)
+//
+// Every slot carries a sequence number that doubles as its published flag:
+// slot `i` starts at `i`; a producer may claim ring position `p` when the
+// slot reads `p`, and marks it `p + 1` once the data is written; a consumer
+// frees it for the next lap by storing `p + CAPACITY`. Positions are claimed
+// with a CAS on `tail` rather than a blind `fetch_add`, so a full ring is
+// reported to the producer instead of over-reserving. How consumers agree on
+// who takes a position is left to the queue built on top.
+
+use std::cmp;
+use std::sync::atomic::Ordering;
+
+use crate::sync::{AtomicU64, Slot};
+
+struct Cell<T> {
+    seq: AtomicU64,
+    data: Slot<T>,
+}
+
+pub(crate) struct SeqRing<T, const CAPACITY: usize> {
+    cells: Box<[Cell<T>]>,
+    tail: AtomicU64,  // Next position to claim
+}
+
+unsafe impl<T: Send, const CAPACITY: usize> Send for SeqRing<T, CAPACITY> {}
+unsafe impl<T: Send, const CAPACITY: usize> Sync for SeqRing<T, CAPACITY> {}
+
+impl<T, const CAPACITY: usize> SeqRing<T, CAPACITY> {
+    /// Ring with all `CAPACITY` slots usable
+    pub(crate) fn new() -> Self {
+        assert!(CAPACITY.is_power_of_two(), "Capacity must be power of 2");
+        // With one slot `p + 1` (published) and `p + CAPACITY` (free for the
+        // next lap) are the same sequence, so a producer could overwrite an
+        // item nobody has popped yet
+        assert!(CAPACITY >= 2, "Capacity must be at least 2");
+        Self {
+            cells: (0..CAPACITY as u64)
+                .map(|i| Cell { seq: AtomicU64::new(i), data: Slot::uninit() })
+                .collect(),
+            tail: AtomicU64::new(0),
+        }
+    }
+    
+    #[inline(always)]
+    fn cell(&self, pos: u64) -> &Cell<T> {
+        &self.cells[(pos as usize) & (CAPACITY - 1)]
+    }
+    
+    /// Any thread: claim the next position and publish `item` there.
+    /// Returns the position, or None if the ring is full
+    #[inline(always)]
+    pub(crate) fn push(&self, item: T) -> Option<u64> {
+        let mut pos = self.tail.load(Ordering::Relaxed);
+        
+        loop {
+            let cell = self.cell(pos);
+            let seq = cell.seq.load(Ordering::Acquire);
+            
+            if seq == pos {
+                // Free for this lap: try to claim it
+                match self.tail.compare_exchange_weak(pos, pos + 1, Ordering::Relaxed, Ordering::Relaxed) {
+                    Ok(_) => {
+                        // Claimed exclusively; the Acquire on seq ordered the last lap's read
+                        unsafe { cell.data.write(item) };
+                        cell.seq.store(pos + 1, Ordering::Release);
+                        return Some(pos);
+                    }
+                    Err(current) => pos = current,
+                }
+            } else if seq < pos {
+                // Still holds the previous lap's item: full
+                return None;
+            } else {
+                // Another producer claimed `pos` first
+                pos = self.tail.load(Ordering::Relaxed);
+            }
+        }
+    }
+    
+    /// Where `pos` stands for a consumer: `Equal` once its item is
+    /// published, `Less` while it is still unwritten, `Greater` once some
+    /// consumer has taken it
+    #[inline(always)]
+    pub(crate) fn state(&self, pos: u64) -> cmp::Ordering {
+        self.cell(pos).seq.load(Ordering::Acquire).cmp(&(pos + 1))
+    }
+    
+    /// Move the item at `pos` out and free its slot for the next lap.
+    /// Safety: `pos` is published and the caller alone has claimed it
+    #[inline(always)]
+    pub(crate) unsafe fn take(&self, pos: u64) -> T {
+        let cell = self.cell(pos);
+        let item = cell.data.read();
+        cell.seq.store(pos + CAPACITY as u64, Ordering::Release);
+        item
+    }
+    
+    /// Borrow the item at `pos` in place.
+    /// Safety: `pos` is published and isn't taken while the borrow lives
+    #[inline(always)]
+    pub(crate) unsafe fn get(&self, pos: u64) -> &T {
+        self.cell(pos).data.get()
+    }
+    
+    /// Positions claimed so far, published or not
+    #[inline(always)]
+    pub(crate) fn tail(&self) -> u64 {
+        self.tail.load(Ordering::Acquire)
+    }
+}
@@ -0,0 +1,231 @@
+// Single-slot SeqLock
+// For pollers that only want the latest value (e.g. the newest tick for one
+// asset) and are happy to miss intermediate updates. The writer never waits
+// for readers; readers retry until they copy a snapshot that no store
+// overlapped, so a large struct is never observed torn.
+
+use std::cell::UnsafeCell;
+use std::collections::HashMap;
+use std::sync::atomic::{fence, AtomicU64, Ordering};
+
+use crate::MarketTick;
+
+pub struct SeqLockCell<T> {
+    seq: AtomicU64,  // Odd while a store is in progress
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: Copy + Send> Send for SeqLockCell<T> {}
+unsafe impl<T: Copy + Send> Sync for SeqLockCell<T> {}
+
+impl<T: Copy> SeqLockCell<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            seq: AtomicU64::new(0),
+            data: UnsafeCell::new(value),
+        }
+    }
+    
+    /// Publish a new value. Meant for a single writer, which never spins;
+    /// concurrent writers are serialized by the odd sequence marker
+    #[inline(always)]
+    pub fn store(&self, value: &T) {
+        let mut seq = self.seq.load(Ordering::Relaxed);
+        loop {
+            if seq & 1 == 0 {
+                match self.seq.compare_exchange_weak(seq, seq + 1, Ordering::Acquire, Ordering::Relaxed) {
+                    Ok(_) => break,
+                    Err(current) => seq = current,
+                }
+            } else {
+                std::hint::spin_loop();
+                seq = self.seq.load(Ordering::Relaxed);
+            }
+        }
+        // Odd marker must be visible before any byte of the new data
+        fence(Ordering::Release);
+        unsafe { std::ptr::write_volatile(self.data.get(), *value) };
+        self.seq.store(seq + 2, Ordering::Release);
+    }
+    
+    /// Latest consistent value; retries while a store overlaps the copy
+    #[inline(always)]
+    pub fn load(&self) -> T {
+        loop {
+            let before = self.seq.load(Ordering::Acquire);
+            if before & 1 == 1 {
+                std::hint::spin_loop();
+                continue;
+            }
+            let copy = unsafe { std::ptr::read_volatile(self.data.get()) };
+            fence(Ordering::Acquire);
+            if self.seq.load(Ordering::Relaxed) == before {
+                return copy;
+            }
+        }
+    }
+    
+    /// Number of completed stores
+    #[inline(always)]
+    pub fn version(&self) -> u64 {
+        self.seq.load(Ordering::Acquire) / 2
+    }
+}
+
+impl<T: Copy + Default> Default for SeqLockCell<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+// Latest Tick per Asset
+// One SeqLockCell per asset, fixed at construction so publishing never
+// allocates or locks; "latest book state" readers poll it instead of
+// draining a queue of ticks they would skip anyway.
+
+pub struct LatestTicks {
+    index: HashMap<u32, usize>,
+    cells: Box<[SeqLockCell<MarketTick>]>,
+}
+
+impl LatestTicks {
+    /// Table for exactly these assets; ticks for any other asset are refused
+    pub fn new(asset_ids: &[u32]) -> Self {
+        let mut index = HashMap::with_capacity(asset_ids.len());
+        for &asset_id in asset_ids {
+            let next = index.len();
+            index.entry(asset_id).or_insert(next);
+        }
+        let cells = (0..index.len()).map(|_| SeqLockCell::default()).collect();
+        Self { index, cells }
+    }
+    
+    /// Writer: replace the asset's snapshot; false if the asset isn't tracked.
+    /// One writer per asset (different assets may be published from different threads)
+    #[inline(always)]
+    pub fn publish(&self, tick: &MarketTick) -> bool {
+        match self.index.get(&tick.asset_id) {
+            Some(&i) => {
+                self.cells[i].store(tick);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Torn-read-free copy of the newest tick; `None` if untracked or never published
+    #[inline(always)]
+    pub fn latest(&self, asset_id: u32) -> Option<MarketTick> {
+        let cell = &self.cells[*self.index.get(&asset_id)?];
+        if cell.version() == 0 {
+            return None;
+        }
+        Some(cell.load())
+    }
+    
+    /// Ticks published for the asset so far; lets a poller skip unchanged assets
+    #[inline(always)]
+    pub fn version(&self, asset_id: u32) -> Option<u64> {
+        Some(self.cells[*self.index.get(&asset_id)?].version())
+    }
+    
+    pub fn assets(&self) -> impl Iterator<Item = u32> + '_ {
+        self.index.keys().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicBool;
+    
+    #[test]
+    fn test_seqlock_load_never_torn() {
+        // Initial value satisfies the same invariants as every store
+        let cell = SeqLockCell::new(MarketTick { ask_price: 1.0, ..Default::default() });
+        let done = AtomicBool::new(false);
+        
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                for i in 1..=200_000i64 {
+                    let mut tick = MarketTick { timestamp_ns: i, bid_price: i as f64, ask_price: i as f64 + 1.0, ..Default::default() };
+                    tick.bid_sizes = [i as u64; 10];
+                    tick.trade_volume = i as u64;
+                    cell.store(&tick);
+                }
+                done.store(true, Ordering::Release);
+            });
+            
+            s.spawn(|| {
+                let mut last = 0;
+                let mut loads = 0u64;
+                while !done.load(Ordering::Acquire) || loads == 0 {
+                    let tick = cell.load();
+                    // Every field the writer derives from `i` must agree
+                    assert_eq!(tick.bid_price, tick.timestamp_ns as f64);
+                    assert_eq!(tick.ask_price, tick.bid_price + 1.0);
+                    assert_eq!(tick.trade_volume, tick.timestamp_ns as u64);
+                    assert!(tick.bid_sizes.iter().all(|&size| size == tick.trade_volume));
+                    assert!(tick.timestamp_ns >= last);
+                    last = tick.timestamp_ns;
+                    loads += 1;
+                }
+            });
+        });
+        
+        assert_eq!(cell.version(), 200_000);
+        assert_eq!(cell.load().timestamp_ns, 200_000);
+    }
+    
+    #[test]
+    fn test_latest_ticks_per_asset() {
+        let table = LatestTicks::new(&[7, 9, 7]);
+        assert_eq!(table.assets().count(), 2);
+        assert!(table.latest(7).is_none());
+        assert_eq!(table.version(7), Some(0));
+        
+        for ts in 1..=3 {
+            assert!(table.publish(&MarketTick { asset_id: 7, timestamp_ns: ts, ..Default::default() }));
+        }
+        assert!(table.publish(&MarketTick { asset_id: 9, timestamp_ns: 50, ..Default::default() }));
+        assert!(!table.publish(&MarketTick { asset_id: 11, ..Default::default() }));
+        
+        // Intermediate ticks are gone; only the newest per asset is kept
+        assert_eq!(table.latest(7).map(|t| t.timestamp_ns), Some(3));
+        assert_eq!(table.latest(9).map(|t| t.timestamp_ns), Some(50));
+        assert_eq!(table.version(7), Some(3));
+        assert!(table.latest(11).is_none());
+        assert_eq!(table.version(11), None);
+    }
+    
+    #[test]
+    fn test_latest_ticks_concurrent_reader() {
+        let table = LatestTicks::new(&[1, 2]);
+        let done = AtomicBool::new(false);
+        
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                for i in 1..=20_000i64 {
+                    let asset_id = 1 + (i % 2) as u32;
+                    table.publish(&MarketTick { asset_id, timestamp_ns: i, bid_price: i as f64, ask_price: i as f64 + 0.5, ..Default::default() });
+                }
+                done.store(true, Ordering::Release);
+            });
+            
+            while !done.load(Ordering::Acquire) {
+                for asset_id in [1, 2] {
+                    if let Some(tick) = table.latest(asset_id) {
+                        assert_eq!(tick.asset_id, asset_id);
+                        assert_eq!(tick.bid_price, tick.timestamp_ns as f64);
+                        assert_eq!(tick.ask_price, tick.bid_price + 0.5);
+                    }
+                }
+                std::thread::yield_now();
+            }
+        });
+        // Even timestamps went to asset 1, odd ones to asset 2
+        assert_eq!(table.latest(1).map(|t| t.timestamp_ns), Some(20_000));
+        assert_eq!(table.latest(2).map(|t| t.timestamp_ns), Some(19_999));
+        assert_eq!(table.version(2), Some(10_000));
+    }
+}
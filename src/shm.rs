@@ -0,0 +1,1325 @@
+// Native POSIX shared memory: the MarketTick ring and managed segments
+// Same SPSC discipline as LockFreeSPSC, but the indices and slots live in a
+// shm_open/mmap segment so producer and consumer can be separate processes.
+// The header mirrors hft::shm::SharedMemoryHeader in include/shared_memory.hpp.
+
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::fs::File;
+use std::mem::ManuallyDrop;
+use std::os::fd::{AsRawFd, FromRawFd, IntoRawFd};
+use std::os::unix::fs::{MetadataExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::clock::{self, Clock};
+use crate::{HiResTimer, MarketTick, ShmError};
+
+mod sys {
+    use std::ffi::{c_char, c_int, c_void};
+    
+    pub const O_RDWR: c_int = 0o2;
+    pub const O_CREAT: c_int = 0o100;
+    pub const O_EXCL: c_int = 0o200;
+    pub const PROT_READ: c_int = 0x1;
+    pub const PROT_WRITE: c_int = 0x2;
+    pub const MAP_SHARED: c_int = 0x01;
+    pub const SEEK_END: c_int = 2;
+    pub const EEXIST: i32 = 17;
+    pub const ESRCH: i32 = 3;
+    pub const LOCK_EX: c_int = 2;
+    pub const LOCK_NB: c_int = 4;
+    #[cfg(test)]
+    pub const LOCK_UN: c_int = 8;
+    pub const MADV_HUGEPAGE: c_int = 14;
+    pub const MPOL_BIND: c_int = 2;
+    pub const MPOL_MF_MOVE: u32 = 1 << 1;
+    #[cfg(target_arch = "x86_64")]
+    pub const SYS_MBIND: i64 = 237;
+    #[cfg(target_arch = "aarch64")]
+    pub const SYS_MBIND: i64 = 235;
+    
+    pub const MAP_FAILED: *mut c_void = !0usize as *mut c_void;
+    
+    extern "C" {
+        pub fn shm_open(name: *const c_char, oflag: c_int, mode: u32) -> c_int;
+        pub fn shm_unlink(name: *const c_char) -> c_int;
+        pub fn ftruncate(fd: c_int, length: i64) -> c_int;
+        pub fn lseek(fd: c_int, offset: i64, whence: c_int) -> i64;
+        pub fn close(fd: c_int) -> c_int;
+        pub fn mmap(addr: *mut c_void, len: usize, prot: c_int, flags: c_int, fd: c_int, offset: i64) -> *mut c_void;
+        pub fn munmap(addr: *mut c_void, len: usize) -> c_int;
+        pub fn dup(fd: c_int) -> c_int;
+        pub fn getpid() -> c_int;
+        pub fn kill(pid: c_int, sig: c_int) -> c_int;
+        pub fn flock(fd: c_int, operation: c_int) -> c_int;
+        pub fn madvise(addr: *mut c_void, len: usize, advice: c_int) -> c_int;
+        #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+        pub fn syscall(num: i64, ...) -> i64;
+    }
+}
+
+#[inline(always)]
+fn last_errno() -> i32 {
+    std::io::Error::last_os_error().raw_os_error().unwrap_or(0)
+}
+
+/// Map `len` bytes of `fd` read-write and shared; closes `fd` on failure
+fn map_shared(fd: c_int, len: usize) -> Result<NonNull<u8>, ShmError> {
+    let addr = unsafe {
+        sys::mmap(std::ptr::null_mut(), len, sys::PROT_READ | sys::PROT_WRITE, sys::MAP_SHARED, fd, 0)
+    };
+    if addr == sys::MAP_FAILED {
+        let errno = last_errno();
+        unsafe { sys::close(fd) };
+        return Err(ShmError::Os(errno));
+    }
+    NonNull::new(addr as *mut u8).ok_or(ShmError::NotMapped)
+}
+
+/// Where `shm_open` keeps `name` on Linux
+fn shm_path(name: &CStr) -> PathBuf {
+    Path::new("/dev/shm").join(name.to_str().unwrap_or_default().trim_start_matches('/'))
+}
+
+/// Whether `path` still names the file open on `fd`; false once it has been
+/// unlinked or replaced by someone else's segment
+fn names_fd(path: &Path, fd: c_int) -> bool {
+    let file = ManuallyDrop::new(unsafe { File::from_raw_fd(fd) });
+    match (file.metadata(), std::fs::metadata(path)) {
+        (Ok(ours), Ok(named)) => ours.dev() == named.dev() && ours.ino() == named.ino(),
+        _ => false,
+    }
+}
+
+/// Take the creator's lock on a ring's file. Held until the fd closes, so it
+/// is released exactly when the creating process exits or drops the ring
+#[inline(always)]
+fn lock_owner(fd: c_int) -> bool {
+    unsafe { sys::flock(fd, sys::LOCK_EX | sys::LOCK_NB) == 0 }
+}
+
+// Segment header (one per mapping, slots follow immediately)
+
+#[repr(C, align(64))]
+struct ShmHeader {
+    write_seq: AtomicU64,
+    read_seq: AtomicU64,
+    is_initialized: AtomicBool,
+    capacity: u64,
+    element_size: u64,
+    name: [u8; 64],
+    // Rust-only: sit in the C++ header's tail padding (offset 104), zero when created from C++
+    flags: u64,
+    magic: u32,
+    version: u32,
+    creator_beat: AtomicU32,
+    attacher_beat: AtomicU32,
+}
+
+const RING_MAGIC: u32 = u32::from_le_bytes(*b"HFTR");
+const RING_VERSION: u32 = 1;
+
+const FLAG_CHECKSUM: u64 = 0x1;
+
+// Per-record CRC32 is stored in the slot's tail padding, right after the last field
+const CRC_OFFSET: usize = std::mem::offset_of!(MarketTick, ask_sizes) + std::mem::size_of::<[u64; 10]>();
+const _: () = assert!(CRC_OFFSET + 4 <= std::mem::size_of::<MarketTick>());
+
+/// Options fixed when a segment is created
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShmOptions {
+    /// CRC32 every record on write and verify on read (costs ~1us per tick)
+    pub checksum: bool,
+    /// Page size to back the ring with; see `ShmRing::huge_pages` for what was granted
+    pub huge_pages: HugePages,
+    /// Bind the ring's memory to this NUMA node; see `ShmRing::numa_node`
+    pub numa_node: Option<u32>,
+    /// Permission bits for the segment (default `DEFAULT_SHM_MODE`); anyone
+    /// who can open it can write ticks into it
+    pub mode: Option<u32>,
+}
+
+/// Huge page backing for a ring. Each falls back to normal pages if the
+/// system can't provide it, so asking is always safe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HugePages {
+    #[default]
+    Off,
+    /// `madvise(MADV_HUGEPAGE)` on the shm mapping; takes effect when
+    /// `/sys/kernel/mm/transparent_hugepage/shmem_enabled` is `advise` or `always`
+    Transparent,
+    /// File on the 2MB hugetlbfs mount (`HUGETLBFS_2M_DIR`); needs reserved pages
+    Huge2M,
+    /// File on the 1GB hugetlbfs mount (`HUGETLBFS_1G_DIR`)
+    Huge1G,
+}
+
+pub const HUGETLBFS_2M_DIR: &str = "/dev/hugepages";
+pub const HUGETLBFS_1G_DIR: &str = "/dev/hugepages1G";
+
+impl HugePages {
+    fn hugetlbfs(self) -> Option<(&'static str, usize)> {
+        match self {
+            HugePages::Huge2M => Some((HUGETLBFS_2M_DIR, 2 << 20)),
+            HugePages::Huge1G => Some((HUGETLBFS_1G_DIR, 1 << 30)),
+            HugePages::Off | HugePages::Transparent => None,
+        }
+    }
+}
+
+/// Open a fresh file for a ring on hugetlbfs, sized to `len`, with the
+/// creator's lock held. A file already at `path` is removed only if
+/// `reclaim_ring` agrees its creator is gone
+fn create_hugetlb(path: &Path, len: usize, mode: u32) -> Result<c_int, ShmError> {
+    let os_err = |err: std::io::Error| ShmError::Os(err.raw_os_error().unwrap_or(0));
+    let create = || std::fs::OpenOptions::new().read(true).write(true).create_new(true).mode(mode).open(path);
+    let file = match create() {
+        Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => {
+            let existing = std::fs::OpenOptions::new().read(true).write(true).open(path).map_err(os_err)?;
+            reclaim_ring(existing.into_raw_fd(), path, || {
+                let _ = std::fs::remove_file(path);
+            })?;
+            create()
+        }
+        other => other,
+    }
+    .map_err(os_err)?;
+    if !lock_owner(file.as_raw_fd()) {
+        let _ = std::fs::remove_file(path);
+        return Err(ShmError::InUse);
+    }
+    file.set_len(len as u64).map_err(|err| {
+        let _ = std::fs::remove_file(path);
+        ShmError::Os(err.raw_os_error().unwrap_or(0))
+    })?;
+    Ok(file.into_raw_fd())
+}
+
+/// `mbind` the range to `node` before it's touched; false if the kernel refused
+fn bind_to_node(base: NonNull<u8>, len: usize, node: u32) -> bool {
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    if node < 64 {
+        let mask: u64 = 1 << node;
+        let rc = unsafe {
+            sys::syscall(sys::SYS_MBIND, base.as_ptr(), len, sys::MPOL_BIND, &mask as *const u64, 64u64, sys::MPOL_MF_MOVE)
+        };
+        return rc == 0;
+    }
+    let _ = (base, len, node);
+    false
+}
+
+// CRC-32 (IEEE 802.3, reflected), table built at compile time
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |crc, &b| CRC_TABLE[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8))
+}
+
+/// Checksum over the portable encoding so compiler-inserted padding never matters
+#[inline(always)]
+fn tick_crc(tick: &MarketTick) -> u32 {
+    crc32(&tick.to_bytes())
+}
+
+/// Make way for a new ring where an old one (open on `fd`, which this closes)
+/// still has the name. Only a ring created by this crate whose creator has
+/// gone is removed, by `remove`; `InUse` while the creator still holds its
+/// lock, `Incompatible` for anything else (C++ rings and foreign segments)
+fn reclaim_ring(fd: c_int, path: &Path, remove: impl FnOnce()) -> Result<(), ShmError> {
+    let result = check_reclaimable(fd, path);
+    if result.is_ok() {
+        remove();  // Still holding the dead creator's lock, so no one else reclaims it too
+    }
+    unsafe { sys::close(fd) };
+    result
+}
+
+fn check_reclaimable(fd: c_int, path: &Path) -> Result<(), ShmError> {
+    let header_len = std::mem::size_of::<ShmHeader>();
+    if unsafe { sys::lseek(fd, 0, sys::SEEK_END) } < header_len as i64 {
+        return Err(ShmError::Incompatible);
+    }
+    let view = unsafe { sys::dup(fd) };
+    if view == -1 {
+        return Err(ShmError::Os(last_errno()));
+    }
+    let base = map_shared(view, header_len)?;
+    let magic = unsafe {
+        let magic = std::ptr::read_volatile(std::ptr::addr_of!((*(base.as_ptr() as *const ShmHeader)).magic));
+        sys::munmap(base.as_ptr() as *mut c_void, header_len);
+        sys::close(view);
+        magic
+    };
+    // Magic first: a creator locks before it writes it, so an unlocked ring
+    // with our magic really has been abandoned
+    if magic != RING_MAGIC {
+        return Err(ShmError::Incompatible);
+    }
+    if !lock_owner(fd) || !names_fd(path, fd) {
+        return Err(ShmError::InUse);
+    }
+    Ok(())
+}
+
+const GUARD_BYTES: usize = 4096;  // Matches the C++ guard page
+
+#[inline(always)]
+fn segment_len(capacity: usize) -> usize {
+    std::mem::size_of::<ShmHeader>() + capacity * std::mem::size_of::<MarketTick>() + GUARD_BYTES
+}
+
+// Shared Memory Ring
+
+type HugetlbBacking = (c_int, NonNull<u8>, usize, PathBuf);  // (fd, base, len, file)
+
+pub struct ShmRing {
+    name: CString,
+    fd: c_int,
+    base: NonNull<u8>,
+    len: usize,
+    capacity: u64,
+    owner: bool,  // Creator unlinks the segment on drop
+    checksum: bool,
+    corruptions: AtomicU64,
+    hugetlb_path: Option<PathBuf>,  // Set when backed by a hugetlbfs file rather than shm_open
+    huge_pages: HugePages,
+    numa_node: Option<u32>,
+    peer_beat_seen: AtomicU32,  // Peer's heartbeat word at our last look
+    peer_beat_changed_ns: AtomicI64,
+}
+
+impl ShmRing {
+    /// Create a segment holding `capacity` ticks. A ring left under `name` by
+    /// a creator that has since exited is replaced; a live one gives `InUse`
+    /// and anything that isn't one of our rings gives `Incompatible`
+    pub fn create(name: &str, capacity: usize) -> Result<Self, ShmError> {
+        Self::create_with(name, capacity, ShmOptions::default())
+    }
+    
+    /// Create with explicit options; attaching processes pick them up from the header
+    pub fn create_with(name: &str, capacity: usize, options: ShmOptions) -> Result<Self, ShmError> {
+        if !capacity.is_power_of_two() {
+            return Err(ShmError::Incompatible);
+        }
+        let cname = CString::new(name).map_err(|_| ShmError::BadName)?;
+        
+        let (fd, base, len, hugetlb_path, mut huge_pages) = match Self::create_hugetlb_backing(name, capacity, options)? {
+            Some((fd, base, len, path)) => (fd, base, len, Some(path), options.huge_pages),
+            None => {
+                let mode = options.mode.unwrap_or(DEFAULT_SHM_MODE);
+                let (fd, base, len) = Self::create_shm_backing(&cname, capacity, mode)?;
+                (fd, base, len, None, HugePages::Off)
+            }
+        };
+        if options.huge_pages == HugePages::Transparent
+            && unsafe { sys::madvise(base.as_ptr() as *mut c_void, len, sys::MADV_HUGEPAGE) } == 0
+        {
+            huge_pages = HugePages::Transparent;
+        }
+        let numa_node = options.numa_node.filter(|&node| bind_to_node(base, len, node));
+        
+        let ring = Self {
+            name: cname,
+            fd,
+            base,
+            len,
+            capacity: capacity as u64,
+            owner: true,
+            checksum: options.checksum,
+            corruptions: AtomicU64::new(0),
+            hugetlb_path,
+            huge_pages,
+            numa_node,
+            peer_beat_seen: AtomicU32::new(0),
+            peer_beat_changed_ns: AtomicI64::new(HiResTimer::now_ns()),
+        };
+        
+        // ftruncate zero-fills, so the atomics already read as 0/false
+        unsafe {
+            let header = ring.base.as_ptr() as *mut ShmHeader;
+            (*header).capacity = capacity as u64;
+            (*header).element_size = std::mem::size_of::<MarketTick>() as u64;
+            (*header).flags = if options.checksum { FLAG_CHECKSUM } else { 0 };
+            (*header).magic = RING_MAGIC;
+            (*header).version = RING_VERSION;
+            let bytes = name.as_bytes();
+            let n = bytes.len().min(63);
+            (&mut (*header).name)[..n].copy_from_slice(&bytes[..n]);
+        }
+        ring.header().is_initialized.store(true, Ordering::Release);
+        Ok(ring)
+    }
+    
+    fn create_shm_backing(cname: &CString, capacity: usize, mode: u32) -> Result<(c_int, NonNull<u8>, usize), ShmError> {
+        let flags = sys::O_CREAT | sys::O_RDWR | sys::O_EXCL;
+        let mut fd = unsafe { sys::shm_open(cname.as_ptr(), flags, mode) };
+        if fd == -1 && last_errno() == sys::EEXIST {
+            let existing = unsafe { sys::shm_open(cname.as_ptr(), sys::O_RDWR, 0) };
+            if existing == -1 {
+                return Err(ShmError::Os(last_errno()));
+            }
+            reclaim_ring(existing, &shm_path(cname), || unsafe {
+                sys::shm_unlink(cname.as_ptr());
+            })?;
+            fd = unsafe { sys::shm_open(cname.as_ptr(), flags, mode) };
+        }
+        if fd == -1 {
+            return Err(ShmError::Os(last_errno()));
+        }
+        if !lock_owner(fd) {
+            unsafe { sys::close(fd) };
+            return Err(ShmError::InUse);
+        }
+        
+        let len = segment_len(capacity);
+        if unsafe { sys::ftruncate(fd, len as i64) } == -1 {
+            let errno = last_errno();
+            unsafe {
+                sys::close(fd);
+                sys::shm_unlink(cname.as_ptr());
+            }
+            return Err(ShmError::Os(errno));
+        }
+        
+        match map_shared(fd, len) {
+            Ok(base) => Ok((fd, base, len)),
+            Err(err) => {
+                unsafe { sys::shm_unlink(cname.as_ptr()) };
+                Err(err)
+            }
+        }
+    }
+    
+    /// `Ok(None)` (after cleaning up) if hugetlbfs isn't mounted or has no
+    /// pages to give; `InUse`/`Incompatible` if a ring we may not replace is there
+    fn create_hugetlb_backing(name: &str, capacity: usize, options: ShmOptions) -> Result<Option<HugetlbBacking>, ShmError> {
+        let Some((dir, page)) = options.huge_pages.hugetlbfs() else {
+            return Ok(None);
+        };
+        let path = Path::new(dir).join(name.trim_start_matches('/'));
+        let len = segment_len(capacity).next_multiple_of(page);
+        let fd = match create_hugetlb(&path, len, options.mode.unwrap_or(DEFAULT_SHM_MODE)) {
+            Ok(fd) => fd,
+            Err(err @ (ShmError::InUse | ShmError::Incompatible)) => return Err(err),
+            Err(_) => return Ok(None),
+        };
+        match map_shared(fd, len) {
+            Ok(base) => Ok(Some((fd, base, len, path))),
+            Err(_) => {
+                let _ = std::fs::remove_file(&path);  // Freshly created by us
+                Ok(None)
+            }
+        }
+    }
+    
+    /// Attach to a segment created by another process, whether it lives in
+    /// POSIX shm or on one of the hugetlbfs mounts
+    pub fn attach(name: &str) -> Result<Self, ShmError> {
+        let cname = CString::new(name).map_err(|_| ShmError::BadName)?;
+        
+        let mut huge_pages = HugePages::Off;
+        let mut fd = unsafe { sys::shm_open(cname.as_ptr(), sys::O_RDWR, 0o666) };
+        if fd == -1 {
+            let errno = last_errno();
+            let hugetlb = [HugePages::Huge2M, HugePages::Huge1G].into_iter().find_map(|kind| {
+                let (dir, _) = kind.hugetlbfs()?;
+                let file = std::fs::OpenOptions::new().read(true).write(true).open(Path::new(dir).join(name.trim_start_matches('/')));
+                file.ok().map(|file| (kind, file.into_raw_fd()))
+            });
+            match hugetlb {
+                Some((kind, hugetlb_fd)) => (huge_pages, fd) = (kind, hugetlb_fd),
+                None => return Err(ShmError::Os(errno)),
+            }
+        }
+        
+        let len = unsafe { sys::lseek(fd, 0, sys::SEEK_END) };
+        if len < std::mem::size_of::<ShmHeader>() as i64 {
+            unsafe { sys::close(fd) };
+            return Err(ShmError::NotMapped);
+        }
+        let len = len as usize;
+        let base = map_shared(fd, len)?;
+        
+        let mut ring = Self {
+            name: cname,
+            fd,
+            base,
+            len,
+            capacity: 0,
+            owner: false,
+            checksum: false,
+            corruptions: AtomicU64::new(0),
+            hugetlb_path: None,
+            huge_pages,
+            numa_node: None,
+            peer_beat_seen: AtomicU32::new(0),
+            peer_beat_changed_ns: AtomicI64::new(HiResTimer::now_ns()),
+        };
+        
+        let header = ring.header();
+        if !header.is_initialized.load(Ordering::Acquire) {
+            return Err(ShmError::NotMapped);
+        }
+        let capacity = header.capacity;
+        let checksum = header.flags & FLAG_CHECKSUM != 0;
+        // A zero magic is a segment created by the C++ side, which predates it
+        let foreign = header.magic != 0 && (header.magic != RING_MAGIC || header.version != RING_VERSION);
+        if foreign
+            || header.element_size != std::mem::size_of::<MarketTick>() as u64
+            || !capacity.is_power_of_two()
+            || segment_len(capacity as usize) > len
+        {
+            return Err(ShmError::Incompatible);
+        }
+        let peer_beat = header.creator_beat.load(Ordering::Acquire);
+        ring.capacity = capacity;
+        ring.checksum = checksum;
+        ring.peer_beat_seen = AtomicU32::new(peer_beat);
+        Ok(ring)
+    }
+    
+    #[inline(always)]
+    fn header(&self) -> &ShmHeader {
+        unsafe { &*(self.base.as_ptr() as *const ShmHeader) }
+    }
+    
+    #[inline(always)]
+    fn slot_crc(&self, seq: u64) -> *mut u32 {
+        unsafe { (self.slot(seq) as *mut u8).add(CRC_OFFSET) as *mut u32 }
+    }
+    
+    #[inline(always)]
+    fn slot(&self, seq: u64) -> *mut MarketTick {
+        let idx = (seq & (self.capacity - 1)) as usize;
+        unsafe {
+            let slots = self.base.as_ptr().add(std::mem::size_of::<ShmHeader>()) as *mut MarketTick;
+            slots.add(idx)
+        }
+    }
+    
+    /// Producer: write one tick
+    #[inline(always)]
+    pub fn write_tick(&self, tick: &MarketTick) -> Result<(), ShmError> {
+        let header = self.header();
+        let write = header.write_seq.load(Ordering::Relaxed);
+        let read = header.read_seq.load(Ordering::Acquire);
+        
+        if write.wrapping_sub(read) >= self.capacity {
+            return Err(ShmError::Full);
+        }
+        
+        unsafe {
+            self.slot(write).write(*tick);
+            if self.checksum {
+                self.slot_crc(write).write(tick_crc(tick));
+            }
+        }
+        header.write_seq.store(write.wrapping_add(1), Ordering::Release);
+        Ok(())
+    }
+    
+    /// Consumer: borrow the oldest tick in place instead of copying it out.
+    /// The slot stays reserved until the guard drops, which consumes it.
+    /// Checksums are not checked here; call `ShmTickRef::verify` if enabled
+    #[inline(always)]
+    pub fn read_tick_ref(&mut self) -> Option<ShmTickRef<'_>> {
+        let header = self.header();
+        let read = header.read_seq.load(Ordering::Relaxed);
+        let write = header.write_seq.load(Ordering::Acquire);
+        if read == write {
+            return None;
+        }
+        Some(ShmTickRef { ring: self, seq: read })
+    }
+    
+    /// Consumer: read one tick; `Ok(None)` when empty.
+    /// With checksums on, a damaged record is consumed and reported as `Corrupt`
+    #[inline(always)]
+    pub fn read_tick(&self) -> Result<Option<MarketTick>, ShmError> {
+        let header = self.header();
+        let read = header.read_seq.load(Ordering::Relaxed);
+        let write = header.write_seq.load(Ordering::Acquire);
+        
+        if read == write {
+            return Ok(None);
+        }
+        
+        let tick = unsafe { self.slot(read).read() };
+        let intact = !self.checksum || unsafe { self.slot_crc(read).read() } == tick_crc(&tick);
+        header.read_seq.store(read.wrapping_add(1), Ordering::Release);
+        
+        if !intact {
+            self.corruptions.fetch_add(1, Ordering::Relaxed);
+            return Err(ShmError::Corrupt);
+        }
+        Ok(Some(tick))
+    }
+    
+    /// Producer: write as many of `ticks` as fit, publishing them with one
+    /// index store; returns how many were accepted (from the front)
+    pub fn write_ticks(&self, ticks: &[MarketTick]) -> usize {
+        let header = self.header();
+        let write = header.write_seq.load(Ordering::Relaxed);
+        let read = header.read_seq.load(Ordering::Acquire);
+        let n = ticks.len().min((self.capacity - write.wrapping_sub(read)) as usize);
+        
+        for (i, tick) in ticks[..n].iter().enumerate() {
+            let seq = write.wrapping_add(i as u64);
+            unsafe {
+                self.slot(seq).write(*tick);
+                if self.checksum {
+                    self.slot_crc(seq).write(tick_crc(tick));
+                }
+            }
+        }
+        if n > 0 {
+            header.write_seq.store(write.wrapping_add(n as u64), Ordering::Release);
+        }
+        n
+    }
+    
+    /// Consumer: drain up to `out.len()` ticks and return how many were filled.
+    /// Damaged records are consumed, counted in `corruptions` and left out
+    pub fn read_ticks(&self, out: &mut [MarketTick]) -> usize {
+        let header = self.header();
+        let read = header.read_seq.load(Ordering::Relaxed);
+        let write = header.write_seq.load(Ordering::Acquire);
+        let available = (write.wrapping_sub(read) as usize).min(out.len());
+        
+        let mut filled = 0;
+        for i in 0..available {
+            let seq = read.wrapping_add(i as u64);
+            let tick = unsafe { self.slot(seq).read() };
+            if self.checksum && unsafe { self.slot_crc(seq).read() } != tick_crc(&tick) {
+                self.corruptions.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+            out[filled] = tick;
+            filled += 1;
+        }
+        if available > 0 {
+            header.read_seq.store(read.wrapping_add(available as u64), Ordering::Release);
+        }
+        filled
+    }
+    
+    /// Records that failed checksum verification in this process
+    pub fn corruptions(&self) -> u64 {
+        self.corruptions.load(Ordering::Relaxed)
+    }
+    
+    pub fn checksummed(&self) -> bool {
+        self.checksum
+    }
+    
+    /// Bump this side's heartbeat word in the header; call it from the
+    /// event loop more often than the peer's `peer_alive` timeout
+    #[inline(always)]
+    pub fn heartbeat(&self) {
+        let header = self.header();
+        let beat = if self.owner { &header.creator_beat } else { &header.attacher_beat };
+        beat.fetch_add(1, Ordering::Release);
+    }
+    
+    /// Whether the other side's heartbeat word moved in the last `timeout_ns`
+    /// (counted from when this handle was opened until the first beat)
+    pub fn peer_alive(&self, timeout_ns: i64) -> bool {
+        self.peer_alive_at(timeout_ns, HiResTimer::now_ns())
+    }
+    
+    pub fn peer_alive_at(&self, timeout_ns: i64, now_ns: i64) -> bool {
+        let header = self.header();
+        let beat = if self.owner { &header.attacher_beat } else { &header.creator_beat };
+        let current = beat.load(Ordering::Acquire);
+        if self.peer_beat_seen.swap(current, Ordering::Relaxed) != current {
+            self.peer_beat_changed_ns.store(now_ns, Ordering::Relaxed);
+            return true;
+        }
+        now_ns - self.peer_beat_changed_ns.load(Ordering::Relaxed) <= timeout_ns
+    }
+    
+    /// Huge page backing actually in effect (creator), or found on attach
+    pub fn huge_pages(&self) -> HugePages {
+        self.huge_pages
+    }
+    
+    /// NUMA node the creator's `mbind` succeeded for; `None` if unbound or attached
+    pub fn numa_node(&self) -> Option<u32> {
+        self.numa_node
+    }
+    
+    pub fn len(&self) -> usize {
+        let header = self.header();
+        let write = header.write_seq.load(Ordering::Acquire);
+        let read = header.read_seq.load(Ordering::Acquire);
+        write.wrapping_sub(read) as usize
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    pub fn capacity(&self) -> usize {
+        self.capacity as usize
+    }
+    
+    pub fn name(&self) -> &str {
+        self.name.to_str().unwrap_or_default()
+    }
+}
+
+/// A tick still in the ring, from `ShmRing::read_tick_ref`. The producer
+/// can't reuse the slot while this is alive, so the borrow is stable
+pub struct ShmTickRef<'a> {
+    ring: &'a ShmRing,
+    seq: u64,
+}
+
+impl ShmTickRef<'_> {
+    /// Position of this tick in the stream
+    pub fn sequence(&self) -> u64 {
+        self.seq
+    }
+    
+    /// Check the record's CRC; always true on rings created without checksums.
+    /// A failure is counted in `ShmRing::corruptions`
+    pub fn verify(&self) -> bool {
+        let intact = !self.ring.checksum || unsafe { self.ring.slot_crc(self.seq).read() } == tick_crc(self);
+        if !intact {
+            self.ring.corruptions.fetch_add(1, Ordering::Relaxed);
+        }
+        intact
+    }
+}
+
+impl std::ops::Deref for ShmTickRef<'_> {
+    type Target = MarketTick;
+    
+    #[inline(always)]
+    fn deref(&self) -> &MarketTick {
+        unsafe { &*self.ring.slot(self.seq) }
+    }
+}
+
+impl Drop for ShmTickRef<'_> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        self.ring.header().read_seq.store(self.seq.wrapping_add(1), Ordering::Release);
+    }
+}
+
+impl Drop for ShmRing {
+    fn drop(&mut self) {
+        unsafe {
+            sys::munmap(self.base.as_ptr() as *mut c_void, self.len);
+            if self.owner {
+                match &self.hugetlb_path {
+                    Some(path) => {
+                        if names_fd(path, self.fd) {
+                            let _ = std::fs::remove_file(path);
+                        }
+                    }
+                    None => {
+                        // The name may have been unlinked and reused since; only remove our own
+                        if names_fd(&shm_path(&self.name), self.fd) {
+                            sys::shm_unlink(self.name.as_ptr() as *const c_char);
+                        }
+                    }
+                }
+            }
+            sys::close(self.fd);
+        }
+    }
+}
+
+// Not Sync: the ring has one producer and one consumer, and `write_tick` /
+// `read_tick` take `&self`, so sharing a handle across threads would race
+unsafe impl Send for ShmRing {}
+
+// Segment lifecycle
+// General-purpose named segments behind a versioned header. The creator
+// records its PID and refreshes a heartbeat; a later `create` under the same
+// name replaces the segment only if that owner is dead or has stopped beating,
+// and attachers refuse segments whose magic or version they don't recognise.
+
+pub const SEGMENT_MAGIC: u64 = u64::from_le_bytes(*b"HFTSEG\0\0");
+pub const SEGMENT_VERSION: u32 = 1;
+
+#[repr(C, align(64))]
+struct SegmentHeader {
+    magic: AtomicU64,  // Stored last by the creator; 0 while initialising
+    version: u32,
+    owner_pid: AtomicU32,
+    heartbeat_ns: AtomicI64,  // 0 until the owner's first heartbeat
+    payload_len: AtomicU64,
+}
+
+const SEGMENT_HEADER: usize = std::mem::size_of::<SegmentHeader>();
+
+/// A mapped segment: `SegmentHeader` followed by `payload_len` bytes
+pub struct ShmSegment {
+    name: CString,
+    fd: c_int,
+    base: NonNull<u8>,
+    len: usize,
+    owner: bool,  // Creator unlinks the segment on drop
+    clock: Arc<dyn Clock>,
+}
+
+impl ShmSegment {
+    #[inline(always)]
+    fn header(&self) -> &SegmentHeader {
+        unsafe { &*(self.base.as_ptr() as *const SegmentHeader) }
+    }
+    
+    /// Start of the payload; valid for `payload_len` bytes until the next `resize`/`remap`
+    #[inline(always)]
+    pub fn payload(&self) -> *mut u8 {
+        unsafe { self.base.as_ptr().add(SEGMENT_HEADER) }
+    }
+    
+    /// Payload bytes covered by this process's mapping
+    pub fn payload_len(&self) -> usize {
+        self.len - SEGMENT_HEADER
+    }
+    
+    /// Owner: mark the segment live; call more often than the manager's `stale_after`
+    #[inline(always)]
+    pub fn heartbeat(&self) {
+        self.header().heartbeat_ns.store(self.clock.now_ns(), Ordering::Release);
+    }
+    
+    pub fn last_heartbeat_ns(&self) -> i64 {
+        self.header().heartbeat_ns.load(Ordering::Acquire)
+    }
+    
+    pub fn owner_pid(&self) -> u32 {
+        self.header().owner_pid.load(Ordering::Acquire)
+    }
+    
+    pub fn is_owner(&self) -> bool {
+        self.owner
+    }
+    
+    pub fn name(&self) -> &str {
+        self.name.to_str().unwrap_or_default()
+    }
+    
+    /// Owner: grow or shrink the payload. Attached processes keep their old
+    /// mapping until they call `remap`
+    pub fn resize(&mut self, payload_len: usize) -> Result<(), ShmError> {
+        if !self.owner {
+            return Err(ShmError::InUse);
+        }
+        let len = SEGMENT_HEADER + payload_len;
+        if unsafe { sys::ftruncate(self.fd, len as i64) } == -1 {
+            return Err(ShmError::Os(last_errno()));
+        }
+        self.map_to(len)?;
+        self.header().payload_len.store(payload_len as u64, Ordering::Release);
+        Ok(())
+    }
+    
+    /// Attacher: pick up the owner's latest `resize`; returns whether the mapping changed.
+    /// `Incompatible` if the header claims more than the file holds
+    pub fn remap(&mut self) -> Result<bool, ShmError> {
+        let payload_len = self.header().payload_len.load(Ordering::Acquire);
+        let len = usize::try_from(payload_len)
+            .ok()
+            .and_then(|payload_len| payload_len.checked_add(SEGMENT_HEADER))
+            .ok_or(ShmError::Incompatible)?;
+        if len == self.len {
+            return Ok(false);
+        }
+        // The header is peer-writable: never map past the end of the file
+        let file_len = unsafe { sys::lseek(self.fd, 0, sys::SEEK_END) };
+        if file_len == -1 {
+            return Err(ShmError::Os(last_errno()));
+        }
+        if (file_len as u64) < len as u64 {
+            return Err(ShmError::Incompatible);
+        }
+        self.map_to(len)?;
+        Ok(true)
+    }
+    
+    fn map_to(&mut self, len: usize) -> Result<(), ShmError> {
+        let fd = unsafe { sys::dup(self.fd) };
+        if fd == -1 {
+            return Err(ShmError::Os(last_errno()));
+        }
+        let base = map_shared(fd, len)?;
+        unsafe {
+            sys::close(fd);
+            sys::munmap(self.base.as_ptr() as *mut c_void, self.len);
+        }
+        self.base = base;
+        self.len = len;
+        Ok(())
+    }
+}
+
+impl Drop for ShmSegment {
+    fn drop(&mut self) {
+        unsafe {
+            sys::munmap(self.base.as_ptr() as *mut c_void, self.len);
+            // A stale owner may already have been replaced: leave the new segment's name alone
+            if self.owner && names_fd(&shm_path(&self.name), self.fd) {
+                sys::shm_unlink(self.name.as_ptr());
+            }
+            sys::close(self.fd);
+        }
+    }
+}
+
+unsafe impl Send for ShmSegment {}
+unsafe impl Sync for ShmSegment {}
+
+/// Permissions for segments this process creates: owner read/write only
+pub const DEFAULT_SHM_MODE: u32 = 0o600;
+
+/// Creates, attaches and cleans up `ShmSegment`s
+pub struct ShmManager {
+    stale_after_ns: i64,
+    mode: u32,
+    clock: Arc<dyn Clock>,
+}
+
+impl ShmManager {
+    pub const DEFAULT_STALE_AFTER: Duration = Duration::from_secs(1);
+    
+    pub fn new() -> Self {
+        Self { stale_after_ns: Self::DEFAULT_STALE_AFTER.as_nanos() as i64, mode: DEFAULT_SHM_MODE, clock: clock::system() }
+    }
+    
+    /// Permission bits for created segments (default `DEFAULT_SHM_MODE`).
+    /// Anyone who can open a segment can write records into it; widen this
+    /// only for a peer running as another user, e.g. `0o660` with a shared group
+    pub fn with_mode(mut self, mode: u32) -> Self {
+        self.mode = mode;
+        self
+    }
+    
+    /// How long an owner may go without a heartbeat before its segment is fair game
+    pub fn with_stale_after(mut self, stale_after: Duration) -> Self {
+        self.stale_after_ns = stale_after.as_nanos().min(i64::MAX as u128) as i64;
+        self
+    }
+    
+    /// Time source for heartbeats and staleness (default: `HiResTimer`)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    /// Create `name` with `payload_len` zeroed bytes. An existing segment is
+    /// replaced only if it's stale; a live one gives `InUse` and anything
+    /// that isn't a managed segment (a ring, another version) `Incompatible`
+    pub fn create(&self, name: &str, payload_len: usize) -> Result<ShmSegment, ShmError> {
+        let cname = CString::new(name).map_err(|_| ShmError::BadName)?;
+        let flags = sys::O_CREAT | sys::O_RDWR | sys::O_EXCL;
+        
+        let mut fd = unsafe { sys::shm_open(cname.as_ptr(), flags, self.mode) };
+        if fd == -1 && last_errno() == sys::EEXIST {
+            let existing = self.attach(name)?;
+            // Re-check the name after the staleness test so we never unlink a replacement
+            if !self.segment_is_stale(&existing) || !names_fd(&shm_path(&cname), existing.fd) {
+                return Err(ShmError::InUse);
+            }
+            unsafe {
+                sys::shm_unlink(cname.as_ptr());
+                fd = sys::shm_open(cname.as_ptr(), flags, self.mode);
+            }
+        }
+        if fd == -1 {
+            return Err(ShmError::Os(last_errno()));
+        }
+        
+        let len = SEGMENT_HEADER + payload_len;
+        if unsafe { sys::ftruncate(fd, len as i64) } == -1 {
+            let errno = last_errno();
+            unsafe {
+                sys::close(fd);
+                sys::shm_unlink(cname.as_ptr());
+            }
+            return Err(ShmError::Os(errno));
+        }
+        let base = match map_shared(fd, len) {
+            Ok(base) => base,
+            Err(err) => {
+                unsafe { sys::shm_unlink(cname.as_ptr()) };
+                return Err(err);
+            }
+        };
+        
+        let segment = ShmSegment { name: cname, fd, base, len, owner: true, clock: self.clock.clone() };
+        unsafe { (*(base.as_ptr() as *mut SegmentHeader)).version = SEGMENT_VERSION };
+        let header = segment.header();
+        header.owner_pid.store(unsafe { sys::getpid() } as u32, Ordering::Relaxed);
+        header.payload_len.store(payload_len as u64, Ordering::Relaxed);
+        segment.heartbeat();
+        header.magic.store(SEGMENT_MAGIC, Ordering::Release);
+        Ok(segment)
+    }
+    
+    /// Map an existing segment. `NotMapped` while its creator is still
+    /// initialising; `Incompatible` for a wrong magic or version
+    pub fn attach(&self, name: &str) -> Result<ShmSegment, ShmError> {
+        let cname = CString::new(name).map_err(|_| ShmError::BadName)?;
+        let fd = unsafe { sys::shm_open(cname.as_ptr(), sys::O_RDWR, 0o666) };
+        if fd == -1 {
+            return Err(ShmError::Os(last_errno()));
+        }
+        let len = unsafe { sys::lseek(fd, 0, sys::SEEK_END) };
+        if len < SEGMENT_HEADER as i64 {
+            unsafe { sys::close(fd) };
+            return Err(ShmError::NotMapped);
+        }
+        let base = map_shared(fd, SEGMENT_HEADER)?;
+        let mut segment = ShmSegment { name: cname, fd, base, len: SEGMENT_HEADER, owner: false, clock: self.clock.clone() };
+        
+        match segment.header().magic.load(Ordering::Acquire) {
+            0 => return Err(ShmError::NotMapped),
+            SEGMENT_MAGIC => {}
+            _ => return Err(ShmError::Incompatible),
+        }
+        if segment.header().version != SEGMENT_VERSION {
+            return Err(ShmError::Incompatible);
+        }
+        segment.remap()?;
+        Ok(segment)
+    }
+    
+    /// Remove `name`; mappings already open stay valid until dropped
+    pub fn unlink(&self, name: &str) -> Result<(), ShmError> {
+        let cname = CString::new(name).map_err(|_| ShmError::BadName)?;
+        if unsafe { sys::shm_unlink(cname.as_ptr()) } == -1 {
+            return Err(ShmError::Os(last_errno()));
+        }
+        Ok(())
+    }
+    
+    /// Whether `name` was left behind by a dead or silent owner
+    pub fn is_stale(&self, name: &str) -> Result<bool, ShmError> {
+        self.attach(name).map(|segment| self.segment_is_stale(&segment))
+    }
+    
+    fn segment_is_stale(&self, segment: &ShmSegment) -> bool {
+        let pid = segment.owner_pid() as c_int;
+        let dead = unsafe { sys::kill(pid, 0) } == -1 && last_errno() == sys::ESRCH;
+        dead || self.clock.now_ns().saturating_sub(segment.last_heartbeat_ns()) > self.stale_after_ns
+    }
+}
+
+impl Default for ShmManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_header_matches_cpp_layout() {
+        assert_eq!(std::mem::size_of::<ShmHeader>(), 128);
+        assert_eq!(std::mem::offset_of!(ShmHeader, capacity), 24);
+        assert_eq!(std::mem::offset_of!(ShmHeader, name), 40);
+        assert_eq!(std::mem::offset_of!(ShmHeader, flags), 104);
+        assert_eq!(std::mem::offset_of!(ShmHeader, version), 116);
+        assert_eq!(std::mem::offset_of!(ShmHeader, attacher_beat), 124);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+    
+    #[test]
+    fn test_ring_roundtrip_in_process() {
+        let name = format!("hft_ring_unit_{}", std::process::id());
+        let producer = ShmRing::create(&name, 4).unwrap();
+        let consumer = ShmRing::attach(&name).unwrap();
+        assert_eq!(consumer.capacity(), 4);
+        
+        for i in 0..4 {
+            let tick = MarketTick { timestamp_ns: i, ..Default::default() };
+            producer.write_tick(&tick).unwrap();
+        }
+        assert_eq!(producer.write_tick(&MarketTick::default()), Err(ShmError::Full));
+        
+        for i in 0..4 {
+            assert_eq!(consumer.read_tick().unwrap().map(|t| t.timestamp_ns), Some(i));
+        }
+        assert!(consumer.read_tick().unwrap().is_none());
+    }
+    
+    #[test]
+    fn test_huge_page_and_numa_requests_fall_back() {
+        for (i, huge_pages) in [HugePages::Transparent, HugePages::Huge2M, HugePages::Huge1G].into_iter().enumerate() {
+            let name = format!("hft_ring_huge_{}_{}", std::process::id(), i);
+            let options = ShmOptions { huge_pages, numa_node: Some(0), ..Default::default() };
+            let producer = ShmRing::create_with(&name, 4, options).unwrap();
+            assert!(producer.huge_pages() == huge_pages || producer.huge_pages() == HugePages::Off);
+            assert!(producer.numa_node().is_none() || producer.numa_node() == Some(0));
+            
+            let consumer = ShmRing::attach(&name).unwrap();
+            if producer.huge_pages() != HugePages::Transparent {
+                assert_eq!(consumer.huge_pages(), producer.huge_pages());
+            }
+            producer.write_tick(&MarketTick { timestamp_ns: 11, ..Default::default() }).unwrap();
+            assert_eq!(consumer.read_tick().unwrap().map(|t| t.timestamp_ns), Some(11));
+        }
+        
+        // No such node: the ring still works, unbound
+        let name = format!("hft_ring_numa_{}", std::process::id());
+        let ring = ShmRing::create_with(&name, 4, ShmOptions { numa_node: Some(63), ..Default::default() }).unwrap();
+        assert_eq!(ring.numa_node(), None);
+    }
+    
+    #[test]
+    fn test_read_tick_ref_pins_slot_until_dropped() {
+        let name = format!("hft_ring_ref_{}", std::process::id());
+        let producer = ShmRing::create_with(&name, 2, ShmOptions { checksum: true, ..Default::default() }).unwrap();
+        let mut consumer = ShmRing::attach(&name).unwrap();
+        assert!(consumer.read_tick_ref().is_none());
+        
+        producer.write_tick(&MarketTick { timestamp_ns: 1, bid_price: 99.5, ..Default::default() }).unwrap();
+        producer.write_tick(&MarketTick { timestamp_ns: 2, ..Default::default() }).unwrap();
+        {
+            let tick = consumer.read_tick_ref().unwrap();
+            assert_eq!((tick.sequence(), tick.timestamp_ns, tick.bid_price), (0, 1, 99.5));
+            assert!(tick.verify());
+            // Still counted as occupied: the producer can't overwrite it
+            assert_eq!(producer.write_tick(&MarketTick::default()), Err(ShmError::Full));
+        }
+        assert_eq!(consumer.len(), 1);
+        assert_eq!(consumer.read_tick_ref().map(|t| t.timestamp_ns), Some(2));
+        assert!(consumer.is_empty());
+    }
+    
+    #[test]
+    fn test_peer_heartbeats() {
+        let name = format!("hft_ring_beat_{}", std::process::id());
+        let gateway = ShmRing::create(&name, 4).unwrap();
+        let strategy = ShmRing::attach(&name).unwrap();
+        let t0 = HiResTimer::now_ns();
+        const TIMEOUT: i64 = 1_000_000;
+        
+        // Grace period from opening, then silence counts as dead
+        assert!(strategy.peer_alive_at(TIMEOUT, t0));
+        assert!(!strategy.peer_alive_at(TIMEOUT, t0 + 2 * TIMEOUT));
+        
+        gateway.heartbeat();
+        assert!(strategy.peer_alive_at(TIMEOUT, t0 + 2 * TIMEOUT));
+        assert!(strategy.peer_alive_at(TIMEOUT, t0 + 3 * TIMEOUT));
+        assert!(!strategy.peer_alive_at(TIMEOUT, t0 + 3 * TIMEOUT + 1));
+        
+        // Each side watches the other's word, not its own
+        strategy.heartbeat();
+        assert!(gateway.peer_alive_at(TIMEOUT, t0 + 10 * TIMEOUT));
+        assert!(!strategy.peer_alive_at(TIMEOUT, t0 + 10 * TIMEOUT));
+    }
+    
+    #[test]
+    fn test_batch_write_and_drain() {
+        let name = format!("hft_ring_batch_{}", std::process::id());
+        let producer = ShmRing::create_with(&name, 8, ShmOptions { checksum: true, ..Default::default() }).unwrap();
+        let consumer = ShmRing::attach(&name).unwrap();
+        
+        let ticks: Vec<MarketTick> = (0..10).map(|i| MarketTick { timestamp_ns: i, ..Default::default() }).collect();
+        assert_eq!(producer.write_ticks(&ticks), 8);
+        assert_eq!(producer.write_ticks(&ticks), 0);
+        
+        let mut out = [MarketTick::default(); 5];
+        assert_eq!(consumer.read_ticks(&mut out), 5);
+        assert_eq!(out[4].timestamp_ns, 4);
+        assert_eq!(producer.write_ticks(&ticks[8..]), 2);
+        assert_eq!(consumer.read_ticks(&mut out), 5);
+        assert_eq!(out.map(|t| t.timestamp_ns), [5, 6, 7, 8, 9]);
+        assert_eq!(consumer.read_ticks(&mut out), 0);
+    }
+    
+    #[test]
+    fn test_checksum_detects_flipped_byte() {
+        let name = format!("hft_ring_crc_{}", std::process::id());
+        let producer = ShmRing::create_with(&name, 4, ShmOptions { checksum: true, ..Default::default() }).unwrap();
+        let consumer = ShmRing::attach(&name).unwrap();
+        assert!(consumer.checksummed());
+        
+        let tick = MarketTick { timestamp_ns: 7, bid_price: 99.5, ask_price: 100.5, ..Default::default() };
+        producer.write_tick(&tick).unwrap();
+        producer.write_tick(&tick).unwrap();
+        
+        // Damage the first record's bid_price in the mapping
+        unsafe {
+            let byte = (producer.slot(0) as *mut u8).add(std::mem::offset_of!(MarketTick, bid_price));
+            *byte ^= 0x40;
+        }
+        
+        assert_eq!(consumer.read_tick().err(), Some(ShmError::Corrupt));
+        assert_eq!(consumer.corruptions(), 1);
+        // The damaged record is skipped; the next one is intact
+        assert_eq!(consumer.read_tick().unwrap().map(|t| t.bid_price), Some(99.5));
+        assert!(consumer.read_tick().unwrap().is_none());
+    }
+    
+    #[test]
+    fn test_create_replaces_only_abandoned_rings() {
+        let name = format!("hft_ring_live_{}", std::process::id());
+        let producer = ShmRing::create(&name, 4).unwrap();
+        producer.write_tick(&MarketTick { timestamp_ns: 5, ..Default::default() }).unwrap();
+        
+        // A second creator must not throw away a live ring
+        assert_eq!(ShmRing::create(&name, 4).err(), Some(ShmError::InUse));
+        let consumer = ShmRing::attach(&name).unwrap();
+        assert_eq!(consumer.read_tick().unwrap().map(|t| t.timestamp_ns), Some(5));
+        
+        let path = format!("/dev/shm/{}", name);
+        let mode = std::os::unix::fs::PermissionsExt::mode(&std::fs::metadata(&path).unwrap().permissions());
+        assert_eq!(mode & 0o077, 0);
+        
+        // Creator gone (as far as its lock says): the ring is replaced, and the
+        // old handle's drop leaves the replacement's name alone
+        unsafe { sys::flock(producer.fd, sys::LOCK_UN) };
+        let replacement = ShmRing::create(&name, 8).unwrap();
+        drop(producer);
+        assert_eq!(ShmRing::attach(&name).unwrap().capacity(), 8);
+        drop(replacement);
+        assert!(matches!(ShmRing::attach(&name), Err(ShmError::Os(_))));
+    }
+    
+    #[test]
+    fn test_hugetlb_file_replaced_only_when_abandoned() {
+        use std::os::unix::fs::FileExt;
+        
+        // Any filesystem will do for the file handling; hugetlbfs only changes the page size
+        let path = std::env::temp_dir().join(format!("hft_ring_hugetlb_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let first = create_hugetlb(&path, 4096, DEFAULT_SHM_MODE).unwrap();
+        assert_eq!(create_hugetlb(&path, 4096, DEFAULT_SHM_MODE).err(), Some(ShmError::Incompatible));
+        
+        let file = ManuallyDrop::new(unsafe { File::from_raw_fd(first) });
+        file.write_all_at(&RING_MAGIC.to_le_bytes(), std::mem::offset_of!(ShmHeader, magic) as u64).unwrap();
+        assert_eq!(create_hugetlb(&path, 4096, DEFAULT_SHM_MODE).err(), Some(ShmError::InUse));
+        
+        // The creator closing its file is what marks the ring abandoned
+        unsafe { sys::close(first) };
+        let second = create_hugetlb(&path, 4096, DEFAULT_SHM_MODE).unwrap();
+        assert!(names_fd(&path, second));
+        unsafe { sys::close(second) };
+        std::fs::remove_file(&path).unwrap();
+    }
+    
+    #[test]
+    fn test_attach_missing_segment() {
+        assert!(matches!(ShmRing::attach("hft_ring_does_not_exist"), Err(ShmError::Os(_))));
+        assert_eq!(ShmRing::create("bad\0name", 4).err(), Some(ShmError::BadName));
+        assert_eq!(ShmRing::create("hft_ring_odd", 3).err(), Some(ShmError::Incompatible));
+    }
+    
+    #[test]
+    fn test_manager_create_attach_resize() {
+        let name = format!("hft_seg_unit_{}", std::process::id());
+        let manager = ShmManager::new();
+        let mut owner = manager.create(&name, 64).unwrap();
+        assert_eq!(owner.owner_pid(), std::process::id());
+        assert_eq!(manager.create(&name, 64).err(), Some(ShmError::InUse));
+        
+        let mut peer = manager.attach(&name).unwrap();
+        assert_eq!(peer.payload_len(), 64);
+        unsafe { owner.payload().write(42) };
+        assert_eq!(unsafe { peer.payload().read() }, 42);
+        assert_eq!(peer.resize(128).err(), Some(ShmError::InUse));
+        
+        owner.resize(8192).unwrap();
+        unsafe { owner.payload().add(8000).write(7) };
+        assert_eq!(peer.remap(), Ok(true));
+        assert_eq!(peer.payload_len(), 8192);
+        assert_eq!(unsafe { (peer.payload().read(), peer.payload().add(8000).read()) }, (42, 7));
+        assert_eq!(peer.remap(), Ok(false));
+        
+        // A payload length the file can't back is refused; the old mapping stays
+        owner.header().payload_len.store(1 << 40, Ordering::Release);
+        assert_eq!(peer.remap(), Err(ShmError::Incompatible));
+        owner.header().payload_len.store(u64::MAX, Ordering::Release);
+        assert_eq!(peer.remap(), Err(ShmError::Incompatible));
+        assert_eq!(unsafe { peer.payload().add(8000).read() }, 7);
+        owner.header().payload_len.store(8192, Ordering::Release);
+        
+        // umask can only narrow the default
+        let path = format!("/dev/shm/{}", name);
+        let mode = std::os::unix::fs::PermissionsExt::mode(&std::fs::metadata(&path).unwrap().permissions());
+        assert_eq!(mode & 0o077, 0);
+        
+        drop(owner);
+        assert!(matches!(manager.attach(&name), Err(ShmError::Os(_))));
+    }
+    
+    #[test]
+    fn test_manager_replaces_stale_segments() {
+        let name = format!("hft_seg_stale_{}", std::process::id());
+        let clock = Arc::new(crate::clock::MockClock::new(1_000_000_000));
+        let manager = ShmManager::new().with_stale_after(Duration::from_millis(10)).with_clock(clock.clone());
+        let first = manager.create(&name, 16).unwrap();
+        
+        // Owner alive but silent past the deadline
+        clock.advance(Duration::from_millis(5));
+        assert_eq!(manager.is_stale(&name), Ok(false));
+        clock.advance(Duration::from_millis(6));
+        assert_eq!(manager.is_stale(&name), Ok(true));
+        first.heartbeat();
+        assert_eq!(manager.is_stale(&name), Ok(false));
+        
+        // Owner process gone
+        first.header().owner_pid.store(i32::MAX as u32, Ordering::Relaxed);
+        assert_eq!(manager.is_stale(&name), Ok(true));
+        let second = manager.create(&name, 16).unwrap();
+        assert_eq!(second.owner_pid(), std::process::id());
+        
+        // The replaced owner's drop must not take the new segment's name with it
+        drop(first);
+        assert_eq!(manager.attach(&name).map(|segment| segment.owner_pid()), Ok(std::process::id()));
+        assert_eq!(manager.create(&name, 16).err(), Some(ShmError::InUse));
+        drop(second);
+        assert!(matches!(manager.attach(&name), Err(ShmError::Os(_))));
+    }
+    
+    #[test]
+    fn test_attach_rejects_foreign_header() {
+        let name = format!("hft_seg_foreign_{}", std::process::id());
+        let manager = ShmManager::new();
+        let segment = manager.create(&name, 16).unwrap();
+        unsafe { (*(segment.base.as_ptr() as *mut SegmentHeader)).version = SEGMENT_VERSION + 1 };
+        assert_eq!(manager.attach(&name).err(), Some(ShmError::Incompatible));
+        // Refused rather than replaced, even though nothing checks its owner
+        assert_eq!(manager.create(&name, 16).err(), Some(ShmError::Incompatible));
+        // Nor is a ring allowed to take over the segment's name
+        assert_eq!(ShmRing::create(&name, 4).err(), Some(ShmError::Incompatible));
+        
+        drop(segment);
+        
+        // A ring is not a managed segment
+        let ring = ShmRing::create(&name, 4).unwrap();
+        ring.write_tick(&MarketTick::default()).unwrap();
+        assert_eq!(manager.attach(&name).err(), Some(ShmError::Incompatible));
+        assert_eq!(manager.create(&name, 16).err(), Some(ShmError::Incompatible));
+        assert_eq!(ring.len(), 1);
+        drop(ring);
+    }
+}
@@ -0,0 +1,383 @@
+// Shared-memory tick bus: one writer process, any number of reader processes
+// The cross-process counterpart of `BroadcastRing`. Readers never write to
+// the segment; each keeps its own cursor, so attaching or dropping a reader
+// costs the writer nothing, and a reader that falls a full ring behind gets
+// `RecvError::Lagged` with the number of ticks it missed.
+//
+// Slots are seqlocks as in `broadcast`: `2w + 1` while tick `w` is being
+// written, `2w + 2` once it's complete.
+
+use std::sync::atomic::{fence, AtomicBool, AtomicU64, Ordering};
+
+use crate::broadcast::RecvError;
+use crate::shm::{ShmManager, ShmSegment};
+use crate::{MarketTick, ShmError};
+
+const BUS_MAGIC: u64 = u64::from_le_bytes(*b"HFTBUS\0\0");
+
+#[repr(C, align(64))]
+struct BusHeader {
+    magic: AtomicU64,  // Stored last by the writer; 0 while initialising
+    capacity: u64,
+    element_size: u64,
+    tail: AtomicU64,  // Ticks published so far
+    writer_alive: AtomicBool,
+}
+
+#[repr(C, align(64))]
+struct BusSlot {
+    seq: AtomicU64,
+    tick: MarketTick,
+}
+
+#[inline(always)]
+fn payload_len(capacity: usize) -> usize {
+    std::mem::size_of::<BusHeader>() + capacity * std::mem::size_of::<BusSlot>()
+}
+
+/// Mapped bus shared by the writer and reader handles
+struct Bus {
+    segment: ShmSegment,
+    capacity: u64,
+}
+
+impl Bus {
+    #[inline(always)]
+    fn header(&self) -> &BusHeader {
+        unsafe { &*(self.segment.payload() as *const BusHeader) }
+    }
+    
+    #[inline(always)]
+    fn slot(&self, index: u64) -> *mut BusSlot {
+        let idx = (index & (self.capacity - 1)) as usize;
+        unsafe { (self.segment.payload().add(std::mem::size_of::<BusHeader>()) as *mut BusSlot).add(idx) }
+    }
+    
+    #[inline(always)]
+    fn seq(&self, index: u64) -> &AtomicU64 {
+        unsafe { &(*self.slot(index)).seq }
+    }
+}
+
+// Writer
+
+pub struct ShmBusWriter {
+    bus: Bus,
+    next: u64,
+}
+
+impl ShmBusWriter {
+    /// Create the bus segment `name` with room for `capacity` ticks
+    pub fn create(manager: &ShmManager, name: &str, capacity: usize) -> Result<Self, ShmError> {
+        if !capacity.is_power_of_two() {
+            return Err(ShmError::Incompatible);
+        }
+        let segment = manager.create(name, payload_len(capacity))?;
+        unsafe {
+            let header = segment.payload() as *mut BusHeader;
+            (*header).capacity = capacity as u64;
+            (*header).element_size = std::mem::size_of::<MarketTick>() as u64;
+        }
+        let bus = Bus { segment, capacity: capacity as u64 };
+        bus.header().writer_alive.store(true, Ordering::Relaxed);
+        bus.header().magic.store(BUS_MAGIC, Ordering::Release);
+        Ok(Self { bus, next: 0 })
+    }
+    
+    /// Publish to every reader; never blocks, overwrites the oldest tick
+    #[inline(always)]
+    pub fn publish(&mut self, tick: &MarketTick) {
+        let w = self.next;
+        let seq = self.bus.seq(w);
+        
+        seq.store(2 * w + 1, Ordering::Relaxed);
+        // Odd marker must be visible before any byte of the new tick
+        fence(Ordering::Release);
+        unsafe { std::ptr::write_volatile(&mut (*self.bus.slot(w)).tick, *tick) };
+        seq.store(2 * w + 2, Ordering::Release);
+        
+        self.next = w + 1;
+        self.bus.header().tail.store(self.next, Ordering::Release);
+    }
+    
+    pub fn published(&self) -> u64 {
+        self.next
+    }
+    
+    pub fn capacity(&self) -> usize {
+        self.bus.capacity as usize
+    }
+    
+    /// Keep the segment from being treated as stale by `ShmManager`
+    #[inline(always)]
+    pub fn heartbeat(&self) {
+        self.bus.segment.heartbeat();
+    }
+}
+
+impl Drop for ShmBusWriter {
+    fn drop(&mut self) {
+        self.bus.header().writer_alive.store(false, Ordering::Release);
+    }
+}
+
+unsafe impl Send for ShmBusWriter {}
+
+// Reader
+
+pub struct ShmBusReader {
+    bus: Bus,
+    cursor: u64,
+    torn: u64,
+}
+
+impl ShmBusReader {
+    /// Attach to bus `name`; the first `recv` returns the next tick published
+    pub fn attach(manager: &ShmManager, name: &str) -> Result<Self, ShmError> {
+        let segment = manager.attach(name)?;
+        if segment.payload_len() < std::mem::size_of::<BusHeader>() {
+            return Err(ShmError::Incompatible);
+        }
+        let header = unsafe { &*(segment.payload() as *const BusHeader) };
+        match header.magic.load(Ordering::Acquire) {
+            0 => return Err(ShmError::NotMapped),
+            BUS_MAGIC => {}
+            _ => return Err(ShmError::Incompatible),
+        }
+        let capacity = header.capacity;
+        if header.element_size != std::mem::size_of::<MarketTick>() as u64
+            || !capacity.is_power_of_two()
+            || payload_len(capacity as usize) > segment.payload_len()
+        {
+            return Err(ShmError::Incompatible);
+        }
+        let bus = Bus { segment, capacity };
+        let cursor = bus.header().tail.load(Ordering::Acquire);
+        Ok(Self { bus, cursor, torn: 0 })
+    }
+    
+    /// Next tick for this reader (non-blocking)
+    #[inline(always)]
+    pub fn recv(&mut self) -> Result<MarketTick, RecvError> {
+        let expected = 2 * self.cursor + 2;
+        let seq = self.bus.seq(self.cursor);
+        
+        let before = seq.load(Ordering::Acquire);
+        if before == expected {
+            let copy = unsafe { std::ptr::read_volatile(&(*self.bus.slot(self.cursor)).tick) };
+            fence(Ordering::Acquire);
+            if seq.load(Ordering::Relaxed) == expected {
+                self.cursor += 1;
+                return Ok(copy);
+            }
+        } else if before < expected {
+            return Err(RecvError::Empty);
+        }
+        Err(self.skip_lapped())
+    }
+    
+    /// Read the next tick in place, field by field, skipping the 448-byte
+    /// copy. The writer doesn't wait for readers, so every read through the
+    /// guard is a volatile copy checked against the slot sequence and comes
+    /// back `None` once the slot has been overwritten. Dropping the guard
+    /// moves on to the next tick
+    #[inline(always)]
+    pub fn recv_ref(&mut self) -> Result<BusTickRef<'_>, RecvError> {
+        let expected = 2 * self.cursor + 2;
+        let before = self.bus.seq(self.cursor).load(Ordering::Acquire);
+        if before == expected {
+            return Ok(BusTickRef { reader: self, expected });
+        } else if before < expected {
+            return Err(RecvError::Empty);
+        }
+        Err(self.skip_lapped())
+    }
+    
+    /// Lapped by the writer: skip to the oldest tick still in the ring
+    #[cold]
+    fn skip_lapped(&mut self) -> RecvError {
+        let tail = self.bus.header().tail.load(Ordering::Acquire);
+        let oldest = tail.saturating_sub(self.bus.capacity).max(self.cursor + 1);
+        let missed = oldest - self.cursor;
+        self.cursor = oldest;
+        RecvError::Lagged(missed)
+    }
+    
+    /// `BusTickRef`s dropped after the writer had overwritten them
+    pub fn torn(&self) -> u64 {
+        self.torn
+    }
+    
+    /// Ticks published but not yet received (may exceed the capacity when lagging)
+    pub fn pending(&self) -> u64 {
+        self.bus.header().tail.load(Ordering::Acquire).saturating_sub(self.cursor)
+    }
+    
+    /// Sequence number of the next tick this reader will return
+    pub fn cursor(&self) -> u64 {
+        self.cursor
+    }
+    
+    /// True while the writer holds the bus open
+    pub fn is_connected(&self) -> bool {
+        self.bus.header().writer_alive.load(Ordering::Acquire)
+    }
+}
+
+unsafe impl Send for ShmBusReader {}
+
+/// A tick read in place from the bus, from `ShmBusReader::recv_ref`
+pub struct BusTickRef<'a> {
+    reader: &'a mut ShmBusReader,
+    expected: u64,  // Slot sequence while it holds our tick
+}
+
+/// Volatile copy of one tick field, kept only if the slot still holds our tick
+macro_rules! tick_field {
+    ($($field:ident: $ty:ty),* $(,)?) => {
+        $(
+            #[inline(always)]
+            pub fn $field(&self) -> Option<$ty> {
+                let value = unsafe { std::ptr::read_volatile(std::ptr::addr_of!((*self.slot()).tick.$field)) };
+                self.is_valid().then_some(value)
+            }
+        )*
+    };
+}
+
+impl BusTickRef<'_> {
+    /// Position of this tick in the stream
+    pub fn sequence(&self) -> u64 {
+        self.reader.cursor
+    }
+    
+    /// True while the slot still holds this tick: every value read through
+    /// the guard before this call came from it
+    #[inline(always)]
+    pub fn is_valid(&self) -> bool {
+        fence(Ordering::Acquire);
+        self.reader.bus.seq(self.reader.cursor).load(Ordering::Relaxed) == self.expected
+    }
+    
+    #[inline(always)]
+    fn slot(&self) -> *const BusSlot {
+        self.reader.bus.slot(self.reader.cursor)
+    }
+    
+    /// The whole tick, or `None` if the writer overwrote it
+    #[inline(always)]
+    pub fn read(&self) -> Option<MarketTick> {
+        let copy = unsafe { std::ptr::read_volatile(std::ptr::addr_of!((*self.slot()).tick)) };
+        self.is_valid().then_some(copy)
+    }
+    
+    tick_field! {
+        timestamp_ns: i64,
+        bid_price: f64,
+        ask_price: f64,
+        mid_price: f64,
+        bid_size: u64,
+        ask_size: u64,
+        trade_volume: u64,
+        asset_id: u32,
+    }
+}
+
+impl Drop for BusTickRef<'_> {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if !self.is_valid() {
+            self.reader.torn += 1;
+        }
+        self.reader.cursor += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn tick(i: i64) -> MarketTick {
+        MarketTick { timestamp_ns: i, ..Default::default() }
+    }
+    
+    #[test]
+    fn test_readers_see_every_tick_independently() {
+        let name = format!("hft_bus_fanout_{}", std::process::id());
+        let manager = ShmManager::new();
+        let mut writer = ShmBusWriter::create(&manager, &name, 8).unwrap();
+        let mut a = ShmBusReader::attach(&manager, &name).unwrap();
+        assert_eq!(a.recv().err(), Some(RecvError::Empty));
+        
+        writer.publish(&tick(0));
+        let mut b = ShmBusReader::attach(&manager, &name).unwrap();  // Joins after tick 0
+        for i in 1..4 {
+            writer.publish(&tick(i));
+        }
+        let got_a: Vec<i64> = std::iter::from_fn(|| a.recv().ok()).map(|t| t.timestamp_ns).collect();
+        let got_b: Vec<i64> = std::iter::from_fn(|| b.recv().ok()).map(|t| t.timestamp_ns).collect();
+        assert_eq!(got_a, vec![0, 1, 2, 3]);
+        assert_eq!(got_b, vec![1, 2, 3]);
+        
+        drop(writer);
+        assert!(!a.is_connected());
+    }
+    
+    #[test]
+    fn test_lapped_reader_reports_missed_ticks() {
+        let name = format!("hft_bus_lapped_{}", std::process::id());
+        let manager = ShmManager::new();
+        let mut writer = ShmBusWriter::create(&manager, &name, 4).unwrap();
+        let mut reader = ShmBusReader::attach(&manager, &name).unwrap();
+        
+        for i in 0..10 {
+            writer.publish(&tick(i));
+        }
+        assert_eq!(reader.pending(), 10);
+        assert_eq!(reader.recv().err(), Some(RecvError::Lagged(6)));
+        let rest: Vec<i64> = std::iter::from_fn(|| reader.recv().ok()).map(|t| t.timestamp_ns).collect();
+        assert_eq!(rest, vec![6, 7, 8, 9]);
+        assert_eq!(reader.cursor(), 10);
+    }
+    
+    #[test]
+    fn test_recv_ref_reads_in_place_and_detects_overwrite() {
+        let name = format!("hft_bus_ref_{}", std::process::id());
+        let manager = ShmManager::new();
+        let mut writer = ShmBusWriter::create(&manager, &name, 2).unwrap();
+        let mut reader = ShmBusReader::attach(&manager, &name).unwrap();
+        assert_eq!(reader.recv_ref().err(), Some(RecvError::Empty));
+        
+        writer.publish(&MarketTick { timestamp_ns: 1, bid_price: 99.5, ..Default::default() });
+        {
+            let borrowed = reader.recv_ref().unwrap();
+            assert_eq!((borrowed.sequence(), borrowed.bid_price()), (0, Some(99.5)));
+            assert_eq!(borrowed.read().map(|t| t.timestamp_ns), Some(1));
+        }
+        assert_eq!(reader.torn(), 0);
+        
+        // The writer laps the slot while it's borrowed
+        writer.publish(&tick(2));
+        let borrowed = reader.recv_ref().unwrap();
+        writer.publish(&tick(3));
+        writer.publish(&tick(4));
+        assert!(!borrowed.is_valid());
+        assert_eq!(borrowed.timestamp_ns(), None);
+        assert!(borrowed.read().is_none());
+        drop(borrowed);
+        assert_eq!(reader.torn(), 1);
+        assert_eq!(reader.recv().map(|t| t.timestamp_ns), Ok(3));
+        assert_eq!(reader.recv().map(|t| t.timestamp_ns), Ok(4));
+    }
+    
+    #[test]
+    fn test_attach_rejects_non_bus_segment() {
+        let name = format!("hft_bus_foreign_{}", std::process::id());
+        let manager = ShmManager::new();
+        let segment = manager.create(&name, 4096).unwrap();
+        assert_eq!(ShmBusReader::attach(&manager, &name).err(), Some(ShmError::NotMapped));
+        unsafe { segment.payload().write(0xFF) };
+        assert_eq!(ShmBusReader::attach(&manager, &name).err(), Some(ShmError::Incompatible));
+        assert_eq!(ShmBusWriter::create(&manager, &name, 3).err(), Some(ShmError::Incompatible));
+    }
+}
@@ -0,0 +1,272 @@
+// Typed shared-memory SPSC channels between the strategy and the C++ gateway
+// `ShmRing` carries ticks in the C++ ring's layout; orders and execution
+// reports travel on `ShmChannel`s instead, one per direction, each on a
+// `ShmManager` segment that records the element type so a process attaching
+// with the wrong `T` fails with `Incompatible` instead of misreading bytes.
+//
+// Payload layout (native byte order, offsets from the segment payload):
+//   0   ChannelHeader  magic, record type, element size, capacity
+//   64  write_seq      producer's index, own cache line
+//   128 read_seq       consumer's index, own cache line
+//   192 slots          `capacity` records of `size_of::<T>()` bytes
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::shm::{ShmManager, ShmSegment};
+use crate::{CancelSink, ExecutionReport, MarketTick, Order, ShmError};
+
+/// A plain `#[repr(C)]` record that may be copied through shared memory.
+///
+/// # Safety
+/// `Self` must hold no pointers or references, and any bytes the other
+/// process writes must make a valid `Self` once `BOOL_OFFSETS` are
+/// normalised (enum codes are stored as `u8` and checked on use). Every
+/// `bool` field must be listed. `TYPE_ID` must be unique per record layout.
+pub unsafe trait ShmRecord: Copy {
+    const TYPE_ID: u32;
+    /// Offsets of `bool` fields: read back as `u8` and folded to 0/1, since
+    /// a foreign process can leave any byte there
+    const BOOL_OFFSETS: &'static [usize] = &[];
+}
+
+unsafe impl ShmRecord for MarketTick {
+    const TYPE_ID: u32 = 1;
+}
+
+unsafe impl ShmRecord for Order {
+    const TYPE_ID: u32 = 2;
+    const BOOL_OFFSETS: &'static [usize] = &[std::mem::offset_of!(Order, is_active)];
+}
+
+unsafe impl ShmRecord for ExecutionReport {
+    const TYPE_ID: u32 = 3;
+}
+
+/// Strategy -> gateway order submissions; an `Order` with `is_active == false`
+/// cancels the working order with that `order_id`. Not `Sync`: the sending
+/// side must stay on one thread
+pub type OrderChannel = ShmChannel<Order>;
+/// Gateway -> strategy acks, fills, cancels and rejects
+pub type ExecChannel = ShmChannel<ExecutionReport>;
+
+const CHANNEL_MAGIC: u64 = u64::from_le_bytes(*b"HFTCHAN\0");
+
+#[repr(C, align(64))]
+struct ChannelHeader {
+    magic: AtomicU64,  // Stored last by the creator; 0 while initialising
+    record_type: u32,
+    element_size: u32,
+    capacity: u64,
+}
+
+#[repr(C, align(64))]
+struct Index {
+    seq: AtomicU64,
+}
+
+const SLOTS_OFFSET: usize = std::mem::size_of::<ChannelHeader>() + 2 * std::mem::size_of::<Index>();
+
+/// One direction of an order or execution-report stream. Exactly one
+/// process sends and one receives; both sides use the same type. A handle
+/// can move between threads but not be shared, so each end of the ring
+/// keeps a single thread
+pub struct ShmChannel<T: ShmRecord> {
+    segment: ShmSegment,
+    capacity: u64,
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T: ShmRecord> ShmChannel<T> {
+    /// Create the channel segment `name` with room for `capacity` records
+    pub fn create(manager: &ShmManager, name: &str, capacity: usize) -> Result<Self, ShmError> {
+        if !capacity.is_power_of_two() {
+            return Err(ShmError::Incompatible);
+        }
+        let segment = manager.create(name, SLOTS_OFFSET + capacity * std::mem::size_of::<T>())?;
+        unsafe {
+            let header = segment.payload() as *mut ChannelHeader;
+            (*header).record_type = T::TYPE_ID;
+            (*header).element_size = std::mem::size_of::<T>() as u32;
+            (*header).capacity = capacity as u64;
+        }
+        let channel = Self { segment, capacity: capacity as u64, _marker: std::marker::PhantomData };
+        channel.header().magic.store(CHANNEL_MAGIC, Ordering::Release);
+        Ok(channel)
+    }
+    
+    /// Attach to a channel created by the other process
+    pub fn attach(manager: &ShmManager, name: &str) -> Result<Self, ShmError> {
+        let segment = manager.attach(name)?;
+        if segment.payload_len() < SLOTS_OFFSET {
+            return Err(ShmError::Incompatible);
+        }
+        let header = unsafe { &*(segment.payload() as *const ChannelHeader) };
+        match header.magic.load(Ordering::Acquire) {
+            0 => return Err(ShmError::NotMapped),
+            CHANNEL_MAGIC => {}
+            _ => return Err(ShmError::Incompatible),
+        }
+        let capacity = header.capacity;
+        if header.record_type != T::TYPE_ID
+            || header.element_size as usize != std::mem::size_of::<T>()
+            || !capacity.is_power_of_two()
+            || SLOTS_OFFSET + capacity as usize * std::mem::size_of::<T>() > segment.payload_len()
+        {
+            return Err(ShmError::Incompatible);
+        }
+        Ok(Self { segment, capacity, _marker: std::marker::PhantomData })
+    }
+    
+    #[inline(always)]
+    fn header(&self) -> &ChannelHeader {
+        unsafe { &*(self.segment.payload() as *const ChannelHeader) }
+    }
+    
+    #[inline(always)]
+    fn index(&self, which: usize) -> &AtomicU64 {
+        unsafe {
+            let base = self.segment.payload().add(std::mem::size_of::<ChannelHeader>()) as *const Index;
+            &(*base.add(which)).seq
+        }
+    }
+    
+    #[inline(always)]
+    fn write_seq(&self) -> &AtomicU64 {
+        self.index(0)
+    }
+    
+    #[inline(always)]
+    fn read_seq(&self) -> &AtomicU64 {
+        self.index(1)
+    }
+    
+    #[inline(always)]
+    fn slot(&self, seq: u64) -> *mut T {
+        let idx = (seq & (self.capacity - 1)) as usize;
+        unsafe { (self.segment.payload().add(SLOTS_OFFSET) as *mut T).add(idx) }
+    }
+    
+    /// Sender: enqueue one record; `Full` if the receiver is a whole ring behind
+    #[inline(always)]
+    pub fn send(&self, record: &T) -> Result<(), ShmError> {
+        let write = self.write_seq().load(Ordering::Relaxed);
+        let read = self.read_seq().load(Ordering::Acquire);
+        if write.wrapping_sub(read) >= self.capacity {
+            return Err(ShmError::Full);
+        }
+        unsafe { self.slot(write).write(*record) };
+        self.write_seq().store(write.wrapping_add(1), Ordering::Release);
+        Ok(())
+    }
+    
+    /// Receiver: dequeue the oldest record, if any
+    #[inline(always)]
+    pub fn recv(&self) -> Option<T> {
+        let read = self.read_seq().load(Ordering::Relaxed);
+        let write = self.write_seq().load(Ordering::Acquire);
+        if read == write {
+            return None;
+        }
+        // Copied as bytes, so nothing the sender wrote is taken as a `T` before it's checked
+        let mut raw = std::mem::MaybeUninit::<T>::uninit();
+        let record = unsafe {
+            std::ptr::copy_nonoverlapping(self.slot(read) as *const u8, raw.as_mut_ptr() as *mut u8, std::mem::size_of::<T>());
+            for &offset in T::BOOL_OFFSETS {
+                let flag = (raw.as_mut_ptr() as *mut u8).add(offset);
+                *flag = (*flag != 0) as u8;
+            }
+            raw.assume_init()
+        };
+        self.read_seq().store(read.wrapping_add(1), Ordering::Release);
+        Some(record)
+    }
+    
+    pub fn len(&self) -> usize {
+        let write = self.write_seq().load(Ordering::Acquire);
+        let read = self.read_seq().load(Ordering::Acquire);
+        write.wrapping_sub(read) as usize
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    
+    pub fn capacity(&self) -> usize {
+        self.capacity as usize
+    }
+    
+    /// The underlying segment, for heartbeats and owner checks
+    pub fn segment(&self) -> &ShmSegment {
+        &self.segment
+    }
+}
+
+impl CancelSink for OrderChannel {
+    fn send_cancel(&self, cancel: &Order) -> bool {
+        self.send(cancel).is_ok()
+    }
+}
+
+unsafe impl<T: ShmRecord + Send> Send for ShmChannel<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ExecType;
+    use crate::order_state::OrderEvent;
+    
+    #[test]
+    fn test_order_out_fill_back() {
+        let pid = std::process::id();
+        let manager = ShmManager::new();
+        // Strategy creates the order channel, gateway the execution channel
+        let strategy_orders = OrderChannel::create(&manager, &format!("hft_orders_{}", pid), 4).unwrap();
+        let gateway_execs = ExecChannel::create(&manager, &format!("hft_execs_{}", pid), 4).unwrap();
+        let gateway_orders = OrderChannel::attach(&manager, &format!("hft_orders_{}", pid)).unwrap();
+        let strategy_execs = ExecChannel::attach(&manager, &format!("hft_execs_{}", pid)).unwrap();
+        
+        let order = Order { order_id: 42, asset_id: 7, side: 1, price: 100.25, quantity: 300, is_active: true, ..Default::default() };
+        strategy_orders.send(&order).unwrap();
+        let received = gateway_orders.recv().unwrap();
+        assert_eq!((received.order_id, received.price, received.quantity), (42, 100.25, 300));
+        assert!(gateway_orders.recv().is_none());
+        
+        let mut fill = ExecutionReport::for_order(&received, ExecType::PartialFill);
+        fill.last_price = 100.25;
+        fill.last_quantity = 100;
+        fill.cum_quantity = 100;
+        fill.leaves_quantity = 200;
+        gateway_execs.send(&fill).unwrap();
+        
+        let report = strategy_execs.recv().unwrap();
+        assert_eq!(report, fill);
+        assert_eq!((report.order_id, report.side, report.venue_id), (42, 1, 0));
+        assert_eq!(report.order_event(), Some(OrderEvent::Fill { quantity: 100 }));
+    }
+    
+    #[test]
+    fn test_channel_full_and_type_checked() {
+        let name = format!("hft_execs_typed_{}", std::process::id());
+        let manager = ShmManager::new();
+        let channel = ExecChannel::create(&manager, &name, 2).unwrap();
+        let reject = ExecutionReport::for_order(&Order::default(), ExecType::Rejected);
+        channel.send(&reject).unwrap();
+        channel.send(&reject).unwrap();
+        assert_eq!(channel.send(&reject), Err(ShmError::Full));
+        assert_eq!(channel.len(), 2);
+        
+        // Same segment viewed as the wrong record type
+        assert_eq!(OrderChannel::attach(&manager, &name).err(), Some(ShmError::Incompatible));
+        assert_eq!(ExecChannel::create(&manager, "hft_execs_odd", 3).err(), Some(ShmError::Incompatible));
+        
+        // A foreign sender's non-0/1 flag byte still reads as a valid bool
+        let orders = OrderChannel::create(&manager, &format!("hft_orders_flag_{}", std::process::id()), 2).unwrap();
+        orders.send(&Order { order_id: 3, ..Default::default() }).unwrap();
+        unsafe { (orders.slot(0) as *mut u8).add(std::mem::offset_of!(Order, is_active)).write(0x7F) };
+        assert!(orders.recv().is_some_and(|order| order.is_active && order.order_id == 3));
+        
+        let mut unknown = reject;
+        unknown.exec_type = 9;
+        assert_eq!((unknown.exec_type(), unknown.order_event()), (None, None));
+    }
+}
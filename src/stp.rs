@@ -0,0 +1,184 @@
+// Self-trade prevention
+// Keeps our own resting orders per asset and venue, as fed from the order
+// manager, and checks each new order against them before it leaves. A new buy
+// priced at or above one of our resting sells (or a sell at or below one of
+// our bids) on the same venue would trade with ourselves; depending on
+// `StpMode` it is either blocked or allowed once the resting orders it crosses
+// are cancelled, oldest first. Unpriced orders (price <= 0, e.g. market
+// orders) fail closed: they cross every opposite-side order.
+
+use std::collections::HashMap;
+
+use crate::Order;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StpMode {
+    /// Refuse the new order
+    CancelNew,
+    /// Cancel the crossed resting orders, then send the new one
+    CancelOldest,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StpAction {
+    Allow,
+    /// Our oldest resting order the new one would trade with
+    Block { resting_order_id: u64 },
+    /// Cancel these resting orders (oldest first) before sending the new one
+    CancelResting(Vec<u64>),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Resting {
+    order_id: u64,
+    side: u8,
+    price: f64,
+}
+
+// Self-Trade Guard
+
+/// Orders can only trade with each other on the same venue
+type BookKey = (u32, u8);  // (asset_id, venue_id)
+
+pub struct SelfTradeGuard {
+    mode: StpMode,
+    books: HashMap<BookKey, Vec<Resting>>,  // Resting orders in arrival order
+    keys: HashMap<u64, BookKey>,  // order_id -> its book
+}
+
+impl SelfTradeGuard {
+    pub fn new(mode: StpMode) -> Self {
+        Self { mode, books: HashMap::new(), keys: HashMap::new() }
+    }
+    
+    /// Record an order now resting at the venue (e.g. on its ack); false if already known
+    pub fn add_resting(&mut self, order: &Order) -> bool {
+        if self.keys.contains_key(&order.order_id) {
+            return false;
+        }
+        self.keys.insert(order.order_id, book_key(order));
+        self.books.entry(book_key(order)).or_default().push(Resting {
+            order_id: order.order_id,
+            side: order.side,
+            price: order.price,
+        });
+        true
+    }
+    
+    /// Forget a resting order once it is filled or cancelled; false if unknown
+    pub fn remove_resting(&mut self, order_id: u64) -> bool {
+        let Some(key) = self.keys.remove(&order_id) else {
+            return false;
+        };
+        if let Some(book) = self.books.get_mut(&key) {
+            book.retain(|resting| resting.order_id != order_id);
+        }
+        true
+    }
+    
+    /// What to do with `order` given our resting orders. `CancelResting`
+    /// leaves the orders tracked: remove them when their cancels are acked
+    pub fn check(&self, order: &Order) -> StpAction {
+        let mut crossed = self.books.get(&book_key(order)).into_iter().flatten()
+            .filter(|resting| crosses(order, resting))
+            .map(|resting| resting.order_id);
+        match self.mode {
+            StpMode::CancelNew => match crossed.next() {
+                Some(resting_order_id) => StpAction::Block { resting_order_id },
+                None => StpAction::Allow,
+            },
+            StpMode::CancelOldest => {
+                let crossed: Vec<u64> = crossed.collect();
+                if crossed.is_empty() { StpAction::Allow } else { StpAction::CancelResting(crossed) }
+            }
+        }
+    }
+    
+    /// Resting orders tracked for an asset on a venue
+    pub fn resting_count(&self, asset_id: u32, venue_id: u8) -> usize {
+        self.books.get(&(asset_id, venue_id)).map_or(0, Vec::len)
+    }
+    
+    pub fn mode(&self) -> StpMode {
+        self.mode
+    }
+}
+
+#[inline(always)]
+fn book_key(order: &Order) -> BookKey {
+    (order.asset_id, order.venue_id)
+}
+
+/// False for 0, negative and NaN prices
+#[inline(always)]
+fn is_priced(price: f64) -> bool {
+    price > 0.0
+}
+
+#[inline(always)]
+fn crosses(order: &Order, resting: &Resting) -> bool {
+    if order.side == resting.side {
+        return false;
+    }
+    if !is_priced(order.price) || !is_priced(resting.price) {
+        return true;
+    }
+    if order.side == 0 { order.price >= resting.price } else { order.price <= resting.price }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn order(order_id: u64, asset_id: u32, side: u8, price: f64) -> Order {
+        Order { order_id, asset_id, side, price, quantity: 10, ..Default::default() }
+    }
+    
+    #[test]
+    fn test_cancel_new_blocks_crossing_orders() {
+        let mut guard = SelfTradeGuard::new(StpMode::CancelNew);
+        assert!(guard.add_resting(&order(1, 7, 1, 100.02)));
+        assert!(guard.add_resting(&order(2, 7, 1, 100.01)));
+        assert!(guard.add_resting(&order(3, 7, 0, 99.98)));
+        assert!(!guard.add_resting(&order(3, 7, 0, 99.98)));
+        
+        assert_eq!(guard.check(&order(10, 7, 0, 100.00)), StpAction::Allow);
+        assert_eq!(guard.check(&order(10, 7, 0, 100.01)), StpAction::Block { resting_order_id: 2 });
+        assert_eq!(guard.check(&order(10, 7, 0, 100.05)), StpAction::Block { resting_order_id: 1 });
+        assert_eq!(guard.check(&order(10, 7, 1, 99.98)), StpAction::Block { resting_order_id: 3 });
+        // Other assets and the same asset on another venue don't cross
+        assert_eq!(guard.check(&order(10, 8, 0, 100.05)), StpAction::Allow);
+        assert_eq!(guard.check(&Order { venue_id: 2, ..order(10, 7, 0, 100.05) }), StpAction::Allow);
+    }
+    
+    #[test]
+    fn test_unpriced_orders_cross_everything_opposite() {
+        let mut guard = SelfTradeGuard::new(StpMode::CancelOldest);
+        guard.add_resting(&order(1, 7, 0, 99.98));
+        guard.add_resting(&order(2, 7, 0, 90.00));
+        guard.add_resting(&order(3, 7, 1, 100.02));
+        
+        // A market sell would hit both of our bids, whatever their price
+        assert_eq!(guard.check(&order(10, 7, 1, 0.0)), StpAction::CancelResting(vec![1, 2]));
+        assert_eq!(guard.check(&order(10, 7, 1, f64::NAN)), StpAction::CancelResting(vec![1, 2]));
+        assert_eq!(guard.check(&order(10, 7, 0, -1.0)), StpAction::CancelResting(vec![3]));
+        assert_eq!(guard.check(&order(10, 8, 1, 0.0)), StpAction::Allow);
+    }
+    
+    #[test]
+    fn test_cancel_oldest_lists_crossed_orders() {
+        let mut guard = SelfTradeGuard::new(StpMode::CancelOldest);
+        guard.add_resting(&order(1, 7, 0, 99.99));
+        guard.add_resting(&order(2, 7, 0, 100.00));
+        guard.add_resting(&order(3, 7, 0, 99.97));
+        
+        assert_eq!(guard.check(&order(10, 7, 1, 99.98)), StpAction::CancelResting(vec![1, 2]));
+        
+        // Once the cancels are acked the new order goes through
+        assert!(guard.remove_resting(1));
+        assert!(guard.remove_resting(2));
+        assert!(!guard.remove_resting(2));
+        assert_eq!(guard.check(&order(10, 7, 1, 99.98)), StpAction::Allow);
+        assert_eq!(guard.resting_count(7, 0), 1);
+    }
+}
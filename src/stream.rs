@@ -0,0 +1,296 @@
+// Async consumer side of the SPSC ring for non-latency-critical services
+// `TickStream` polls a plain ring with a yield-then-park backoff, so the
+// producer is untouched. `AsyncSPSC` registers wakers instead: push and pop
+// wake the other side only when it is actually parked, at the cost of one
+// flag check per call.
+
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::sync::atomic::{fence, AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use futures_core::Stream;
+use tokio::time::Sleep;
+
+use crate::{LockFreeSPSC, MarketTick};
+
+/// Backoff applied while the ring is empty
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    /// Empty polls that just yield back to the runtime before parking
+    pub yields: u32,
+    /// Timer interval between polls once parked (bounds added latency)
+    pub park: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            yields: 64,
+            park: Duration::from_millis(1),
+        }
+    }
+}
+
+/// Never-ending `Stream` of ticks popped from a ring; must be the ring's only consumer
+pub struct TickStream<'a, const CAPACITY: usize> {
+    queue: &'a LockFreeSPSC<MarketTick, CAPACITY>,
+    backoff: Backoff,
+    idle_polls: u32,
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl<'a, const CAPACITY: usize> TickStream<'a, CAPACITY> {
+    pub fn new(queue: &'a LockFreeSPSC<MarketTick, CAPACITY>) -> Self {
+        Self::with_backoff(queue, Backoff::default())
+    }
+    
+    pub fn with_backoff(queue: &'a LockFreeSPSC<MarketTick, CAPACITY>, backoff: Backoff) -> Self {
+        Self {
+            queue,
+            backoff,
+            idle_polls: 0,
+            sleep: None,
+        }
+    }
+}
+
+impl<const CAPACITY: usize> Stream for TickStream<'_, CAPACITY> {
+    type Item = MarketTick;
+    
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<MarketTick>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(tick) = this.queue.pop() {
+                this.idle_polls = 0;
+                this.sleep = None;
+                return Poll::Ready(Some(tick));
+            }
+            
+            if this.idle_polls < this.backoff.yields {
+                this.idle_polls += 1;
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            
+            let park = this.backoff.park;
+            let sleep = this.sleep.get_or_insert_with(|| Box::pin(tokio::time::sleep(park)));
+            match sleep.as_mut().poll(cx) {
+                Poll::Pending => return Poll::Pending,
+                // Timer fired: drop it and re-check the ring
+                Poll::Ready(()) => this.sleep = None,
+            }
+        }
+    }
+}
+
+// Waker-Registered SPSC
+
+/// One parked task's waker. `waiting` keeps the uncontended path to a single
+/// load: the lock is only taken when a task has actually parked
+struct WakerSlot {
+    waiting: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl WakerSlot {
+    fn new() -> Self {
+        Self { waiting: AtomicBool::new(false), waker: Mutex::new(None) }
+    }
+    
+    /// Park `waker`; the caller must re-check the queue afterwards
+    fn register(&self, waker: &Waker) {
+        let mut slot = self.waker.lock().unwrap();
+        if !slot.as_ref().is_some_and(|w| w.will_wake(waker)) {
+            *slot = Some(waker.clone());
+        }
+        self.waiting.store(true, Ordering::Relaxed);
+        drop(slot);
+        // Flag before the re-check, pairing with the fence in `wake`
+        fence(Ordering::SeqCst);
+    }
+    
+    /// Wake the parked task, if any; call after publishing the change it waits for
+    #[inline(always)]
+    fn wake(&self) {
+        fence(Ordering::SeqCst);
+        if self.waiting.load(Ordering::Relaxed) && self.waiting.swap(false, Ordering::Relaxed) {
+            if let Some(waker) = self.waker.lock().unwrap().take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// `LockFreeSPSC` whose sides can `await` instead of spinning: the consumer
+/// parks on empty and the producer on full, and each sync `push`/`pop` wakes
+/// the other side if it is parked. Latency-critical sides keep using the sync
+/// methods; the same one-producer/one-consumer rule applies
+pub struct AsyncSPSC<T, const CAPACITY: usize> {
+    queue: LockFreeSPSC<T, CAPACITY>,
+    consumer: WakerSlot,
+    producer: WakerSlot,
+}
+
+impl<T, const CAPACITY: usize> AsyncSPSC<T, CAPACITY> {
+    pub fn new() -> Self {
+        Self {
+            queue: LockFreeSPSC::new(),
+            consumer: WakerSlot::new(),
+            producer: WakerSlot::new(),
+        }
+    }
+    
+    /// Producer: push, handing the item back if full
+    #[inline(always)]
+    pub fn try_push(&self, item: T) -> Result<(), T> {
+        self.queue.try_push(item)?;
+        self.consumer.wake();
+        Ok(())
+    }
+    
+    /// Consumer: pop (returns None if empty)
+    #[inline(always)]
+    pub fn pop(&self) -> Option<T> {
+        let item = self.queue.pop()?;
+        self.producer.wake();
+        Some(item)
+    }
+    
+    /// Producer: wait for room, then push
+    pub async fn push_async(&self, item: T) {
+        let mut item = Some(item);
+        poll_fn(|cx| {
+            let pending = item.take().expect("polled after completion");
+            match self.try_push(pending) {
+                Ok(()) => return Poll::Ready(()),
+                Err(back) => item = Some(back),
+            }
+            self.producer.register(cx.waker());
+            // The consumer may have popped before the registration was visible
+            match self.try_push(item.take().unwrap()) {
+                Ok(()) => Poll::Ready(()),
+                Err(back) => {
+                    item = Some(back);
+                    Poll::Pending
+                }
+            }
+        })
+        .await
+    }
+    
+    /// Consumer: wait for an item without burning a core
+    pub async fn pop_async(&self) -> T {
+        poll_fn(|cx| {
+            if let Some(item) = self.pop() {
+                return Poll::Ready(item);
+            }
+            self.consumer.register(cx.waker());
+            // The producer may have pushed before the registration was visible
+            match self.pop() {
+                Some(item) => Poll::Ready(item),
+                None => Poll::Pending,
+            }
+        })
+        .await
+    }
+    
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+    
+    #[inline(always)]
+    pub fn size(&self) -> usize {
+        self.queue.size()
+    }
+    
+    #[cfg(feature = "queue_stats")]
+    pub fn stats(&self) -> crate::queue_stats::QueueStats {
+        self.queue.stats()
+    }
+}
+
+impl<T, const CAPACITY: usize> Default for AsyncSPSC<T, CAPACITY> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    
+    #[tokio::test]
+    async fn test_stream_collects_pushed_ticks() {
+        let queue: LockFreeSPSC<MarketTick, 16> = LockFreeSPSC::new();
+        for i in 0..5 {
+            assert!(queue.push(MarketTick { timestamp_ns: i, ..Default::default() }));
+        }
+        
+        let ticks: Vec<MarketTick> = TickStream::new(&queue).take(5).collect().await;
+        let stamps: Vec<i64> = ticks.iter().map(|t| t.timestamp_ns).collect();
+        assert_eq!(stamps, vec![0, 1, 2, 3, 4]);
+    }
+    
+    #[tokio::test]
+    async fn test_stream_waits_through_backoff() {
+        let queue: LockFreeSPSC<MarketTick, 16> = LockFreeSPSC::new();
+        let backoff = Backoff { yields: 2, park: Duration::from_millis(1) };
+        let mut stream = TickStream::with_backoff(&queue, backoff);
+        
+        // Producer shows up after the stream has parked on the timer
+        let (tick, ()) = tokio::join!(stream.next(), async {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            queue.push(MarketTick { timestamp_ns: 42, ..Default::default() });
+        });
+        assert_eq!(tick.unwrap().timestamp_ns, 42);
+    }
+    
+    #[test]
+    fn test_pop_async_woken_by_push_from_thread() {
+        let queue: AsyncSPSC<String, 4> = AsyncSPSC::new();
+        
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                for i in 0..100 {
+                    let mut item = format!("event-{}", i);
+                    while let Err(back) = queue.try_push(item) {
+                        item = back;
+                        std::thread::yield_now();
+                    }
+                    if i % 10 == 0 {
+                        std::thread::sleep(Duration::from_millis(1));
+                    }
+                }
+            });
+            
+            let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+            rt.block_on(async {
+                for i in 0..100 {
+                    assert_eq!(queue.pop_async().await, format!("event-{}", i));
+                }
+            });
+        });
+        assert!(queue.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_push_async_parks_until_consumer_pops() {
+        let queue: AsyncSPSC<u64, 2> = AsyncSPSC::new();
+        queue.push_async(1).await;
+        queue.push_async(2).await;
+        
+        let ((), popped) = tokio::join!(queue.push_async(3), async {
+            tokio::time::sleep(Duration::from_millis(2)).await;
+            queue.pop()
+        });
+        assert_eq!(popped, Some(1));
+        assert_eq!(queue.pop_async().await, 2);
+        assert_eq!(queue.pop_async().await, 3);
+        assert_eq!(queue.size(), 0);
+    }
+}
@@ -0,0 +1,92 @@
+// Primitives used by the lock-free queues
+// Under `RUSTFLAGS="--cfg loom"` these are loom's model-checked versions, so
+// `cargo test --lib loom_` explores every interleaving of push/pop
+
+use std::mem::MaybeUninit;
+
+#[cfg(loom)]
+pub(crate) use loom::sync::atomic::AtomicU64;
+#[cfg(not(loom))]
+pub(crate) use std::sync::atomic::AtomicU64;
+
+#[cfg(loom)]
+type Cell<T> = loom::cell::UnsafeCell<T>;
+#[cfg(not(loom))]
+type Cell<T> = std::cell::UnsafeCell<T>;
+
+/// One ring slot, uninitialized until first written; the queue's index
+/// protocol decides who may touch it and guarantees reads follow a write
+#[repr(transparent)]
+pub(crate) struct Slot<T>(Cell<MaybeUninit<T>>);
+
+impl<T> Slot<T> {
+    pub(crate) fn uninit() -> Self {
+        Self(Cell::new(MaybeUninit::uninit()))
+    }
+    
+    /// Move the value out; the slot counts as uninitialized again.
+    /// Safety: the slot has been written, no `write` runs concurrently, and
+    /// the caller owns the value (nobody else will read it) or `T: Copy`
+    #[inline(always)]
+    pub(crate) unsafe fn read(&self) -> T {
+        #[cfg(loom)]
+        return self.0.with(|ptr| (*ptr).assume_init_read());
+        #[cfg(not(loom))]
+        return (*self.0.get()).assume_init_read();
+    }
+    
+    /// Safety: no concurrent `read` or `write` of this slot
+    #[inline(always)]
+    pub(crate) unsafe fn write(&self, value: T) {
+        #[cfg(loom)]
+        self.0.with_mut(|ptr| {
+            (*ptr).write(value);
+        });
+        #[cfg(not(loom))]
+        {
+            (*self.0.get()).write(value);
+        }
+    }
+    
+    /// Borrow the value in place.
+    /// Safety: the slot has been written and no `read` or `write` of it runs
+    /// while the borrow lives (under loom, only taking the borrow is checked)
+    #[inline(always)]
+    pub(crate) unsafe fn get(&self) -> &T {
+        #[cfg(loom)]
+        return &*self.0.with(|ptr| (*ptr).as_ptr());
+        #[cfg(not(loom))]
+        return (*self.0.get()).assume_init_ref();
+    }
+    
+    /// Start of the slot's storage, for page-level operations
+    #[cfg(not(loom))]
+    #[inline(always)]
+    pub(crate) fn as_byte_ptr(&self) -> *mut u8 {
+        self.0.get() as *mut u8
+    }
+}
+
+/// Busy-wait hint; under loom a yield, so the model schedules the other side
+#[inline(always)]
+pub(crate) fn spin_hint() {
+    #[cfg(loom)]
+    loom::thread::yield_now();
+    #[cfg(not(loom))]
+    std::hint::spin_loop();
+}
+
+/// Keeps `T` on a cache line of its own so indices owned by different
+/// threads don't false-share
+#[derive(Default)]
+#[repr(align(64))]
+pub(crate) struct CachePadded<T>(pub(crate) T);
+
+impl<T> std::ops::Deref for CachePadded<T> {
+    type Target = T;
+    
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
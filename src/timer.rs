@@ -0,0 +1,373 @@
+// Hierarchical timer wheel
+// Order timeouts and scheduled cancels/requotes, polled from the hot loop.
+// Four levels of 64 slots: level 0 slots are one tick wide, each level up is
+// 64 times coarser, so 64^4 ticks (~16.8s at 1µs) are covered directly and
+// later deadlines wait in the top level until they come into range.
+// Scheduling and cancelling are O(1); a timer cascades down at most three
+// times before it fires.
+//
+// Deadlines are UNIX nanoseconds, as from `HiResTimer::unix_now_ns()`.
+
+use std::sync::Arc;
+
+use crate::clock::{self, Clock};
+
+const SLOT_BITS: u32 = 6;
+const SLOTS: usize = 1 << SLOT_BITS;
+const LEVELS: usize = 4;
+const TOP_SHIFT: u32 = SLOT_BITS * (LEVELS as u32 - 1);
+const SPAN: u64 = 1 << (SLOT_BITS * LEVELS as u32);
+// Extra list after the wheel slots: timers due now, waiting for `pop_expired`
+const READY: usize = LEVELS * SLOTS;
+const NIL: u32 = u32::MAX;
+
+/// Identifies a scheduled timer for `cancel`; stale once it fires or is cancelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId {
+    index: u32,
+    generation: u32,
+}
+
+struct Entry<T> {
+    deadline: u64,  // In ticks since `origin_ns`
+    item: Option<T>,
+    list: usize,
+    prev: u32,
+    next: u32,
+    generation: u32,
+}
+
+/// Timer wheel carrying a `T` per timer (an order id, an action enum, or a
+/// boxed callback). Single-threaded: own it from the loop that polls it.
+pub struct TimerWheel<T> {
+    tick_ns: u64,
+    origin_ns: i64,
+    now_tick: u64,
+    entries: Vec<Entry<T>>,
+    free: u32,
+    heads: Box<[u32]>,
+    occupied: [u64; LEVELS],  // Bit per non-empty slot, to skip idle stretches
+    len: usize,
+    clock: Arc<dyn Clock>,
+}
+
+impl<T> TimerWheel<T> {
+    /// Wheel with `tick_ns` resolution whose clock starts at `now_ns`
+    pub fn new(tick_ns: u64, now_ns: i64) -> Self {
+        assert!(tick_ns > 0, "Tick must be positive");
+        Self {
+            tick_ns,
+            origin_ns: now_ns,
+            now_tick: 0,
+            entries: Vec::new(),
+            free: NIL,
+            heads: vec![NIL; READY + 1].into_boxed_slice(),
+            occupied: [0; LEVELS],
+            len: 0,
+            clock: clock::system(),
+        }
+    }
+    
+    /// Clock behind `schedule_after` and `poll_now` (default: `HiResTimer`).
+    /// Pass `clock.now_ns()` to `new` so the wheel starts where the clock is
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    /// Preallocate room for `timers` concurrent timers so scheduling never allocates
+    pub fn with_capacity(mut self, timers: usize) -> Self {
+        self.entries.reserve(timers);
+        self
+    }
+    
+    /// Fire `item` at the first poll at or after `deadline_ns` (up to one
+    /// tick late, never early). Past deadlines fire on the next poll
+    pub fn schedule(&mut self, deadline_ns: i64, item: T) -> TimerId {
+        let since_origin = deadline_ns.saturating_sub(self.origin_ns).max(0) as u64;
+        let deadline = since_origin.div_ceil(self.tick_ns);
+        
+        let index = if self.free != NIL {
+            let index = self.free;
+            let entry = &mut self.entries[index as usize];
+            self.free = entry.next;
+            entry.deadline = deadline;
+            entry.item = Some(item);
+            index
+        } else {
+            assert!(self.entries.len() < NIL as usize, "Too many timers");
+            self.entries.push(Entry { deadline, item: Some(item), list: READY, prev: NIL, next: NIL, generation: 0 });
+            (self.entries.len() - 1) as u32
+        };
+        self.place(index);
+        self.len += 1;
+        TimerId { index, generation: self.entries[index as usize].generation }
+    }
+    
+    /// `schedule` relative to the wheel's clock
+    pub fn schedule_after(&mut self, delay_ns: u64, item: T) -> TimerId {
+        let deadline = self.clock.now_ns().saturating_add(delay_ns.min(i64::MAX as u64) as i64);
+        self.schedule(deadline, item)
+    }
+    
+    /// Remove a pending timer and hand its item back; `None` if it already
+    /// fired or was cancelled
+    pub fn cancel(&mut self, id: TimerId) -> Option<T> {
+        let entry = self.entries.get(id.index as usize)?;
+        if entry.generation != id.generation || entry.item.is_none() {
+            return None;
+        }
+        self.unlink(id.index);
+        Some(self.retire(id.index))
+    }
+    
+    /// Next timer due at `now_ns`, advancing the wheel as needed. Call in a
+    /// loop until `None`; the wheel may be rescheduled between calls
+    #[inline]
+    pub fn pop_expired(&mut self, now_ns: i64) -> Option<T> {
+        let target = (now_ns.saturating_sub(self.origin_ns).max(0) as u64) / self.tick_ns;
+        loop {
+            let head = self.heads[READY];
+            if head != NIL {
+                self.unlink(head);
+                return Some(self.retire(head));
+            }
+            if self.now_tick >= target {
+                return None;
+            }
+            self.advance(target);
+        }
+    }
+    
+    /// `pop_expired` against the wheel's clock
+    #[inline]
+    pub fn poll_now(&mut self) -> Option<T> {
+        self.pop_expired(self.clock.now_ns())
+    }
+    
+    /// Timers scheduled and not yet fired or cancelled
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    
+    // Step toward `target`: one tick, or straight to the next boundary of
+    // the lowest non-empty level, then cascade and collect what is due
+    fn advance(&mut self, target: u64) {
+        let next = match self.occupied.iter().position(|&bits| bits != 0) {
+            None => target,
+            Some(0) => self.now_tick + 1,
+            Some(level) => {
+                let shift = SLOT_BITS * level as u32;
+                ((self.now_tick >> shift) + 1) << shift
+            }
+        };
+        self.now_tick = next.min(target);
+        let now = self.now_tick;
+        
+        // Coarsest boundary first, so its timers can cascade again below
+        for level in (1..LEVELS).rev() {
+            if now & ((1 << (SLOT_BITS * level as u32)) - 1) == 0 {
+                let slot = (now >> (SLOT_BITS * level as u32)) as usize & (SLOTS - 1);
+                self.cascade(level * SLOTS + slot);
+            }
+        }
+        self.cascade(now as usize & (SLOTS - 1));
+    }
+    
+    // Re-place every timer of a slot relative to `now_tick`
+    fn cascade(&mut self, list: usize) {
+        let mut index = std::mem::replace(&mut self.heads[list], NIL);
+        self.occupied[list / SLOTS] &= !(1 << (list % SLOTS));
+        while index != NIL {
+            let next = self.entries[index as usize].next;
+            self.place(index);
+            index = next;
+        }
+    }
+    
+    // Link an entry into the list its deadline belongs to
+    fn place(&mut self, index: u32) {
+        let deadline = self.entries[index as usize].deadline;
+        let list = if deadline <= self.now_tick {
+            READY
+        } else {
+            // Highest 6-bit group where deadline and now differ picks the level
+            let level = ((63 - (deadline ^ self.now_tick).leading_zeros()) / SLOT_BITS) as usize;
+            if level < LEVELS {
+                level * SLOTS + ((deadline >> (SLOT_BITS * level as u32)) as usize & (SLOTS - 1))
+            } else if deadline - self.now_tick < SPAN {
+                // Differs above the wheel but is in range: its top-level slot comes round in time
+                (LEVELS - 1) * SLOTS + ((deadline >> TOP_SHIFT) as usize & (SLOTS - 1))
+            } else {
+                // Out of range: park in the top-level slot visited last, then retry
+                let last = ((self.now_tick >> TOP_SHIFT) as usize).wrapping_sub(1) & (SLOTS - 1);
+                (LEVELS - 1) * SLOTS + last
+            }
+        };
+        if list != READY {
+            self.occupied[list / SLOTS] |= 1 << (list % SLOTS);
+        }
+        
+        let head = self.heads[list];
+        let entry = &mut self.entries[index as usize];
+        entry.list = list;
+        entry.prev = NIL;
+        entry.next = head;
+        if head != NIL {
+            self.entries[head as usize].prev = index;
+        }
+        self.heads[list] = index;
+    }
+    
+    fn unlink(&mut self, index: u32) {
+        let (list, prev, next) = {
+            let entry = &self.entries[index as usize];
+            (entry.list, entry.prev, entry.next)
+        };
+        if prev != NIL {
+            self.entries[prev as usize].next = next;
+        } else {
+            self.heads[list] = next;
+            if next == NIL && list != READY {
+                self.occupied[list / SLOTS] &= !(1 << (list % SLOTS));
+            }
+        }
+        if next != NIL {
+            self.entries[next as usize].prev = prev;
+        }
+    }
+    
+    // Take the item out and put the entry on the free list
+    fn retire(&mut self, index: u32) -> T {
+        let entry = &mut self.entries[index as usize];
+        entry.generation = entry.generation.wrapping_add(1);
+        entry.next = self.free;
+        self.free = index;
+        self.len -= 1;
+        entry.item.take().expect("retired a free timer entry")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const US: i64 = 1_000;
+    
+    fn drain(wheel: &mut TimerWheel<u32>, now_ns: i64) -> Vec<u32> {
+        let mut fired: Vec<u32> = std::iter::from_fn(|| wheel.pop_expired(now_ns)).collect();
+        fired.sort_unstable();
+        fired
+    }
+    
+    #[test]
+    fn test_timers_fire_at_deadline_not_before() {
+        let start = 1_700_000_000_000_000_000;
+        let mut wheel = TimerWheel::new(1_000, start);
+        wheel.schedule(start + 500 * US, 1);      // Cancel order in 500µs
+        wheel.schedule(start + 2_000 * US, 2);    // Requote in 2ms
+        wheel.schedule(start + 90_000 * US, 3);   // Two levels up
+        wheel.schedule(start + 20_000_000_000, 4); // Beyond the wheel's span
+        
+        assert_eq!(drain(&mut wheel, start + 499 * US), Vec::<u32>::new());
+        assert_eq!(drain(&mut wheel, start + 500 * US), vec![1]);
+        assert_eq!(drain(&mut wheel, start + 1_999 * US), Vec::<u32>::new());
+        assert_eq!(drain(&mut wheel, start + 2_000 * US), vec![2]);
+        assert_eq!(drain(&mut wheel, start + 89_999 * US), Vec::<u32>::new());
+        assert_eq!(drain(&mut wheel, start + 90_000 * US), vec![3]);
+        assert_eq!(drain(&mut wheel, start + 19_999_999_999), Vec::<u32>::new());
+        assert_eq!(drain(&mut wheel, start + 20_000_000_000), vec![4]);
+        assert!(wheel.is_empty());
+    }
+    
+    #[test]
+    fn test_poll_now_follows_wheel_clock() {
+        let clock = Arc::new(clock::MockClock::new(1_000 * US));
+        let mut wheel = TimerWheel::new(1_000, clock.now_ns()).with_clock(clock.clone());
+        wheel.schedule_after(250_000, 7);
+        
+        clock.advance_ns(249 * US);
+        assert_eq!(wheel.poll_now(), None);
+        clock.advance_ns(US);
+        assert_eq!(wheel.poll_now(), Some(7));
+    }
+    
+    #[test]
+    fn test_cancel_and_reuse() {
+        let mut wheel = TimerWheel::new(1_000, 0);
+        let a = wheel.schedule(10 * US, 1);
+        let b = wheel.schedule(10 * US, 2);
+        assert_eq!(wheel.len(), 2);
+        
+        assert_eq!(wheel.cancel(a), Some(1));
+        assert_eq!(wheel.cancel(a), None);
+        // The freed entry is reused, but the old id stays dead
+        let c = wheel.schedule(5 * US, 3);
+        assert_eq!(c.index, a.index);
+        assert_eq!(wheel.cancel(a), None);
+        
+        assert_eq!(drain(&mut wheel, 20 * US), vec![2, 3]);
+        assert_eq!(wheel.cancel(b), None);  // Already fired
+        assert!(wheel.is_empty());
+    }
+    
+    #[test]
+    fn test_past_deadline_and_rescheduling_while_draining() {
+        let mut wheel = TimerWheel::new(1_000, 0);
+        wheel.schedule(3 * US, 1);
+        assert_eq!(wheel.pop_expired(5 * US), Some(1));
+        
+        // Overdue timers fire on the very next poll
+        wheel.schedule(-1, 2);
+        wheel.schedule(4 * US, 3);
+        assert_eq!(drain(&mut wheel, 5 * US), vec![2, 3]);
+        
+        let mut fired = Vec::new();
+        wheel.schedule(6 * US, 10);
+        while let Some(n) = wheel.pop_expired(6 * US) {
+            fired.push(n);
+            if n < 13 {
+                wheel.schedule(6 * US, n + 1);  // Chain inside the loop
+            }
+        }
+        assert_eq!(fired, vec![10, 11, 12, 13]);
+    }
+    
+    #[test]
+    fn test_many_timers_in_order() {
+        let mut wheel = TimerWheel::new(1_000, 0).with_capacity(5_000);
+        // Scattered across all levels, polled in uneven steps
+        for i in 0..5_000u32 {
+            let deadline = (i as i64 * 7_919 % 5_000_000) * US;
+            wheel.schedule(deadline, i);
+        }
+        
+        let mut now = 0;
+        let mut fired = 0;
+        while !wheel.is_empty() {
+            now += 37_123 * US;
+            while let Some(i) = wheel.pop_expired(now) {
+                let deadline = (i as i64 * 7_919 % 5_000_000) * US;
+                assert!(deadline <= now, "timer {} fired early", i);
+                assert!(deadline >= now - 37_123 * US, "timer {} fired late", i);
+                fired += 1;
+            }
+        }
+        assert_eq!(fired, 5_000);
+    }
+    
+    #[test]
+    fn test_deadline_just_past_top_level_wrap() {
+        // now and deadline differ above the top level yet are one tick apart
+        let mut wheel = TimerWheel::new(1, 0);
+        let wrap = SPAN as i64;
+        assert_eq!(wheel.pop_expired(wrap - 2), None);
+        wheel.schedule(wrap, 1);
+        assert_eq!(wheel.pop_expired(wrap - 1), None);
+        assert_eq!(wheel.pop_expired(wrap), Some(1));
+    }
+}
@@ -0,0 +1,194 @@
+// Consumer wait strategies for the lock-free queues
+// Latency-critical consumers burn a core; background consumers back off to
+// yielding and then parking so they don't.
+// Also precise sleeps for pacing: the OS sleeps most of the interval and the
+// TSC spin covers the last stretch the scheduler can't hit.
+
+use std::time::{Duration, Instant};
+
+use crate::HiResTimer;
+
+/// Spin-wait hint (`pause` on x86_64): eases the pipeline and the sibling
+/// hyperthread while polling. Same hint as the queues use, so under loom it
+/// yields to the model too
+#[inline(always)]
+pub fn pause() {
+    crate::sync::spin_hint();
+}
+
+/// Below this the whole sleep is spun; above it the OS sleeps all but this
+/// much, which covers typical wake-up overshoot
+pub const SPIN_SLACK_NS: u64 = 100_000;
+
+/// Sleep `ns` nanoseconds, accurate to well under a microsecond. Spins the
+/// final `SPIN_SLACK_NS` (the whole interval if shorter) on the CPU
+#[inline]
+pub fn spin_sleep_ns(ns: u64) {
+    let start = HiResTimer::ticks();
+    if ns > SPIN_SLACK_NS {
+        std::thread::sleep(Duration::from_nanos(ns - SPIN_SLACK_NS));
+    }
+    let ns = ns.min(i64::MAX as u64) as i64;
+    while HiResTimer::ticks_to_ns(HiResTimer::ticks() - start) < ns {
+        pause();
+    }
+}
+
+/// `spin_sleep_ns` until the UNIX time `deadline_ns` (as from
+/// `HiResTimer::now_ns()`), e.g. the next quote refresh slot; returns
+/// at once if it has passed
+#[inline]
+pub fn spin_sleep_until_ns(deadline_ns: i64) {
+    let remaining = deadline_ns.saturating_sub(HiResTimer::now_ns());
+    if remaining > 0 {
+        spin_sleep_ns(remaining as u64);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitStrategy {
+    /// Retry immediately with a spin hint; lowest latency, one full core
+    BusySpin,
+    /// Spin `spins` times, then `yield_now` between attempts
+    SpinThenYield { spins: u32 },
+    /// Spin, then yield `yields` times, then park for up to `park` between
+    /// attempts (producers don't unpark, so `park` bounds the added latency)
+    Park { spins: u32, yields: u32, park: Duration },
+}
+
+impl Default for WaitStrategy {
+    fn default() -> Self {
+        WaitStrategy::SpinThenYield { spins: 128 }
+    }
+}
+
+impl WaitStrategy {
+    /// Call `poll` until it returns `Some`
+    #[inline(always)]
+    pub fn wait<T>(&self, mut poll: impl FnMut() -> Option<T>) -> T {
+        let mut attempt = 0u32;
+        loop {
+            if let Some(item) = poll() {
+                return item;
+            }
+            self.idle(attempt);
+            attempt = attempt.saturating_add(1);
+        }
+    }
+    
+    /// As `wait`, giving up with `None` once `timeout` has passed
+    pub fn wait_timeout<T>(&self, timeout: Duration, mut poll: impl FnMut() -> Option<T>) -> Option<T> {
+        let deadline = Instant::now() + timeout;
+        let mut attempt = 0u32;
+        loop {
+            if let Some(item) = poll() {
+                return Some(item);
+            }
+            // Checked every attempt; a parked attempt may overshoot by up to `park`
+            if Instant::now() >= deadline {
+                return None;
+            }
+            self.idle(attempt);
+            attempt = attempt.saturating_add(1);
+        }
+    }
+    
+    // Back off after `attempt` consecutive empty polls
+    #[inline(always)]
+    fn idle(&self, attempt: u32) {
+        match *self {
+            WaitStrategy::BusySpin => pause(),
+            WaitStrategy::SpinThenYield { spins } => {
+                if attempt < spins {
+                    pause();
+                } else {
+                    std::thread::yield_now();
+                }
+            }
+            WaitStrategy::Park { spins, yields, park } => {
+                if attempt < spins {
+                    pause();
+                } else if attempt - spins < yields {
+                    std::thread::yield_now();
+                } else {
+                    std::thread::park_timeout(park);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mpmc::LockFreeMPMC;
+    use crate::mpsc::MpscRing;
+    use crate::LockFreeSPSC;
+    
+    #[test]
+    fn test_pop_wait_each_strategy() {
+        let strategies = [
+            WaitStrategy::BusySpin,
+            WaitStrategy::SpinThenYield { spins: 16 },
+            WaitStrategy::Park { spins: 16, yields: 16, park: Duration::from_micros(100) },
+        ];
+        for strategy in strategies {
+            let queue: LockFreeSPSC<u64, 64> = LockFreeSPSC::new();
+            std::thread::scope(|s| {
+                s.spawn(|| {
+                    for i in 0..1_000 {
+                        while !queue.push(i) {
+                            std::thread::yield_now();
+                        }
+                        if i % 100 == 0 {
+                            std::thread::sleep(Duration::from_micros(50));
+                        }
+                    }
+                });
+                for i in 0..1_000 {
+                    assert_eq!(queue.pop_wait(strategy), i);
+                }
+            });
+        }
+    }
+    
+    #[test]
+    fn test_spin_sleep_not_short() {
+        for ns in [0, 750, 20_000, 1_500_000] {
+            let start = Instant::now();
+            spin_sleep_ns(ns);
+            let slept = start.elapsed();
+            // Slack for TSC calibration error against the monotonic clock
+            assert!(slept >= Duration::from_nanos(ns - ns / 1_000), "{} ns slept {:?}", ns, slept);
+            // Generous ceiling: the box running tests may be busy
+            assert!(slept < Duration::from_nanos(ns) + Duration::from_millis(50));
+        }
+        
+        let deadline = HiResTimer::now_ns() + 300_000;
+        spin_sleep_until_ns(deadline);
+        assert!(HiResTimer::now_ns() >= deadline);
+        spin_sleep_until_ns(deadline - 1_000_000_000);  // Past: returns at once
+    }
+    
+    #[test]
+    fn test_pop_wait_timeout_gives_up() {
+        let strategy = WaitStrategy::Park { spins: 8, yields: 8, park: Duration::from_millis(1) };
+        let queue: LockFreeSPSC<u64, 8> = LockFreeSPSC::new();
+        let start = Instant::now();
+        assert_eq!(queue.pop_wait_timeout(strategy, Duration::from_millis(5)), None);
+        assert!(start.elapsed() >= Duration::from_millis(5));
+        
+        queue.push(3);
+        assert_eq!(queue.pop_wait_timeout(strategy, Duration::ZERO), Some(3));
+        
+        let mpmc: LockFreeMPMC<u64, 8> = LockFreeMPMC::new();
+        mpmc.push(4);
+        assert_eq!(mpmc.pop_wait(WaitStrategy::BusySpin), 4);
+        assert_eq!(mpmc.pop_wait_timeout(strategy, Duration::from_millis(1)), None);
+        
+        let (producer, mut consumer) = MpscRing::<u64, 8>::channel();
+        producer.push(5);
+        assert_eq!(consumer.pop_wait(WaitStrategy::default()), 5);
+        assert_eq!(consumer.pop_wait_timeout(strategy, Duration::from_millis(1)), None);
+    }
+}
@@ -0,0 +1,32 @@
+// Panic containment at the exported C ABI
+// Its own test binary: a caught panic latches process-wide and halts every
+// risk control, which would leak into the other FFI tests.
+
+use hft_rust_core::ffi::*;
+use hft_rust_core::{FfiStatus, Order};
+
+#[test]
+fn panic_in_entry_point_fails_closed() {
+    let rc = rust_risk_control_new(100);
+    let mut order = Order::default();
+    order.order_id = 1;
+    order.price = 100.0;
+    order.quantity = 10;
+    unsafe {
+        assert!(rust_risk_control_check_pre_trade(rc, &order, 0));
+        assert!(!rust_ffi_panicked());
+        
+        // capacity * size_of::<MarketTick>() overflows: `Vec` panics, the guard returns null
+        assert!(rust_queue_new(1 << 60).is_null());
+        assert!(rust_ffi_panicked());
+        let message = std::ffi::CStr::from_ptr(rust_last_error()).to_string_lossy().into_owned();
+        assert!(message.starts_with("panic at FFI boundary"), "{message}");
+        // Entry points themselves keep working
+        assert_eq!(rust_queue_push_ex(std::ptr::null_mut(), std::ptr::null()), FfiStatus::NullPointer);
+        
+        // Every risk control now refuses trading
+        assert!(rust_risk_control_is_halted(rc));
+        assert!(!rust_risk_control_check_pre_trade(rc, &order, 0));
+        rust_risk_control_free(rc);
+    }
+}
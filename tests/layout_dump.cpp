@@ -0,0 +1,56 @@
+// FFI layout dump (C++ side); output must match tests/ffi_layout.txt
+// Field names follow the Rust structs so the two dumps diff cleanly.
+// Built and diffed by scripts/check_layout.sh
+
+#include "common_types.hpp"
+#include <cstddef>
+#include <cstdio>
+
+#define DUMP_STRUCT(T) \
+    std::printf("%s size=%zu align=%zu\n", #T, sizeof(T), alignof(T))
+#define DUMP_FIELD(T, field, name) \
+    std::printf("%s.%s offset=%zu size=%zu\n", #T, name, offsetof(T, field), sizeof(T::field))
+
+int main() {
+    using namespace hft;
+
+    DUMP_STRUCT(MarketTick);
+    DUMP_FIELD(MarketTick, timestamp, "timestamp_ns");
+    DUMP_FIELD(MarketTick, bid_price, "bid_price");
+    DUMP_FIELD(MarketTick, ask_price, "ask_price");
+    DUMP_FIELD(MarketTick, mid_price, "mid_price");
+    DUMP_FIELD(MarketTick, bid_size, "bid_size");
+    DUMP_FIELD(MarketTick, ask_size, "ask_size");
+    DUMP_FIELD(MarketTick, trade_volume, "trade_volume");
+    DUMP_FIELD(MarketTick, trade_side, "trade_side");
+    DUMP_FIELD(MarketTick, asset_id, "asset_id");
+    DUMP_FIELD(MarketTick, depth_levels, "depth_levels");
+    DUMP_FIELD(MarketTick, bid_prices, "bid_prices");
+    DUMP_FIELD(MarketTick, ask_prices, "ask_prices");
+    DUMP_FIELD(MarketTick, bid_sizes, "bid_sizes");
+    DUMP_FIELD(MarketTick, ask_sizes, "ask_sizes");
+
+    DUMP_STRUCT(Order);
+    DUMP_FIELD(Order, order_id, "order_id");
+    DUMP_FIELD(Order, asset_id, "asset_id");
+    DUMP_FIELD(Order, side, "side");
+    DUMP_FIELD(Order, price, "price");
+    DUMP_FIELD(Order, quantity, "quantity");
+    DUMP_FIELD(Order, submit_time, "submit_time_ns");
+    DUMP_FIELD(Order, venue_id, "venue_id");
+    DUMP_FIELD(Order, is_active, "is_active");
+
+    DUMP_STRUCT(ExecutionReport);
+    DUMP_FIELD(ExecutionReport, order_id, "order_id");
+    DUMP_FIELD(ExecutionReport, exec_id, "exec_id");
+    DUMP_FIELD(ExecutionReport, asset_id, "asset_id");
+    DUMP_FIELD(ExecutionReport, side, "side");
+    DUMP_FIELD(ExecutionReport, exec_type, "exec_type");
+    DUMP_FIELD(ExecutionReport, venue_id, "venue_id");
+    DUMP_FIELD(ExecutionReport, last_price, "last_price");
+    DUMP_FIELD(ExecutionReport, last_quantity, "last_quantity");
+    DUMP_FIELD(ExecutionReport, cum_quantity, "cum_quantity");
+    DUMP_FIELD(ExecutionReport, leaves_quantity, "leaves_quantity");
+    DUMP_FIELD(ExecutionReport, transact_time_ns, "transact_time_ns");
+    return 0;
+}
@@ -0,0 +1,48 @@
+// FFI layout dump, diffed against the C++ side in CI
+// Prints size, alignment and every public field's offset/size of the structs
+// shared with `include/common_types.hpp`, in the format `tests/layout_dump.cpp`
+// prints. Both must match `tests/ffi_layout.txt`; see `scripts/check_layout.sh`.
+// Set HFT_LAYOUT_DUMP=<path> to also write the dump out.
+
+use std::fmt::Write;
+use std::mem::{align_of, offset_of, size_of};
+
+use hft_rust_core::{ExecutionReport, MarketTick, Order};
+
+fn field_size<T, F>(_: impl Fn(&T) -> &F) -> usize {
+    size_of::<F>()
+}
+
+macro_rules! dump {
+    ($out:expr, $ty:ident { $($field:ident),* $(,)? }) => {{
+        writeln!($out, "{} size={} align={}", stringify!($ty), size_of::<$ty>(), align_of::<$ty>()).unwrap();
+        $(
+            let size = field_size(|value: &$ty| &value.$field);
+            writeln!($out, "{}.{} offset={} size={}", stringify!($ty), stringify!($field), offset_of!($ty, $field), size).unwrap();
+        )*
+    }};
+}
+
+fn layout() -> String {
+    let mut out = String::new();
+    dump!(out, MarketTick {
+        timestamp_ns, bid_price, ask_price, mid_price, bid_size, ask_size, trade_volume,
+        trade_side, asset_id, depth_levels, bid_prices, ask_prices, bid_sizes, ask_sizes,
+    });
+    dump!(out, Order { order_id, asset_id, side, price, quantity, submit_time_ns, venue_id, is_active });
+    dump!(out, ExecutionReport {
+        order_id, exec_id, asset_id, side, exec_type, venue_id, last_price,
+        last_quantity, cum_quantity, leaves_quantity, transact_time_ns,
+    });
+    out
+}
+
+#[test]
+fn layout_matches_golden() {
+    let dump = layout();
+    print!("{dump}");
+    if let Ok(path) = std::env::var("HFT_LAYOUT_DUMP") {
+        std::fs::write(path, &dump).expect("write layout dump");
+    }
+    assert_eq!(dump, include_str!("ffi_layout.txt"), "Update tests/ffi_layout.txt and the C++ structs together");
+}
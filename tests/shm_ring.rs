@@ -0,0 +1,68 @@
+// Cross-process ShmRing test: the test binary re-executes itself as the consumer
+
+#![cfg(target_os = "linux")]
+
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use hft_rust_core::shm::ShmRing;
+use hft_rust_core::MarketTick;
+
+const CHILD_ENV: &str = "HFT_SHM_RING_CHILD";
+const TICKS: i64 = 1000;
+
+fn tick(i: i64) -> MarketTick {
+    let mut t = MarketTick::default();
+    t.timestamp_ns = i;
+    t.bid_price = 100.0 + i as f64;
+    t.ask_price = 100.5 + i as f64;
+    t.asset_id = 7;
+    t
+}
+
+#[test]
+fn child_consumer() {
+    // No-op unless launched by cross_process_roundtrip
+    let Ok(name) = std::env::var(CHILD_ENV) else { return };
+    
+    let ring = ShmRing::attach(&name).expect("attach");
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut next = 0;
+    
+    while next < TICKS {
+        assert!(Instant::now() < deadline, "timed out at tick {}", next);
+        match ring.read_tick().expect("read") {
+            Some(t) => {
+                assert_eq!(t.timestamp_ns, next);
+                assert_eq!(t.bid_price, 100.0 + next as f64);
+                assert_eq!(t.asset_id, 7);
+                next += 1;
+            }
+            None => std::thread::yield_now(),
+        }
+    }
+}
+
+#[test]
+fn cross_process_roundtrip() {
+    let name = format!("hft_ring_it_{}", std::process::id());
+    let ring = ShmRing::create(&name, 64).expect("create");
+    
+    let mut child = Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "child_consumer", "--nocapture", "--test-threads=1"])
+        .env(CHILD_ENV, &name)
+        .spawn()
+        .expect("spawn consumer");
+    
+    // Ring is smaller than the stream, so this also exercises wrap-around and Full
+    let deadline = Instant::now() + Duration::from_secs(10);
+    for i in 0..TICKS {
+        while ring.write_tick(&tick(i)).is_err() {
+            assert!(Instant::now() < deadline, "consumer stalled at tick {}", i);
+            std::thread::yield_now();
+        }
+    }
+    
+    assert!(child.wait().expect("wait").success());
+    assert!(ring.is_empty());
+}